@@ -1,99 +1,477 @@
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
 use chrono::DateTime;
-use model::AssetType;
+use reqwest::StatusCode;
 
 use crate::{BoothInfoFetchError, definitions::BoothJsonSchema};
 
-use super::{cache::BoothCache, client::get_reqwest_client, definitions::BoothAssetInfo};
+use super::{
+    cache::{self, BoothCache, BoothCacheConfig, CacheLookup},
+    classifier::estimate_asset_type_from_category,
+    client::get_reqwest_client,
+    definitions::{BoothAssetInfo, BoothVariation},
+    limiter::RateLimiter,
+    retry::{self, RetryPolicy},
+    shop::{self, BoothShopItem},
+};
+
+const DEFAULT_BASE_URL: &str = "https://booth.pm";
+/// 出品者が付けた名前を取得するための言語 (BOOTH の既定の言語)
+const ORIGINAL_LANGUAGE: &str = "ja";
+
+/// 一度に送信できるリクエストの数と、その後 1 秒あたりに送信できるリクエストの数
+const RATE_LIMIT_CAPACITY: u32 = 5;
+const RATE_LIMIT_PER_SECOND: f64 = 1.0;
 
+/// ショップのアイテム一覧を取得する最大のページ数
+const MAX_SHOP_PAGES: u32 = 50;
+
+/// 複製したものはキャッシュと頻度の制限を共有する
+///
+/// 複数の箇所から使う場合は、通信の間ロックを保持し続けないよう複製してから取得する
+#[derive(Clone)]
 pub struct BoothFetcher {
-    cache: BoothCache,
-    reqwest_client: reqwest::Client,
+    cache: Arc<Mutex<BoothCache>>,
+    /// 裏で取得し直している最中のアイテム
+    revalidating: Arc<Mutex<HashSet<u64>>>,
+    api: BoothApi,
+    /// BOOTH に問い合わせず、キャッシュだけを使う
+    offline_mode: bool,
+    /// 表示言語での名前を取得するための、BOOTH の言語コード
+    language: String,
 }
 
 impl BoothFetcher {
     pub fn new<S: AsRef<str>>(version: S) -> Self {
-        let reqwest_client = get_reqwest_client(version).expect("Failed to create reqwest client");
+        Self::builder(version).build()
+    }
 
-        BoothFetcher {
-            cache: BoothCache::new(),
-            reqwest_client,
+    pub fn builder<S: AsRef<str>>(version: S) -> BoothFetcherBuilder {
+        BoothFetcherBuilder {
+            version: version.as_ref().to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            shop_base_url: None,
+            rate_limit: (RATE_LIMIT_CAPACITY, RATE_LIMIT_PER_SECOND),
+            retry_policy: RetryPolicy::default(),
         }
     }
 
-    pub async fn fetch(&mut self, id: u64) -> Result<BoothAssetInfo, BoothInfoFetchError> {
-        if let Some(cached_result) = self.cache.get(id) {
-            return Ok(cached_result);
+    /// 取得結果をファイルにキャッシュし、再起動後も使えるようにする
+    pub fn use_persistent_cache<P: AsRef<Path>>(&mut self, path: P, config: BoothCacheConfig) {
+        *self.cache.lock().unwrap() = BoothCache::open(path, config);
+    }
+
+    pub fn set_cache_config(&mut self, config: BoothCacheConfig) {
+        self.cache.lock().unwrap().set_config(config);
+    }
+
+    /// `LanguageCode::booth_lang_code` の値を渡す
+    pub fn set_language<S: AsRef<str>>(&mut self, language: S) {
+        self.language = language.as_ref().to_string();
+    }
+
+    pub fn set_offline_mode(&mut self, offline_mode: bool) {
+        self.offline_mode = offline_mode;
+    }
+
+    pub fn is_offline_mode(&self) -> bool {
+        self.offline_mode
+    }
+
+    pub fn purge_cache(&mut self) -> Result<(), std::io::Error> {
+        self.cache.lock().unwrap().purge()
+    }
+
+    /// BOOTH に問い合わせずに、キャッシュされている情報を返す (期限切れでも猶予期間内であれば返す)
+    pub fn get_cached(&self, id: u64) -> Option<BoothAssetInfo> {
+        match self.cache.lock().unwrap().lookup(id, &self.language) {
+            CacheLookup::Fresh(info) | CacheLookup::Stale(info) => Some(info),
+            CacheLookup::Miss => None,
         }
+    }
 
-        let url = construct_api_url(id);
-        let response = self.reqwest_client.get(&url).send().await?;
+    /// キャッシュが有効期限内であればそれを返し、期限切れでも猶予期間内であれば返しつつ裏で取得し直す
+    ///
+    /// オフラインの場合や BOOTH に接続できなかった場合は、期限切れのキャッシュも使う
+    pub async fn fetch(&self, id: u64) -> Result<BoothAssetInfo, BoothInfoFetchError> {
+        if !self.offline_mode {
+            let lookup = self.cache.lock().unwrap().lookup(id, &self.language);
 
-        if response.status().as_u16() == 404 {
-            return Err(BoothInfoFetchError::NotFound(id));
+            match lookup {
+                CacheLookup::Fresh(info) => return Ok(info),
+                CacheLookup::Stale(info) => {
+                    self.revalidate_in_background(id);
+                    return Ok(info);
+                }
+                CacheLookup::Miss => {}
+            }
         }
 
-        let body = response.text().await?;
-        self.parse_response_and_process(&body)
+        self.refresh(id).await
     }
 
-    fn parse_response_and_process(
-        &mut self,
-        body: &str,
-    ) -> Result<BoothAssetInfo, BoothInfoFetchError> {
-        let response: BoothJsonSchema = serde_json::from_str(body)?;
-
-        let id = response.id;
-        let image_urls: Vec<String> = response.images.into_iter().map(|i| i.original).collect();
-        let published_at = DateTime::parse_from_rfc3339(&response.published_at)?.timestamp_millis();
+    /// キャッシュが有効期限内でも BOOTH から取得し直し、キャッシュを更新する
+    ///
+    /// オフラインの場合や BOOTH に接続できなかった場合は、期限切れのキャッシュも使う
+    pub async fn refresh(&self, id: u64) -> Result<BoothAssetInfo, BoothInfoFetchError> {
+        if self.offline_mode {
+            return self
+                .cache
+                .lock()
+                .unwrap()
+                .get_even_if_expired(id, &self.language)
+                .ok_or(BoothInfoFetchError::Offline(id));
+        }
 
-        let estimated_asset_type = estimate_asset_type_from_category(response.category.id);
+        let result = match self.api.fetch(id, &self.language).await {
+            Ok(result) => result,
+            Err(e) if e.is_transient() => {
+                let cached = self
+                    .cache
+                    .lock()
+                    .unwrap()
+                    .get_even_if_expired(id, &self.language);
 
-        let result = BoothAssetInfo {
-            id,
-            name: response.name,
-            creator: response.shop.name,
-            image_urls,
-            published_at,
-            estimated_asset_type,
+                return match cached {
+                    Some(info) => {
+                        log::warn!("Using expired BOOTH cache (id = {}): {}", id, e);
+                        Ok(info)
+                    }
+                    None => Err(e),
+                };
+            }
+            Err(e) => return Err(e),
         };
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(id, &self.language, result.clone());
+        cache::schedule_save(&self.cache);
 
-        self.cache.insert(id, result.clone());
         Ok(result)
     }
+
+    /// ショップに出品されているアイテムを、すべてのページから取得する
+    ///
+    /// アイテムの情報と異なり、一覧はキャッシュしない
+    pub async fn fetch_shop_items(
+        &self,
+        shop_subdomain: &str,
+    ) -> Result<Vec<BoothShopItem>, BoothInfoFetchError> {
+        if self.offline_mode {
+            return Err(BoothInfoFetchError::ShopOffline(shop_subdomain.to_string()));
+        }
+
+        let mut items: Vec<BoothShopItem> = vec![];
+
+        for page in 1..=MAX_SHOP_PAGES {
+            let Some(page_items) = self.api.fetch_shop_page(shop_subdomain, page).await? else {
+                if page == 1 {
+                    return Err(BoothInfoFetchError::ShopNotFound(
+                        shop_subdomain.to_string(),
+                    ));
+                }
+                break;
+            };
+
+            // 最後のページを超えると、空の一覧か最後のページと同じ内容が返る
+            let new_items: Vec<BoothShopItem> = page_items
+                .into_iter()
+                .filter(|item| !items.iter().any(|existing| existing.id == item.id))
+                .collect();
+            if new_items.is_empty() {
+                break;
+            }
+
+            items.extend(new_items);
+        }
+
+        Ok(items)
+    }
+
+    fn revalidate_in_background(&self, id: u64) {
+        if !self.revalidating.lock().unwrap().insert(id) {
+            return;
+        }
+
+        let api = self.api.clone();
+        let cache = self.cache.clone();
+        let revalidating = self.revalidating.clone();
+        let language = self.language.clone();
+
+        tokio::spawn(async move {
+            match api.fetch(id, &language).await {
+                Ok(info) => {
+                    cache.lock().unwrap().insert(id, &language, info);
+                    cache::schedule_save(&cache);
+                }
+                Err(e) => log::warn!("Failed to revalidate BOOTH cache (id = {}): {}", id, e),
+            }
+
+            revalidating.lock().unwrap().remove(&id);
+        });
+    }
 }
 
-fn estimate_asset_type_from_category(category_id: i32) -> Option<AssetType> {
-    match category_id {
-        208 //   3Dキャラクター
-        => Some(AssetType::Avatar),
-        209 | // 3D衣装
-        217 | // 3D装飾品
-        210 | // 3D小道具
-        214 | // 3Dテクスチャ
-        215 | // 3Dツール・システム
-        216 | // 3Dモーション・アニメーション
-        127 //   3Dモデル（その他）
-        => Some(AssetType::AvatarWearable),
-        211 //   3D環境・ワールド
-        => Some(AssetType::WorldObject),
-        _ => None,
+/// 接続先や頻度の制限を変えて `BoothFetcher` を作成する (主にテスト用)
+pub struct BoothFetcherBuilder {
+    version: String,
+    base_url: String,
+    shop_base_url: Option<String>,
+    rate_limit: (u32, f64),
+    retry_policy: RetryPolicy,
+}
+
+impl BoothFetcherBuilder {
+    /// BOOTH の代わりに問い合わせる URL (例: `http://127.0.0.1:8080`)
+    pub fn base_url<S: AsRef<str>>(mut self, base_url: S) -> Self {
+        self.base_url = base_url.as_ref().trim_end_matches('/').to_string();
+        self
     }
+
+    /// ショップのページの代わりに問い合わせる URL (`{URL}/{サブドメイン}/items` に問い合わせる)
+    pub fn shop_base_url<S: AsRef<str>>(mut self, shop_base_url: S) -> Self {
+        self.shop_base_url = Some(shop_base_url.as_ref().trim_end_matches('/').to_string());
+        self
+    }
+
+    pub fn rate_limit(mut self, capacity: u32, per_second: f64) -> Self {
+        self.rate_limit = (capacity, per_second);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn build(self) -> BoothFetcher {
+        let reqwest_client =
+            get_reqwest_client(&self.version).expect("Failed to create reqwest client");
+        let (capacity, per_second) = self.rate_limit;
+
+        BoothFetcher {
+            cache: Arc::new(Mutex::new(BoothCache::new())),
+            revalidating: Arc::new(Mutex::new(HashSet::new())),
+            api: BoothApi {
+                client: reqwest_client,
+                limiter: Arc::new(RateLimiter::new(capacity, per_second)),
+                retry_policy: self.retry_policy,
+                base_url: self.base_url,
+                shop_base_url: self.shop_base_url,
+            },
+            offline_mode: false,
+            language: ORIGINAL_LANGUAGE.to_string(),
+        }
+    }
+}
+
+/// BOOTH の API へのリクエストを、頻度を制限しつつ送信する
+#[derive(Clone)]
+struct BoothApi {
+    client: reqwest::Client,
+    /// 裏での取得とも共有する
+    limiter: Arc<RateLimiter>,
+    retry_policy: RetryPolicy,
+    base_url: String,
+    /// `None` の場合は各ショップのサブドメインに問い合わせる
+    shop_base_url: Option<String>,
+}
+
+impl BoothApi {
+    /// 出品者が付けた名前を含む情報を取得し、言語が異なる場合はその言語での名前も取得する
+    ///
+    /// 表示言語での取得に失敗しても、元の情報は返す
+    async fn fetch(&self, id: u64, language: &str) -> Result<BoothAssetInfo, BoothInfoFetchError> {
+        let mut info = self.fetch_in(id, ORIGINAL_LANGUAGE).await?;
+
+        if language == ORIGINAL_LANGUAGE {
+            return Ok(info);
+        }
+
+        match self.fetch_in(id, language).await {
+            Ok(localized) => info.localized_name = Some(localized.name),
+            Err(e) => log::warn!(
+                "Failed to fetch localized BOOTH item (id = {}, language = {}): {}",
+                id,
+                language,
+                e
+            ),
+        }
+
+        Ok(info)
+    }
+
+    async fn fetch_in(
+        &self,
+        id: u64,
+        language: &str,
+    ) -> Result<BoothAssetInfo, BoothInfoFetchError> {
+        let url = construct_api_url(&self.base_url, language, id);
+
+        match self.get(&url).await? {
+            Some(body) => parse_response(&body),
+            None => Err(BoothInfoFetchError::NotFound(id)),
+        }
+    }
+
+    /// ショップのアイテム一覧の 1 ページを取得する。ショップが存在しない場合は `None` を返す
+    async fn fetch_shop_page(
+        &self,
+        shop_subdomain: &str,
+        page: u32,
+    ) -> Result<Option<Vec<BoothShopItem>>, BoothInfoFetchError> {
+        let url = construct_shop_url(self.shop_base_url.as_deref(), shop_subdomain, page);
+
+        Ok(self
+            .get(&url)
+            .await?
+            .map(|body| shop::parse_shop_items(&body, shop_subdomain)))
+    }
+
+    /// 混雑している場合は待ってから再試行し、本文を返す。見つからなかった場合は `None` を返す
+    async fn get(&self, url: &str) -> Result<Option<String>, BoothInfoFetchError> {
+        let mut attempt = 0;
+
+        loop {
+            self.limiter.acquire().await;
+
+            let retry_after = match self.client.get(url).send().await {
+                Ok(response) => {
+                    let status = response.status();
+
+                    if status == StatusCode::NOT_FOUND {
+                        return Ok(None);
+                    }
+
+                    if status.is_success() {
+                        return Ok(Some(response.text().await?));
+                    }
+
+                    if !retry::is_retryable_status(status)
+                        || attempt >= self.retry_policy.max_retries
+                    {
+                        return Err(BoothInfoFetchError::UnexpectedStatus(status.as_u16()));
+                    }
+
+                    retry::parse_retry_after(&response)
+                }
+                Err(e) => {
+                    if !retry::is_retryable_error(&e) || attempt >= self.retry_policy.max_retries {
+                        return Err(e.into());
+                    }
+
+                    None
+                }
+            };
+
+            let delay = self.retry_policy.delay(attempt, retry_after);
+            log::warn!(
+                "Retrying BOOTH request in {:?} (url = {}, attempt = {})",
+                delay,
+                url,
+                attempt + 1
+            );
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn parse_response(body: &str) -> Result<BoothAssetInfo, BoothInfoFetchError> {
+    let response: BoothJsonSchema = serde_json::from_str(body)?;
+
+    let id = response.id;
+    let image_urls: Vec<String> = response.images.into_iter().map(|i| i.original).collect();
+    let published_at = DateTime::parse_from_rfc3339(&response.published_at)?.timestamp_millis();
+
+    let category_id = response.category.id;
+    let estimated_asset_type = estimate_asset_type_from_category(category_id);
+
+    let variations = response
+        .variations
+        .into_iter()
+        .map(|variation| BoothVariation {
+            id: variation.id,
+            name: variation.name,
+            price: variation.price,
+            downloadable_files: variation
+                .downloadable
+                .map(|downloadable| {
+                    downloadable
+                        .no_musics
+                        .into_iter()
+                        .chain(downloadable.musics)
+                        .map(|file| file.name)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(BoothAssetInfo {
+        id,
+        name: response.name,
+        localized_name: None,
+        creator: response.shop.name,
+        image_urls,
+        published_at,
+        estimated_asset_type,
+        category_id,
+        price: response.price,
+        description: response.description,
+        tags: response.tags.into_iter().map(|tag| tag.name).collect(),
+        shop_url: response.shop.url,
+        shop_subdomain: response.shop.subdomain,
+        variations,
+    })
 }
 
-fn construct_api_url(id: u64) -> String {
-    format!("https://booth.pm/ja/items/{}.json", id)
+fn construct_api_url(base_url: &str, language: &str, id: u64) -> String {
+    format!("{}/{}/items/{}.json", base_url, language, id)
+}
+
+fn construct_shop_url(shop_base_url: Option<&str>, shop_subdomain: &str, page: u32) -> String {
+    match shop_base_url {
+        Some(base_url) => format!("{}/{}/items?page={}", base_url, shop_subdomain, page),
+        None => format!("https://{}.booth.pm/items?page={}", shop_subdomain, page),
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use model::{AssetType, preference::BoothNameSource};
+
+    use crate::test_server::{MockResponse, MockServer};
+
     use super::*;
 
+    const FIXTURE_ID: u64 = 6641548;
+    const FIXTURE: &str = include_str!("../test/6641548.json");
+
+    fn create_fetcher(server: &MockServer) -> BoothFetcher {
+        BoothFetcher::builder("test")
+            .base_url(server.base_url())
+            .retry_policy(RetryPolicy {
+                max_retries: 2,
+                base_delay: Duration::from_millis(10),
+                max_delay: Duration::from_millis(50),
+            })
+            .build()
+    }
+
     #[test]
-    fn test_parse_response_and_process() {
-        let mut fetcher = BoothFetcher::new("0.0.0+cargo-test");
+    fn test_parse_response() {
         let body = include_str!("../test/6641548.json");
 
-        let result = fetcher.parse_response_and_process(body).unwrap();
+        let result = parse_response(body).unwrap();
 
         assert_eq!(result.id, 6641548);
         assert_eq!(result.name, "KonoAsset - VRChat向けアセット管理ツール");
@@ -115,39 +493,257 @@ mod tests {
         );
         assert_eq!(result.published_at, 1740651821000);
         assert_eq!(result.estimated_asset_type, Some(AssetType::AvatarWearable));
+        assert_eq!(result.category_id, 215);
+        assert_eq!(result.price, "¥ 0");
+        assert!(
+            result
+                .description
+                .starts_with("アバター改変をしているときに")
+        );
+        assert_eq!(result.tags, vec!["VRChat"]);
+        assert_eq!(result.shop_url, "https://silolab.booth.pm/");
+        assert_eq!(result.shop_subdomain, "silolab");
+        assert_eq!(
+            result.variations,
+            vec![BoothVariation {
+                id: 11181441,
+                name: None,
+                price: 0,
+                downloadable_files: vec!["KonoAsset_v1.2.5.zip".into()],
+            }]
+        );
+        assert_eq!(
+            result.downloadable_file_names(),
+            vec!["KonoAsset_v1.2.5.zip"]
+        );
     }
 
     #[test]
-    fn test_estimate_asset_type() {
-        // 3Dキャラクター
+    fn test_construct_api_url() {
+        assert_eq!(
+            construct_api_url(DEFAULT_BASE_URL, ORIGINAL_LANGUAGE, 0),
+            "https://booth.pm/ja/items/0.json"
+        );
+        assert_eq!(
+            construct_api_url(DEFAULT_BASE_URL, ORIGINAL_LANGUAGE, 1),
+            "https://booth.pm/ja/items/1.json"
+        );
+        assert_eq!(
+            construct_api_url(DEFAULT_BASE_URL, ORIGINAL_LANGUAGE, 1234567890),
+            "https://booth.pm/ja/items/1234567890.json"
+        );
         assert_eq!(
-            estimate_asset_type_from_category(208),
-            Some(AssetType::Avatar)
+            construct_api_url(DEFAULT_BASE_URL, "zh-cn", 1),
+            "https://booth.pm/zh-cn/items/1.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_fixture_server() {
+        let server = MockServer::with_fixtures();
+        let fetcher = BoothFetcher::builder("test")
+            .base_url(format!("{}/", server.base_url()))
+            .build();
+
+        let result = fetcher.fetch(FIXTURE_ID).await.unwrap();
+        assert_eq!(result, parse_response(FIXTURE).unwrap());
+
+        // 2 回目はキャッシュを使う
+        fetcher.fetch(FIXTURE_ID).await.unwrap();
+        assert_eq!(server.requests(), vec!["/ja/items/6641548.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_localized_name() {
+        let server = MockServer::with_fixtures();
+        server.route(
+            "/en/items/6641548.json",
+            MockResponse::new(
+                200,
+                FIXTURE.replace(
+                    "KonoAsset - VRChat向けアセット管理ツール",
+                    "KonoAsset - Asset management tool for VRChat",
+                ),
+            ),
         );
 
-        // 3D衣装
+        let mut fetcher = create_fetcher(&server);
+        fetcher.set_language("en");
+
+        let result = fetcher.fetch(FIXTURE_ID).await.unwrap();
+        assert_eq!(result.name, "KonoAsset - VRChat向けアセット管理ツール");
         assert_eq!(
-            estimate_asset_type_from_category(209),
-            Some(AssetType::AvatarWearable)
+            result.name_for(BoothNameSource::Localized),
+            "KonoAsset - Asset management tool for VRChat"
         );
+        assert_eq!(
+            result.name_for(BoothNameSource::Original),
+            "KonoAsset - VRChat向けアセット管理ツール"
+        );
+
+        // 表示言語での取得に失敗した場合は、元の名前だけを返す
+        fetcher.set_language("zh-cn");
 
-        // 3D環境・ワールド
+        let result = fetcher.fetch(FIXTURE_ID).await.unwrap();
+        assert_eq!(result.localized_name, None);
         assert_eq!(
-            estimate_asset_type_from_category(211),
-            Some(AssetType::WorldObject)
+            result.name_for(BoothNameSource::Localized),
+            "KonoAsset - VRChat向けアセット管理ツール"
         );
 
-        // 不明なカテゴリ
-        assert_eq!(estimate_asset_type_from_category(0), None);
+        assert_eq!(
+            server.requests(),
+            vec![
+                "/ja/items/6641548.json",
+                "/en/items/6641548.json",
+                "/ja/items/6641548.json",
+                "/zh-cn/items/6641548.json",
+            ]
+        );
     }
 
-    #[test]
-    fn test_construct_api_url() {
-        assert_eq!(construct_api_url(0), "https://booth.pm/ja/items/0.json");
-        assert_eq!(construct_api_url(1), "https://booth.pm/ja/items/1.json");
+    #[tokio::test]
+    async fn test_fetch_retries_transient_errors() {
+        let server = MockServer::start();
+        server.push(MockResponse::new(503, "Service Unavailable"));
+        server.push(MockResponse::new(429, "Too Many Requests").with_header("Retry-After", "0"));
+        server.push(MockResponse::new(200, FIXTURE));
+
+        let fetcher = create_fetcher(&server);
+        let result = fetcher.fetch(FIXTURE_ID).await.unwrap();
+
+        assert_eq!(result.id, FIXTURE_ID);
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(server.requests()[0], "/ja/items/6641548.json");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_gives_up_after_max_retries() {
+        let server = MockServer::start();
+        for _ in 0..3 {
+            server.push(MockResponse::new(500, "Internal Server Error"));
+        }
+
+        let fetcher = create_fetcher(&server);
+        let result = fetcher.fetch(FIXTURE_ID).await;
+
+        assert!(matches!(
+            result,
+            Err(BoothInfoFetchError::UnexpectedStatus(500))
+        ));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_does_not_retry_not_found() {
+        let server = MockServer::start();
+
+        let fetcher = create_fetcher(&server);
+        let result = fetcher.fetch(1).await;
+
+        assert!(matches!(result, Err(BoothInfoFetchError::NotFound(1))));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_falls_back_to_expired_cache() {
+        let server = MockServer::start();
+        server.push(MockResponse::new(200, FIXTURE));
+        for _ in 0..3 {
+            server.push(MockResponse::new(503, "Service Unavailable"));
+        }
+
+        let mut fetcher = create_fetcher(&server);
+        let fetched = fetcher.fetch(FIXTURE_ID).await.unwrap();
+
+        // すぐに期限切れになるようにして、取得し直しに失敗させる
+        fetcher.set_cache_config(BoothCacheConfig {
+            ttl_seconds: 0,
+            stale_seconds: 0,
+        });
+
+        assert_eq!(fetcher.fetch(FIXTURE_ID).await.unwrap(), fetched);
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_ignores_fresh_cache() {
+        let server = MockServer::start();
+        server.push(MockResponse::new(200, FIXTURE));
+        server.push(MockResponse::new(200, FIXTURE));
+
+        let fetcher = create_fetcher(&server);
+        let fetched = fetcher.fetch(FIXTURE_ID).await.unwrap();
+
+        // 有効期限内のキャッシュは使わずに取得し直す
+        assert_eq!(fetcher.refresh(FIXTURE_ID).await.unwrap(), fetched);
+        assert_eq!(server.requests().len(), 2);
+
+        assert_eq!(fetcher.fetch(FIXTURE_ID).await.unwrap(), fetched);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cloned_fetcher_shares_cache() {
+        let server = MockServer::start();
+        server.push(MockResponse::new(200, FIXTURE));
+
+        let fetcher = create_fetcher(&server);
+
+        // 複製したもので取得した結果は、元のものからも使える
+        let fetched = fetcher.clone().fetch(FIXTURE_ID).await.unwrap();
+
+        assert_eq!(fetcher.get_cached(FIXTURE_ID), Some(fetched));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_offline_mode() {
+        let server = MockServer::start();
+        server.push(MockResponse::new(200, FIXTURE));
+
+        let mut fetcher = create_fetcher(&server);
+        let fetched = fetcher.fetch(FIXTURE_ID).await.unwrap();
+
+        fetcher.set_offline_mode(true);
+        fetcher.set_cache_config(BoothCacheConfig {
+            ttl_seconds: 0,
+            stale_seconds: 0,
+        });
+
+        assert_eq!(fetcher.fetch(FIXTURE_ID).await.unwrap(), fetched);
+        assert!(matches!(
+            fetcher.fetch(1).await,
+            Err(BoothInfoFetchError::Offline(1))
+        ));
+
+        // オフラインの間は BOOTH に問い合わせない
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_shop_items() {
+        let server = MockServer::start();
+        let shop_items = include_str!("../test/shop_items.html");
+        server.route("/silolab/items?page=1", MockResponse::new(200, shop_items));
+        // 最後のページを超えた場合は、最後のページと同じ内容が返る
+        server.route("/silolab/items?page=2", MockResponse::new(200, shop_items));
+
+        let fetcher = BoothFetcher::builder("test")
+            .shop_base_url(server.base_url())
+            .build();
+
+        let items = fetcher.fetch_shop_items("silolab").await.unwrap();
+
         assert_eq!(
-            construct_api_url(1234567890),
-            "https://booth.pm/ja/items/1234567890.json"
+            items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![6641548, 1234567]
         );
+        assert_eq!(server.requests().len(), 2);
+
+        assert!(matches!(
+            fetcher.fetch_shop_items("unknown").await,
+            Err(BoothInfoFetchError::ShopNotFound(_))
+        ));
     }
 }
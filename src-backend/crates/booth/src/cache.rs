@@ -1,34 +1,296 @@
 use super::definitions::BoothAssetInfo;
 use chrono::Local;
-use std::collections::HashMap;
+use model::preference::BoothCachePreference;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-const EXPIRATION_SECONDS: i64 = 60;
+/// キャッシュファイルの形式が変わった場合に、古いキャッシュを破棄するためのバージョン
+const CACHE_FILE_VERSION: u32 = 4;
+
+/// 続けて取得した結果をまとめて保存するため、追加してからこの時間が経過した後に保存する
+const SAVE_DELAY: Duration = Duration::from_secs(3);
+
+/// キャッシュの有効期限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoothCacheConfig {
+    /// この秒数が経過するまでは、BOOTH に問い合わせずにキャッシュを返す
+    pub ttl_seconds: i64,
+    /// 有効期限を過ぎてからこの秒数の間は、キャッシュを返しつつ裏で取得し直す
+    pub stale_seconds: i64,
+}
+
+impl Default for BoothCacheConfig {
+    fn default() -> Self {
+        (&BoothCachePreference::default()).into()
+    }
+}
+
+impl From<&BoothCachePreference> for BoothCacheConfig {
+    fn from(preference: &BoothCachePreference) -> Self {
+        Self {
+            ttl_seconds: preference.ttl_hours as i64 * 60 * 60,
+            stale_seconds: preference.stale_days as i64 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    info: BoothAssetInfo,
+    /// 取得した時の BOOTH の言語コード
+    language: String,
+    /// 取得した日時 (UNIX 時間、秒)
+    fetched_at: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    entries: HashMap<u64, CacheEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum CacheLookup {
+    Fresh(BoothAssetInfo),
+    /// 有効期限は過ぎているが、取得し直すまでの間は使ってよい
+    Stale(BoothAssetInfo),
+    Miss,
+}
 
 pub struct BoothCache {
-    cache: HashMap<u64, (BoothAssetInfo, i64)>,
+    /// `None` の場合はファイルに保存しない
+    path: Option<PathBuf>,
+    config: BoothCacheConfig,
+    cache: HashMap<u64, CacheEntry>,
+    /// ファイルに保存していない変更がある
+    dirty: bool,
+    save_scheduled: bool,
 }
 
 impl BoothCache {
     pub fn new() -> Self {
         Self {
+            path: None,
+            config: BoothCacheConfig::default(),
             cache: HashMap::new(),
+            dirty: false,
+            save_scheduled: false,
+        }
+    }
+
+    /// ファイルからキャッシュを読み込み、以降の変更をファイルに保存する
+    ///
+    /// 読み込めない場合はキャッシュを空にして続行する
+    pub fn open<P: AsRef<Path>>(path: P, config: BoothCacheConfig) -> Self {
+        let path = path.as_ref().to_path_buf();
+
+        let cache = match read_cache_file(&path) {
+            Ok(cache) => cache,
+            Err(e) => {
+                log::warn!("Discarding BOOTH cache ({}): {}", path.display(), e);
+                HashMap::new()
+            }
+        };
+
+        let mut result = Self {
+            path: Some(path),
+            config,
+            cache,
+            dirty: false,
+            save_scheduled: false,
+        };
+        result.remove_expired();
+
+        result
+    }
+
+    pub fn set_config(&mut self, config: BoothCacheConfig) {
+        self.config = config;
+    }
+
+    /// 別の言語で取得したものは、名前が異なるため使わない
+    pub(crate) fn lookup(&self, id: u64, language: &str) -> CacheLookup {
+        let Some(entry) = self
+            .cache
+            .get(&id)
+            .filter(|entry| entry.language == language)
+        else {
+            return CacheLookup::Miss;
+        };
+
+        let age = Local::now().timestamp() - entry.fetched_at;
+
+        if age < self.config.ttl_seconds {
+            CacheLookup::Fresh(entry.info.clone())
+        } else if age < self.config.ttl_seconds + self.config.stale_seconds {
+            CacheLookup::Stale(entry.info.clone())
+        } else {
+            CacheLookup::Miss
         }
     }
 
-    pub fn get(&self, id: u64) -> Option<BoothAssetInfo> {
-        let result = self.cache.get(&id)?;
+    /// 有効期限に関わらず、残っているキャッシュを返す (オフライン時や取得に失敗した時に使う)
+    ///
+    /// 別の言語で取得したものしかない場合は、表示言語での名前を除いて返す
+    pub(crate) fn get_even_if_expired(&self, id: u64, language: &str) -> Option<BoothAssetInfo> {
+        let entry = self.cache.get(&id)?;
+
+        if entry.language == language {
+            return Some(entry.info.clone());
+        }
+
+        Some(BoothAssetInfo {
+            localized_name: None,
+            ..entry.info.clone()
+        })
+    }
+
+    /// ファイルへの保存は `schedule_save` か `flush` で行う
+    pub fn insert(&mut self, id: u64, language: &str, value: BoothAssetInfo) {
+        self.cache.insert(
+            id,
+            CacheEntry {
+                info: value,
+                language: language.to_string(),
+                fetched_at: Local::now().timestamp(),
+            },
+        );
 
-        if result.1 < Local::now().timestamp() {
-            return None;
+        self.dirty = true;
+    }
+
+    /// 保存していない変更があれば、すぐにファイルに保存する
+    pub fn flush(&mut self) {
+        if self.dirty {
+            self.save();
         }
+    }
+
+    /// すべてのキャッシュを削除する
+    pub fn purge(&mut self) -> Result<(), std::io::Error> {
+        self.cache.clear();
+        self.dirty = false;
 
-        Some(result.0.clone())
+        match &self.path {
+            Some(path) if path.exists() => std::fs::remove_file(path),
+            _ => Ok(()),
+        }
     }
 
-    pub fn insert(&mut self, id: u64, value: BoothAssetInfo) {
+    fn remove_expired(&mut self) {
+        let now = Local::now().timestamp();
+        let max_age = self.config.ttl_seconds + self.config.stale_seconds;
+
         self.cache
-            .insert(id, (value, Local::now().timestamp() + EXPIRATION_SECONDS));
+            .retain(|_, entry| now - entry.fetched_at < max_age);
+    }
+
+    /// キャッシュの保存に失敗しても取得結果は返せるため、ログに残すだけにする
+    fn save(&mut self) {
+        self.dirty = false;
+
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        if let Err(e) = write_cache_file(path, &self.cache) {
+            log::warn!("Failed to save BOOTH cache ({}): {}", path.display(), e);
+        }
+    }
+}
+
+impl Drop for BoothCache {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// 保存していない変更があれば、少し待ってからファイルに保存する (tokio ランタイム上で呼び出す必要がある)
+///
+/// ロックを保持したままファイルに書き込まないよう、内容を複製してから別のスレッドで書き込む
+pub(crate) fn schedule_save(cache: &Arc<Mutex<BoothCache>>) {
+    {
+        let mut cache = cache.lock().unwrap();
+
+        if cache.path.is_none() || !cache.dirty || cache.save_scheduled {
+            return;
+        }
+
+        cache.save_scheduled = true;
+    }
+
+    let cache = cache.clone();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+
+        let snapshot = {
+            let mut cache = cache.lock().unwrap();
+            cache.save_scheduled = false;
+
+            if cache.dirty {
+                cache.dirty = false;
+                cache.path.clone().map(|path| (path, cache.cache.clone()))
+            } else {
+                None
+            }
+        };
+
+        let Some((path, entries)) = snapshot else {
+            return;
+        };
+
+        let result =
+            tokio::task::spawn_blocking(move || (write_cache_file(&path, &entries), path)).await;
+
+        match result {
+            Ok((Ok(()), _)) => {}
+            Ok((Err(e), path)) => {
+                log::warn!("Failed to save BOOTH cache ({}): {}", path.display(), e)
+            }
+            Err(e) => log::warn!("Failed to save BOOTH cache: {}", e),
+        }
+    });
+}
+
+fn read_cache_file(path: &Path) -> Result<HashMap<u64, CacheEntry>, String> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: CacheFile = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+    if file.version != CACHE_FILE_VERSION {
+        return Err(format!("Unsupported cache version: {}", file.version));
+    }
+
+    Ok(file.entries)
+}
+
+fn write_cache_file(path: &Path, entries: &HashMap<u64, CacheEntry>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
+
+    let file = CacheFile {
+        version: CACHE_FILE_VERSION,
+        entries: entries.clone(),
+    };
+    let content = serde_json::to_string(&file).map_err(|e| e.to_string())?;
+
+    // 書き込み途中で終了してもキャッシュが壊れないよう、一時ファイルから置き換える
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, content).map_err(|e| e.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|e| e.to_string())?;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -37,26 +299,73 @@ mod tests {
 
     use super::*;
 
+    const LANGUAGE: &str = "ja";
+
     fn get_example_asset_info() -> BoothAssetInfo {
         BoothAssetInfo {
             id: 12345,
             name: "Test Asset".to_string(),
+            localized_name: None,
             creator: "Test Creator".to_string(),
             estimated_asset_type: Some(AssetType::Avatar),
+            category_id: 208,
             image_urls: vec![],
             published_at: 12345,
+            price: "¥ 500".to_string(),
+            description: String::new(),
+            tags: vec![],
+            shop_url: "https://test.booth.pm/".to_string(),
+            shop_subdomain: "test".to_string(),
+            variations: vec![],
         }
     }
 
+    fn insert_with_age(cache: &mut BoothCache, id: u64, age_seconds: i64) {
+        cache.cache.insert(
+            id,
+            CacheEntry {
+                info: get_example_asset_info(),
+                language: LANGUAGE.to_string(),
+                fetched_at: Local::now().timestamp() - age_seconds,
+            },
+        );
+    }
+
     #[test]
     fn test_cache_hit() {
         let id = 12345;
         let asset_info = get_example_asset_info();
 
         let mut cache = BoothCache::new();
-        cache.insert(id, asset_info.clone());
+        cache.insert(id, LANGUAGE, asset_info.clone());
+
+        assert_eq!(
+            cache.lookup(id, LANGUAGE),
+            CacheLookup::Fresh(asset_info.clone())
+        );
+
+        // 別の言語で取得したものは使わない
+        assert_eq!(cache.lookup(id, "en"), CacheLookup::Miss);
+        assert_eq!(cache.get_even_if_expired(id, LANGUAGE), Some(asset_info));
+    }
+
+    #[test]
+    fn test_get_expired_cache_in_other_language() {
+        let id = 12345;
+        let asset_info = BoothAssetInfo {
+            localized_name: Some("Localized Asset".to_string()),
+            ..get_example_asset_info()
+        };
+
+        let mut cache = BoothCache::new();
+        cache.insert(id, "en", asset_info.clone());
+
+        assert_eq!(cache.get_even_if_expired(id, "en"), Some(asset_info));
 
-        assert_eq!(cache.get(id), Some(asset_info));
+        // 別の言語での名前は使わず、出品者が付けた名前だけを返す
+        let fallback = cache.get_even_if_expired(id, "ko").unwrap();
+        assert_eq!(fallback.name, "Test Asset");
+        assert_eq!(fallback.localized_name, None);
     }
 
     #[test]
@@ -64,20 +373,100 @@ mod tests {
         let id = 12345;
         let cache = BoothCache::new();
 
-        assert_eq!(cache.get(id), None);
+        assert_eq!(cache.lookup(id, LANGUAGE), CacheLookup::Miss);
     }
 
     #[test]
     fn test_cache_expired() {
-        let id = 12345;
+        let mut cache = BoothCache::new();
+        cache.set_config(BoothCacheConfig {
+            ttl_seconds: 60,
+            stale_seconds: 60,
+        });
+
+        insert_with_age(&mut cache, 1, 30);
+        insert_with_age(&mut cache, 2, 90);
+        insert_with_age(&mut cache, 3, 150);
+
         let asset_info = get_example_asset_info();
 
-        let mut cache = BoothCache::new();
+        assert_eq!(
+            cache.lookup(1, LANGUAGE),
+            CacheLookup::Fresh(asset_info.clone())
+        );
+        assert_eq!(
+            cache.lookup(2, LANGUAGE),
+            CacheLookup::Stale(asset_info.clone())
+        );
+        assert_eq!(cache.lookup(3, LANGUAGE), CacheLookup::Miss);
+        assert_eq!(cache.get_even_if_expired(3, LANGUAGE), Some(asset_info));
+        assert_eq!(cache.get_even_if_expired(4, LANGUAGE), None);
+    }
+
+    #[test]
+    fn test_persistent_cache() {
+        let path = PathBuf::from("test/temp/booth_cache/cache.json");
+
+        if std::fs::exists(&path).unwrap() {
+            std::fs::remove_file(&path).unwrap();
+        }
 
+        let config = BoothCacheConfig::default();
+        let asset_info = get_example_asset_info();
+
+        let mut cache = BoothCache::open(&path, config);
+        cache.insert(asset_info.id, LANGUAGE, asset_info.clone());
+        insert_with_age(&mut cache, 1, config.ttl_seconds + config.stale_seconds + 1);
+        cache.save();
+
+        // 再起動後も読み込まれ、期限切れのものは捨てられる
+        let mut cache = BoothCache::open(&path, config);
+        assert_eq!(
+            cache.lookup(asset_info.id, LANGUAGE),
+            CacheLookup::Fresh(asset_info.clone())
+        );
+        assert_eq!(cache.lookup(1, LANGUAGE), CacheLookup::Miss);
+
+        cache.purge().unwrap();
+        assert!(!path.exists());
+        assert_eq!(
+            BoothCache::open(&path, config).lookup(asset_info.id, LANGUAGE),
+            CacheLookup::Miss
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_save() {
+        let path = PathBuf::from("test/temp/booth_cache/scheduled.json");
+
+        if std::fs::exists(&path).unwrap() {
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        let config = BoothCacheConfig::default();
+        let asset_info = get_example_asset_info();
+
+        let cache = Arc::new(Mutex::new(BoothCache::open(&path, config)));
+
+        // 追加しただけではファイルに書き込まない
         cache
-            .cache
-            .insert(id, (asset_info, Local::now().timestamp() - 1));
+            .lock()
+            .unwrap()
+            .insert(asset_info.id, LANGUAGE, asset_info.clone());
+        schedule_save(&cache);
+        assert!(!path.exists());
+
+        tokio::time::timeout(SAVE_DELAY * 3, async {
+            while !path.exists() {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("cache was not saved");
 
-        assert_eq!(cache.get(id), None);
+        assert_eq!(
+            BoothCache::open(&path, config).lookup(asset_info.id, LANGUAGE),
+            CacheLookup::Fresh(asset_info)
+        );
     }
 }
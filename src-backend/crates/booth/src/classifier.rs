@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+
+use model::{AssetDescription, AssetType};
+use serde::Serialize;
+
+use super::definitions::BoothAssetInfo;
+
+/// 過去のインポートからの推定を採用するのに必要なスコア
+const LEARNED_SCORE_THRESHOLD: f64 = 2.0;
+
+/// 過去のインポートからの推定を採用するのに必要な、一致した特徴の数
+const LEARNED_MIN_MATCHED_FEATURES: usize = 2;
+
+const TAG_WEIGHT: f64 = 2.0;
+const CREATOR_WEIGHT: f64 = 1.0;
+const WORD_WEIGHT: f64 = 1.0;
+
+/// アイテム名で一致した場合のスコア (タグで一致した場合は 1)
+const NAME_MATCH_SCORE: u32 = 2;
+
+/// BOOTH のアイテムから推定した、アセットの種類とカテゴリ
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetClassification {
+    pub asset_type: Option<AssetType>,
+    pub category: Option<String>,
+}
+
+struct CategoryRule {
+    category: &'static str,
+    asset_type: AssetType,
+    /// 英字のキーワードは単語単位で、それ以外は部分一致で判定する
+    keywords: &'static [&'static str],
+}
+
+const CATEGORY_RULES: &[CategoryRule] = &[
+    CategoryRule {
+        category: "髪型",
+        asset_type: AssetType::AvatarWearable,
+        keywords: &["髪", "ヘアスタイル", "ウィッグ", "hair", "hairstyle", "wig"],
+    },
+    CategoryRule {
+        category: "衣装",
+        asset_type: AssetType::AvatarWearable,
+        keywords: &[
+            "衣装",
+            "服",
+            "ドレス",
+            "パーカー",
+            "水着",
+            "制服",
+            "スカート",
+            "outfit",
+            "costume",
+            "dress",
+            "clothes",
+        ],
+    },
+    CategoryRule {
+        category: "アクセサリー",
+        asset_type: AssetType::AvatarWearable,
+        keywords: &[
+            "アクセサリー",
+            "装飾品",
+            "ピアス",
+            "イヤリング",
+            "ネックレス",
+            "指輪",
+            "眼鏡",
+            "メガネ",
+            "帽子",
+            "accessory",
+            "accessories",
+        ],
+    },
+    CategoryRule {
+        category: "シェーダー",
+        asset_type: AssetType::OtherAsset,
+        keywords: &["シェーダー", "shader"],
+    },
+    CategoryRule {
+        category: "ギミック",
+        asset_type: AssetType::AvatarWearable,
+        keywords: &["ギミック", "gimmick"],
+    },
+    CategoryRule {
+        category: "テクスチャ",
+        asset_type: AssetType::AvatarWearable,
+        keywords: &["テクスチャ", "texture"],
+    },
+    CategoryRule {
+        category: "モーション",
+        asset_type: AssetType::AvatarWearable,
+        keywords: &[
+            "モーション",
+            "アニメーション",
+            "ポーズ",
+            "motion",
+            "animation",
+            "pose",
+        ],
+    },
+    CategoryRule {
+        category: "ツール",
+        asset_type: AssetType::OtherAsset,
+        keywords: &["ツール", "エディタ拡張", "tool", "editor"],
+    },
+];
+
+/// BOOTH の情報からアセットの種類とカテゴリを推定する
+///
+/// ユーザーが過去にインポートしたアセットから学習した対応を優先し、
+/// 十分な手がかりがなければ、BOOTH のカテゴリとアイテム名・タグのキーワードから推定する。
+/// キーワードから推定したカテゴリは、ユーザーが使っている同じ意味のカテゴリ名があればそちらに置き換える
+#[derive(Default)]
+pub struct AssetClassifier {
+    /// 特徴ごとの、付けられた種類とカテゴリの回数
+    observations: HashMap<String, HashMap<(AssetType, String), u32>>,
+    /// 登録済みのカテゴリごとの、使われている回数
+    categories: HashMap<String, u32>,
+}
+
+impl AssetClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登録済みのアセットの種類とカテゴリを学習する
+    pub fn learn(&mut self, asset_type: AssetType, category: &str, description: &AssetDescription) {
+        let label = (asset_type, category.trim().to_string());
+
+        if !label.1.is_empty() {
+            *self.categories.entry(label.1.clone()).or_default() += 1;
+        }
+
+        for (feature, _) in
+            extract_features(&description.name, &description.creator, &description.tags)
+        {
+            *self
+                .observations
+                .entry(feature)
+                .or_default()
+                .entry(label.clone())
+                .or_default() += 1;
+        }
+    }
+
+    pub fn classify(&self, info: &BoothAssetInfo) -> AssetClassification {
+        if let Some((asset_type, category)) = self.classify_by_history(info) {
+            return AssetClassification {
+                asset_type: Some(asset_type),
+                category: (!category.is_empty()).then_some(category),
+            };
+        }
+
+        let estimated_asset_type = estimate_asset_type_from_category(info.category_id);
+
+        // アバターにはカテゴリがない
+        if estimated_asset_type == Some(AssetType::Avatar) {
+            return AssetClassification {
+                asset_type: estimated_asset_type,
+                category: None,
+            };
+        }
+
+        match match_category_rule(&info.name, &info.tags) {
+            Some(rule) => AssetClassification {
+                asset_type: estimated_asset_type.or(Some(rule.asset_type)),
+                category: Some(self.resolve_category(rule)),
+            },
+            None => AssetClassification {
+                asset_type: estimated_asset_type,
+                category: estimate_category_from_category(info.category_id)
+                    .map(|rule| self.resolve_category(rule)),
+            },
+        }
+    }
+
+    /// 規則のカテゴリに相当する、登録済みのカテゴリのうち最もよく使われているものを返す
+    fn resolve_category(&self, rule: &CategoryRule) -> String {
+        self.categories
+            .iter()
+            .filter(|(category, _)| {
+                let words = split_words(category);
+                let category = category.to_lowercase();
+                category == rule.category
+                    || rule
+                        .keywords
+                        .iter()
+                        .any(|keyword| matches_keyword(&category, &words, keyword))
+            })
+            .max_by(|(a_category, a), (b_category, b)| {
+                a.cmp(b).then_with(|| b_category.cmp(a_category))
+            })
+            .map(|(category, _)| category.clone())
+            .unwrap_or_else(|| rule.category.to_string())
+    }
+
+    fn classify_by_history(&self, info: &BoothAssetInfo) -> Option<(AssetType, String)> {
+        let mut scores: HashMap<&(AssetType, String), (f64, usize)> = HashMap::new();
+
+        for (feature, weight) in extract_features(&info.name, &info.creator, &info.tags) {
+            let Some(labels) = self.observations.get(&feature) else {
+                continue;
+            };
+
+            // どのラベルにも付けられている特徴は手がかりとして弱いため、割合で加点する
+            let total: u32 = labels.values().sum();
+            for (label, count) in labels {
+                let (score, matched) = scores.entry(label).or_default();
+                *score += weight * (*count as f64) / (total as f64);
+                *matched += 1;
+            }
+        }
+
+        // 1 つのタグだけの一致では偶然の可能性が高いため、複数の特徴での一致を求める
+        scores
+            .into_iter()
+            .filter(|(_, (score, matched))| {
+                *score >= LEARNED_SCORE_THRESHOLD && *matched >= LEARNED_MIN_MATCHED_FEATURES
+            })
+            .map(|(label, (score, _))| (label, score))
+            .max_by(|(a_label, a), (b_label, b)| {
+                // 同点の場合でも結果が変わらないよう、ラベルの順で決める
+                a.total_cmp(b)
+                    .then_with(|| b_label.1.cmp(&a_label.1))
+                    .then_with(|| asset_type_order(b_label.0).cmp(&asset_type_order(a_label.0)))
+            })
+            .map(|(label, _)| label.clone())
+    }
+}
+
+fn asset_type_order(asset_type: AssetType) -> u8 {
+    match asset_type {
+        AssetType::Avatar => 0,
+        AssetType::AvatarWearable => 1,
+        AssetType::WorldObject => 2,
+        AssetType::OtherAsset => 3,
+    }
+}
+
+fn extract_features(name: &str, creator: &str, tags: &[String]) -> Vec<(String, f64)> {
+    let mut features: Vec<(String, f64)> = vec![];
+
+    let creator = creator.trim().to_lowercase();
+    if !creator.is_empty() {
+        features.push((format!("creator:{}", creator), CREATOR_WEIGHT));
+    }
+
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() {
+            features.push((format!("tag:{}", tag), TAG_WEIGHT));
+        }
+    }
+
+    for word in split_words(name) {
+        if word.chars().count() >= 2 {
+            features.push((format!("word:{}", word), WORD_WEIGHT));
+        }
+    }
+
+    features.sort_by(|(a, _), (b, _)| a.cmp(b));
+    features.dedup_by(|(a, _), (b, _)| a == b);
+
+    features
+}
+
+fn split_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn match_category_rule(name: &str, tags: &[String]) -> Option<&'static CategoryRule> {
+    let name_words = split_words(name);
+    let name = name.to_lowercase();
+    let tags: Vec<(String, Vec<String>)> = tags
+        .iter()
+        .map(|tag| (tag.to_lowercase(), split_words(tag)))
+        .collect();
+
+    let mut best: Option<(&CategoryRule, u32)> = None;
+
+    for rule in CATEGORY_RULES {
+        let mut score = 0;
+
+        for keyword in rule.keywords {
+            if matches_keyword(&name, &name_words, keyword) {
+                score += NAME_MATCH_SCORE;
+            }
+
+            score += tags
+                .iter()
+                .filter(|(tag, words)| matches_keyword(tag, words, keyword))
+                .count() as u32;
+        }
+
+        // 同点の場合は先に定義されている規則を優先する
+        if score > 0 && best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((rule, score));
+        }
+    }
+
+    best.map(|(rule, _)| rule)
+}
+
+fn matches_keyword(text: &str, words: &[String], keyword: &str) -> bool {
+    if keyword.is_ascii() {
+        // "chair" が "hair" に一致しないように単語単位で比較する (複数形は許容する)
+        words
+            .iter()
+            .any(|word| word == keyword || word.strip_suffix('s') == Some(keyword))
+    } else {
+        text.contains(keyword)
+    }
+}
+
+pub(crate) fn estimate_asset_type_from_category(category_id: i32) -> Option<AssetType> {
+    match category_id {
+        208 //   3Dキャラクター
+        => Some(AssetType::Avatar),
+        209 | // 3D衣装
+        217 | // 3D装飾品
+        210 | // 3D小道具
+        214 | // 3Dテクスチャ
+        215 | // 3Dツール・システム
+        216 | // 3Dモーション・アニメーション
+        127 //   3Dモデル（その他）
+        => Some(AssetType::AvatarWearable),
+        211 //   3D環境・ワールド
+        => Some(AssetType::WorldObject),
+        _ => None,
+    }
+}
+
+fn estimate_category_from_category(category_id: i32) -> Option<&'static CategoryRule> {
+    let category = match category_id {
+        209 => "衣装",
+        217 => "アクセサリー",
+        214 => "テクスチャ",
+        215 => "ツール",
+        216 => "モーション",
+        _ => return None,
+    };
+
+    CATEGORY_RULES.iter().find(|rule| rule.category == category)
+}
+
+#[cfg(test)]
+mod tests {
+    use model::AssetDataLocation;
+
+    use super::*;
+
+    fn create_info(name: &str, creator: &str, category_id: i32, tags: &[&str]) -> BoothAssetInfo {
+        BoothAssetInfo {
+            id: 1,
+            name: name.to_string(),
+            localized_name: None,
+            creator: creator.to_string(),
+            estimated_asset_type: estimate_asset_type_from_category(category_id),
+            category_id,
+            image_urls: vec![],
+            published_at: 0,
+            price: "¥ 500".to_string(),
+            description: String::new(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            shop_url: "https://test.booth.pm/".to_string(),
+            shop_subdomain: "test".to_string(),
+            variations: vec![],
+        }
+    }
+
+    fn create_description(name: &str, creator: &str, tags: &[&str]) -> AssetDescription {
+        AssetDescription {
+            name: name.to_string(),
+            creator: creator.to_string(),
+            image_filename: None,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            memo: None,
+            booth_item_id: None,
+            dependencies: vec![],
+            created_at: 0,
+            published_at: None,
+            data_location: AssetDataLocation::Local,
+        }
+    }
+
+    fn classification(asset_type: AssetType, category: Option<&str>) -> AssetClassification {
+        AssetClassification {
+            asset_type: Some(asset_type),
+            category: category.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_estimate_asset_type() {
+        // 3Dキャラクター
+        assert_eq!(
+            estimate_asset_type_from_category(208),
+            Some(AssetType::Avatar)
+        );
+
+        // 3D衣装
+        assert_eq!(
+            estimate_asset_type_from_category(209),
+            Some(AssetType::AvatarWearable)
+        );
+
+        // 3D環境・ワールド
+        assert_eq!(
+            estimate_asset_type_from_category(211),
+            Some(AssetType::WorldObject)
+        );
+
+        // 不明なカテゴリ
+        assert_eq!(estimate_asset_type_from_category(0), None);
+    }
+
+    #[test]
+    fn test_classify_by_rules() {
+        let classifier = AssetClassifier::new();
+
+        // アバターにはカテゴリを付けない
+        assert_eq!(
+            classifier.classify(&create_info("オリジナル3Dモデル", "shop", 208, &["髪"])),
+            classification(AssetType::Avatar, None)
+        );
+
+        // アイテム名のキーワードから推定する
+        assert_eq!(
+            classifier.classify(&create_info(
+                "【複数アバター対応】ふんわりボブ髪",
+                "shop",
+                209,
+                &[]
+            )),
+            classification(AssetType::AvatarWearable, Some("髪型"))
+        );
+
+        // アイテム名での一致をタグでの一致より優先する
+        assert_eq!(
+            classifier.classify(&create_info(
+                "Simple Toon Shader",
+                "shop",
+                215,
+                &["VRChat", "衣装"]
+            )),
+            classification(AssetType::AvatarWearable, Some("シェーダー"))
+        );
+
+        // 英字のキーワードは単語単位で判定する
+        assert_eq!(
+            classifier.classify(&create_info("Wooden Chair", "shop", 211, &[])),
+            classification(AssetType::WorldObject, None)
+        );
+
+        // キーワードがなければ BOOTH のカテゴリから推定する
+        assert_eq!(
+            classifier.classify(&create_info("ぷちぷち", "shop", 217, &[])),
+            classification(AssetType::AvatarWearable, Some("アクセサリー"))
+        );
+
+        // BOOTH のカテゴリが不明でも、キーワードから種類を推定する
+        assert_eq!(
+            classifier.classify(&create_info("Fancy Shaders", "shop", 0, &[])),
+            classification(AssetType::OtherAsset, Some("シェーダー"))
+        );
+
+        assert_eq!(
+            classifier.classify(&create_info("ぷちぷち", "shop", 0, &[])),
+            AssetClassification::default()
+        );
+    }
+
+    #[test]
+    fn test_classify_by_history() {
+        let mut classifier = AssetClassifier::new();
+
+        classifier.learn(
+            AssetType::AvatarWearable,
+            "小物",
+            &create_description("ぷちぷちリボン", "Ribbon Shop", &["リボン"]),
+        );
+        classifier.learn(
+            AssetType::AvatarWearable,
+            "小物",
+            &create_description("もふもふリボン", "Ribbon Shop", &["リボン"]),
+        );
+        classifier.learn(
+            AssetType::Avatar,
+            "",
+            &create_description("Sample Avatar", "Avatar Shop", &["VRChat"]),
+        );
+
+        // 過去のインポートと同じタグ・作者のアイテムは、同じ種類とカテゴリにする
+        assert_eq!(
+            classifier.classify(&create_info(
+                "きらきらリボン",
+                "Ribbon Shop",
+                217,
+                &["リボン"]
+            )),
+            classification(AssetType::AvatarWearable, Some("小物"))
+        );
+
+        // タグが 1 つ一致するだけでは過去のインポートから推定しない
+        assert_eq!(
+            classifier.classify(&create_info("きらきら", "shop", 217, &["リボン"])),
+            classification(AssetType::AvatarWearable, Some("アクセサリー"))
+        );
+
+        // 手がかりが少なければ規則から推定する
+        assert_eq!(
+            classifier.classify(&create_info("Sample Hair", "Ribbon Shop", 209, &[])),
+            classification(AssetType::AvatarWearable, Some("髪型"))
+        );
+
+        // カテゴリのないアセットから学習した場合はカテゴリを推定しない
+        assert_eq!(
+            classifier.classify(&create_info(
+                "Sample Avatar 2",
+                "Avatar Shop",
+                0,
+                &["VRChat"]
+            )),
+            classification(AssetType::Avatar, None)
+        );
+    }
+
+    #[test]
+    fn test_classify_by_rules_with_learned_categories() {
+        let mut classifier = AssetClassifier::new();
+
+        classifier.learn(
+            AssetType::AvatarWearable,
+            "Hair",
+            &create_description("Short Bob", "Hair Shop", &[]),
+        );
+        classifier.learn(
+            AssetType::AvatarWearable,
+            "Outfit",
+            &create_description("Summer Dress", "Dress Shop", &[]),
+        );
+
+        // 規則から推定したカテゴリは、登録済みの同じ意味のカテゴリ名にする
+        assert_eq!(
+            classifier.classify(&create_info("ふんわりボブ髪", "shop", 209, &[])),
+            classification(AssetType::AvatarWearable, Some("Hair"))
+        );
+
+        // BOOTH のカテゴリから推定した場合も同様
+        assert_eq!(
+            classifier.classify(&create_info("ぷちぷち", "shop", 209, &[])),
+            classification(AssetType::AvatarWearable, Some("Outfit"))
+        );
+
+        // 相当するカテゴリがなければ規則のカテゴリ名にする
+        assert_eq!(
+            classifier.classify(&create_info("ぷちぷち", "shop", 217, &[])),
+            classification(AssetType::AvatarWearable, Some("アクセサリー"))
+        );
+    }
+}
@@ -1,15 +1,62 @@
-use model::AssetType;
+use model::{AssetType, preference::BoothNameSource};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Clone, Debug, PartialEq, Eq, specta::Type)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, specta::Type)]
 #[serde(rename_all = "camelCase")]
 pub struct BoothAssetInfo {
     pub id: u64,
+    /// 出品者が付けた名前
     pub name: String,
+    /// 表示言語で取得した名前 (日本語で取得した場合や、取得できなかった場合は `None`)
+    pub localized_name: Option<String>,
     pub creator: String,
     pub image_urls: Vec<String>,
     pub published_at: i64,
     pub estimated_asset_type: Option<AssetType>,
+    /// BOOTH 上のカテゴリの ID
+    pub category_id: i32,
+    /// 表示用の価格 (例: `¥ 500`)
+    pub price: String,
+    pub description: String,
+    /// BOOTH 上で付けられているタグ
+    pub tags: Vec<String>,
+    pub shop_url: String,
+    pub shop_subdomain: String,
+    pub variations: Vec<BoothVariation>,
+}
+
+impl BoothAssetInfo {
+    /// インポート時にアセット名として使う名前を返す
+    pub fn name_for(&self, source: BoothNameSource) -> &str {
+        match (source, &self.localized_name) {
+            (BoothNameSource::Localized, Some(localized_name)) => localized_name,
+            _ => &self.name,
+        }
+    }
+
+    /// すべてのバリエーションでダウンロードできるファイル名を、重複を除いて名前順に返す
+    pub fn downloadable_file_names(&self) -> Vec<String> {
+        let mut file_names: Vec<String> = self
+            .variations
+            .iter()
+            .flat_map(|variation| variation.downloadable_files.iter().cloned())
+            .collect();
+
+        file_names.sort();
+        file_names.dedup();
+
+        file_names
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothVariation {
+    pub id: u64,
+    pub name: Option<String>,
+    pub price: u64,
+    /// ダウンロード商品でない場合は空になる
+    pub downloadable_files: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -20,11 +67,23 @@ pub(crate) struct BoothJsonSchema {
     pub images: Vec<BoothPximg>,
     pub category: BoothCategory,
     pub published_at: String,
+    #[serde(default)]
+    pub price: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<BoothTag>,
+    #[serde(default)]
+    pub variations: Vec<BoothVariationSchema>,
 }
 
 #[derive(Deserialize)]
 pub(crate) struct BoothShop {
     pub name: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub subdomain: String,
 }
 
 #[derive(Deserialize)]
@@ -36,3 +95,30 @@ pub(crate) struct BoothPximg {
 pub(crate) struct BoothCategory {
     pub id: i32,
 }
+
+#[derive(Deserialize)]
+pub(crate) struct BoothTag {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BoothVariationSchema {
+    pub id: u64,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub price: u64,
+    pub downloadable: Option<BoothDownloadable>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BoothDownloadable {
+    #[serde(default)]
+    pub musics: Vec<BoothDownloadableFile>,
+    #[serde(default)]
+    pub no_musics: Vec<BoothDownloadableFile>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BoothDownloadableFile {
+    pub name: String,
+}
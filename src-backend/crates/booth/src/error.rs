@@ -4,12 +4,33 @@ pub enum BoothInfoFetchError {
     NotFound(u64),
     #[error("Failed to fetch asset description from BOOTH: {0}")]
     APICallError(#[from] reqwest::Error),
+    #[error("BOOTH API responded with unexpected status: {0}")]
+    UnexpectedStatus(u16),
+    #[error("Item is not cached and cannot be fetched in offline mode: {0}")]
+    Offline(u64),
+    #[error("Shop not found: {0}")]
+    ShopNotFound(String),
+    #[error("Shop items cannot be fetched in offline mode: {0}")]
+    ShopOffline(String),
     #[error("Failed to parse JSON from BOOTH API response: {0}")]
     JSONParseError(#[from] serde_json::Error),
     #[error("Failed to parse date from BOOTH API response: {0}")]
     DateParseError(#[from] chrono::format::ParseError),
 }
 
+impl BoothInfoFetchError {
+    /// 時間をおけば成功する可能性がある (通信の失敗や BOOTH 側の混雑)
+    pub fn is_transient(&self) -> bool {
+        match self {
+            BoothInfoFetchError::APICallError(_) => true,
+            BoothInfoFetchError::UnexpectedStatus(status) => {
+                *status == 429 || (500..600).contains(status)
+            }
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum PximgResolveError {
     #[error("URL Validation failed: {0}")]
@@ -1,13 +1,37 @@
 mod booth;
 mod cache;
+mod classifier;
 mod client;
 mod definitions;
 mod error;
+mod limiter;
+mod purchase;
 mod pximg;
+mod refresh;
+mod retry;
+mod shop;
+mod tags;
+#[cfg(test)]
+mod test_server;
+mod update;
 
-pub use definitions::BoothAssetInfo;
+pub use definitions::{BoothAssetInfo, BoothVariation};
 
-pub use booth::BoothFetcher;
-pub use pximg::PximgResolver;
+pub use booth::{BoothFetcher, BoothFetcherBuilder};
+pub use cache::BoothCacheConfig;
+pub use classifier::{AssetClassification, AssetClassifier};
+pub use purchase::{BoothPurchasedItem, parse_purchase_history};
+pub use pximg::{PximgResolver, PximgResolverBuilder};
+pub use refresh::{
+    BoothMetadataDiff, BoothMetadataField, BoothMetadataUpdate, BoothMetadataValues,
+    apply_booth_metadata, diff_booth_metadata,
+};
+pub use retry::RetryPolicy;
+pub use shop::{
+    BoothShopItem, BoothShopItemEntry, cross_reference_shop_items, parse_shop_items,
+    parse_shop_subdomain,
+};
+pub use tags::map_booth_tags;
+pub use update::BoothUpdateTracker;
 
 pub use error::*;
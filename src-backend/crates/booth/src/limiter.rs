@@ -0,0 +1,110 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// トークンバケット方式で、BOOTH へのリクエストの頻度を制限する
+///
+/// `capacity` 回までは続けて送信でき、それ以降は 1 秒あたり `refill_per_second` 回に制限される
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// 待機中のリクエストが予約した分だけ負になる
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// 補充の頻度の下限。0 以下や NaN が指定された場合は、この頻度で補充する
+const MIN_REFILL_PER_SECOND: f64 = 0.01;
+
+impl RateLimiter {
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        // f64::max は NaN ではない方を返すため、NaN もここで下限に丸められる
+        let refill_per_second = refill_per_second.max(MIN_REFILL_PER_SECOND);
+
+        Self {
+            capacity: capacity as f64,
+            refill_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// リクエストを送信してよくなるまで待機する
+    pub async fn acquire(&self) {
+        let wait = self.reserve();
+
+        if !wait.is_zero() {
+            log::debug!("Waiting {:?} for BOOTH rate limit", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// トークンを 1 つ予約し、使えるようになるまでの待ち時間を返す
+    fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        bucket.last_refill = now;
+
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.refill_per_second)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve() {
+        let limiter = RateLimiter::new(2, 10.0);
+
+        // 容量までは待たずに送信できる
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+
+        // それ以降は補充される間隔ごとに順番待ちになる
+        let first = limiter.reserve();
+        let second = limiter.reserve();
+        assert!(first > Duration::from_millis(50) && first <= Duration::from_millis(100));
+        assert!(second > Duration::from_millis(150) && second <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_invalid_refill_rate_is_clamped() {
+        for refill_per_second in [0.0, -1.0, f64::NAN] {
+            let limiter = RateLimiter::new(0, refill_per_second);
+
+            assert_eq!(
+                limiter.reserve(),
+                Duration::from_secs_f64(1.0 / MIN_REFILL_PER_SECOND)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_refill() {
+        let limiter = RateLimiter::new(1, 20.0);
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        limiter.acquire().await;
+
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+}
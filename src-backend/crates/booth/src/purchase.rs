@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// 祖先要素をさかのぼってショップへのリンクを探す最大の深さ
+const MAX_CONTAINER_DEPTH: usize = 8;
+
+/// BOOTH のライブラリや注文履歴から読み取った購入済みアイテム
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothPurchasedItem {
+    pub id: u64,
+    pub name: Option<String>,
+    pub shop_name: Option<String>,
+    pub shop_url: Option<String>,
+}
+
+/// 保存された BOOTH のライブラリ / 注文履歴の HTML、またはアイテムの URL を並べたテキストから
+/// 購入済みアイテムを抽出する (通信は行わない)
+pub fn parse_purchase_history(content: &str) -> Vec<BoothPurchasedItem> {
+    let items = parse_html(content);
+
+    if !items.is_empty() {
+        return items;
+    }
+
+    parse_plain_text(content)
+}
+
+fn parse_html(content: &str) -> Vec<BoothPurchasedItem> {
+    let document = Html::parse_document(content);
+    let anchor_selector = Selector::parse("a[href]").unwrap();
+    let image_selector = Selector::parse("img[alt]").unwrap();
+
+    let mut items: Vec<BoothPurchasedItem> = vec![];
+    let mut indexes: HashMap<u64, usize> = HashMap::new();
+
+    for anchor in document.select(&anchor_selector) {
+        let Some(id) = anchor.value().attr("href").and_then(parse_item_id) else {
+            continue;
+        };
+
+        let index = *indexes.entry(id).or_insert_with(|| {
+            items.push(BoothPurchasedItem {
+                id,
+                name: None,
+                shop_name: None,
+                shop_url: None,
+            });
+            items.len() - 1
+        });
+        let item = &mut items[index];
+
+        if item.name.is_none() {
+            // 画像のみのリンクの場合は alt をアイテム名として扱う
+            item.name = normalize_text(anchor.text()).or_else(|| {
+                anchor
+                    .select(&image_selector)
+                    .find_map(|image| normalize_text(image.value().attr("alt")))
+            });
+        }
+
+        if item.shop_url.is_none() {
+            if let Some((shop_name, shop_url)) = find_shop(anchor, id, &anchor_selector) {
+                item.shop_name = shop_name;
+                item.shop_url = Some(shop_url);
+            }
+        }
+    }
+
+    items
+}
+
+/// アイテムのリンクを含む要素をさかのぼり、同じ要素内にあるショップへのリンクを探す
+///
+/// 他のアイテムへのリンクを含む要素まで到達した場合は、一覧全体とみなして探索をやめる
+fn find_shop(
+    anchor: ElementRef,
+    item_id: u64,
+    anchor_selector: &Selector,
+) -> Option<(Option<String>, String)> {
+    let mut current = anchor;
+
+    for _ in 0..MAX_CONTAINER_DEPTH {
+        current = current.parent().and_then(ElementRef::wrap)?;
+
+        let mut shop = None;
+
+        for candidate in current.select(anchor_selector) {
+            let Some(href) = candidate.value().attr("href") else {
+                continue;
+            };
+
+            if let Some(other_id) = parse_item_id(href) {
+                if other_id != item_id {
+                    return None;
+                }
+                continue;
+            }
+
+            if shop.is_none() {
+                shop = parse_shop_url(href).map(|url| (normalize_text(candidate.text()), url));
+            }
+        }
+
+        if shop.is_some() {
+            return shop;
+        }
+    }
+
+    None
+}
+
+/// `https://booth.pm/ja/items/123` や `https://shop.booth.pm/items/123` からアイテムIDを取得する
+pub(crate) fn parse_item_id(href: &str) -> Option<u64> {
+    let url = Url::parse(href.trim()).ok()?;
+
+    if !is_booth_host(&url) {
+        return None;
+    }
+
+    let mut segments = url.path_segments()?;
+
+    while let Some(segment) = segments.next() {
+        if segment == "items" {
+            return segments.next()?.parse().ok();
+        }
+    }
+
+    None
+}
+
+/// `https://shop.booth.pm/` の形式のショップのトップページであれば正規化した URL を返す
+fn parse_shop_url(href: &str) -> Option<String> {
+    let url = Url::parse(href.trim()).ok()?;
+    let host = url.host_str()?;
+
+    let subdomain = host.strip_suffix(".booth.pm")?;
+
+    if ["www", "accounts", "checkout", "manage", "asset"].contains(&subdomain) {
+        return None;
+    }
+
+    if url.path() != "/" && !url.path().is_empty() {
+        return None;
+    }
+
+    Some(format!("https://{}/", host))
+}
+
+fn is_booth_host(url: &Url) -> bool {
+    match url.host_str() {
+        Some(host) => host == "booth.pm" || host.ends_with(".booth.pm"),
+        None => false,
+    }
+}
+
+/// 1 行に 1 アイテムの URL が含まれるテキスト (CSV / TSV を含む) を読み取る
+///
+/// URL 以外の列がある場合、1 列目をアイテム名、2 列目をショップ名として扱う
+fn parse_plain_text(content: &str) -> Vec<BoothPurchasedItem> {
+    let mut items: Vec<BoothPurchasedItem> = vec![];
+
+    for line in content.lines() {
+        let columns = line
+            .split(['\t', ','])
+            .map(|column| column.trim().trim_matches('"').trim())
+            .filter(|column| !column.is_empty())
+            .collect::<Vec<_>>();
+
+        let Some(id) = columns
+            .iter()
+            .find_map(|column| column.split_whitespace().find_map(parse_item_id))
+        else {
+            continue;
+        };
+
+        let mut texts = columns.iter().filter(|column| !column.contains("booth.pm"));
+        let name = texts.next().map(|name| name.to_string());
+        let shop_name = texts.next().map(|shop_name| shop_name.to_string());
+
+        // 同じアイテムが複数行にある場合は、不足している情報のみ補う
+        if let Some(item) = items.iter_mut().find(|item| item.id == id) {
+            item.name = item.name.take().or(name);
+            item.shop_name = item.shop_name.take().or(shop_name);
+            continue;
+        }
+
+        items.push(BoothPurchasedItem {
+            id,
+            name,
+            shop_name,
+            shop_url: None,
+        });
+    }
+
+    items
+}
+
+pub(crate) fn normalize_text<'a, I>(texts: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let text = texts
+        .into_iter()
+        .flat_map(|text| text.split_whitespace())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if text.is_empty() { None } else { Some(text) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_library_html() {
+        let content = include_str!("../test/library.html");
+
+        let result = parse_purchase_history(content);
+
+        assert_eq!(
+            result,
+            vec![
+                BoothPurchasedItem {
+                    id: 6641548,
+                    name: Some("KonoAsset - VRChat向けアセット管理ツール".into()),
+                    shop_name: Some("silolab".into()),
+                    shop_url: Some("https://silolab.booth.pm/".into()),
+                },
+                BoothPurchasedItem {
+                    id: 1234567,
+                    name: Some("Sample Outfit".into()),
+                    shop_name: Some("Sample Shop".into()),
+                    shop_url: Some("https://sample-shop.booth.pm/".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_order_list_html() {
+        let content = include_str!("../test/orders.html");
+
+        let result = parse_purchase_history(content);
+
+        assert_eq!(
+            result,
+            vec![
+                BoothPurchasedItem {
+                    id: 2345678,
+                    name: Some("Sample Avatar".into()),
+                    shop_name: Some("Avatar Shop".into()),
+                    shop_url: Some("https://avatar-shop.booth.pm/".into()),
+                },
+                BoothPurchasedItem {
+                    id: 3456789,
+                    name: Some("Sample Shader".into()),
+                    shop_name: None,
+                    shop_url: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_plain_text() {
+        let content = "\
+https://booth.pm/ja/items/6641548
+\"KonoAsset\",\"silolab\",\"https://silolab.booth.pm/items/6641548\"
+Sample Outfit\tSample Shop\thttps://booth.pm/en/items/1234567
+not a booth url
+";
+
+        let result = parse_purchase_history(content);
+
+        assert_eq!(
+            result,
+            vec![
+                BoothPurchasedItem {
+                    id: 6641548,
+                    name: Some("KonoAsset".into()),
+                    shop_name: Some("silolab".into()),
+                    shop_url: None,
+                },
+                BoothPurchasedItem {
+                    id: 1234567,
+                    name: Some("Sample Outfit".into()),
+                    shop_name: Some("Sample Shop".into()),
+                    shop_url: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_item_id() {
+        assert_eq!(parse_item_id("https://booth.pm/ja/items/123"), Some(123));
+        assert_eq!(parse_item_id("https://shop.booth.pm/items/456"), Some(456));
+        assert_eq!(parse_item_id("https://example.com/items/789"), None);
+        assert_eq!(parse_item_id("https://booth.pm/ja/items/abc"), None);
+        assert_eq!(parse_item_id("/ja/items/123"), None);
+    }
+
+    #[test]
+    fn test_parse_shop_url() {
+        assert_eq!(
+            parse_shop_url("https://silolab.booth.pm/"),
+            Some("https://silolab.booth.pm/".into())
+        );
+        assert_eq!(parse_shop_url("https://silolab.booth.pm/items/123"), None);
+        assert_eq!(parse_shop_url("https://accounts.booth.pm/"), None);
+        assert_eq!(parse_shop_url("https://booth.pm/"), None);
+    }
+}
@@ -2,8 +2,8 @@ use std::collections::HashMap;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use file::DeleteOnDrop;
-use reqwest::Client;
+use file::{CropRect, DeleteOnDrop};
+use reqwest::{Client, Url};
 
 use crate::{PximgResolveError, PximgResolverValidationError};
 
@@ -12,10 +12,16 @@ use super::client::get_reqwest_client;
 type ImageUrl = String;
 type Filename = String;
 
+const DEFAULT_IMAGE_BASE_URL: &str = "https://booth.pximg.net";
+
 pub struct PximgResolver {
     client: reqwest::Client,
     images_dir: PathBuf,
     file_map: HashMap<ImageUrl, Filename>,
+    /// 選択用に、縮小せずに保存した画像
+    preview_map: HashMap<ImageUrl, Filename>,
+    /// この URL とスキーム・ホスト・ポートが一致する画像だけを取得する
+    image_origin: Url,
 }
 
 impl PximgResolver {
@@ -24,10 +30,18 @@ impl PximgResolver {
         P: AsRef<Path>,
         S: AsRef<str>,
     {
-        Self {
-            client: get_reqwest_client(version).expect("Failed to create reqwest client"),
+        Self::builder(images_dir, version).build()
+    }
+
+    pub fn builder<P, S>(images_dir: P, version: S) -> PximgResolverBuilder
+    where
+        P: AsRef<Path>,
+        S: AsRef<str>,
+    {
+        PximgResolverBuilder {
             images_dir: images_dir.as_ref().to_path_buf(),
-            file_map: HashMap::new(),
+            version: version.as_ref().to_string(),
+            image_base_url: DEFAULT_IMAGE_BASE_URL.to_string(),
         }
     }
 
@@ -45,14 +59,74 @@ impl PximgResolver {
 
         log::info!("Resolving image from URL: {}", url);
 
-        let bytes = fetch_image(&self.client, url).await?;
+        let bytes = fetch_image(&self.client, url, &self.image_origin).await?;
         self.encode_and_save_image(&bytes, url.to_string()).await
     }
 
+    /// サムネイルを選べるように、画像を縮小せずに一時ファイルとして保存する
+    pub async fn download_preview(&mut self, url: &str) -> Result<String, PximgResolveError> {
+        if let Some(filename) = self.preview_map.get(url) {
+            if self.images_dir.join(filename).exists() {
+                return Ok(filename.clone());
+            }
+        }
+
+        log::info!("Downloading preview image from URL: {}", url);
+
+        let bytes = fetch_image(&self.client, url, &self.image_origin).await?;
+
+        let filename = format!(
+            "temp_preview_{}.{}",
+            uuid::Uuid::new_v4(),
+            image_extension(url)
+        );
+        self.write_file(&filename, &bytes).await?;
+
+        self.preview_map.insert(url.to_string(), filename.clone());
+        Ok(filename)
+    }
+
+    /// 画像の指定された範囲をサムネイルにする
+    ///
+    /// `keep_original` が `true` の場合、詳細表示用に縮小していない画像を `file::original_image_path` に残す
+    pub async fn resolve_with_crop(
+        &mut self,
+        url: &str,
+        crop: Option<CropRect>,
+        keep_original: bool,
+    ) -> Result<String, PximgResolveError> {
+        // 選択用に取得済みであれば取得し直さない
+        let preview = self
+            .preview_map
+            .get(url)
+            .map(|filename| self.images_dir.join(filename))
+            .filter(|path| path.exists());
+
+        let bytes = match preview {
+            Some(path) => tokio::fs::read(path).await?,
+            None => fetch_image(&self.client, url, &self.image_origin).await?,
+        };
+
+        self.encode_and_save_image_with(&bytes, crop, keep_original)
+            .await
+    }
+
     async fn encode_and_save_image(
         &mut self,
         bytes: &[u8],
         string_url: String,
+    ) -> Result<String, PximgResolveError> {
+        let resized_filename = self.encode_and_save_image_with(bytes, None, false).await?;
+
+        self.file_map.insert(string_url, resized_filename.clone());
+        Ok(resized_filename)
+    }
+
+    async fn encode_and_save_image_with(
+        &self,
+        bytes: &[u8],
+        crop: Option<CropRect>,
+        keep_original: bool,
     ) -> Result<String, PximgResolveError> {
         let filename = format!("temp_{}.jpg", uuid::Uuid::new_v4());
         let original_path = self.images_dir.join(&filename);
@@ -60,51 +134,111 @@ impl PximgResolver {
         // 元のファイルはリサイズが終わったら自動で削除する
         let _cleanup = DeleteOnDrop::new(original_path.clone());
 
-        {
-            if let Some(parent) = original_path.parent() {
-                tokio::fs::create_dir_all(parent).await?;
-            }
-
-            let mut file = std::fs::File::create(&original_path)?;
-            file.write(&bytes)?;
-            file.flush()?;
-        }
+        self.write_file(&filename, bytes).await?;
 
         log::info!("Resolved and saved image to {}", original_path.display());
 
         let resized_filename = format!("temp_{}.jpg", uuid::Uuid::new_v4());
         let resized_path = self.images_dir.join(&resized_filename);
 
-        file::resize_and_encode_with_jpeg(&original_path, &resized_path)?;
+        file::crop_resize_and_encode_with_jpeg(&original_path, &resized_path, crop)?;
+
+        if keep_original {
+            let kept_path = file::original_image_path(&self.images_dir, &resized_filename);
+
+            if let Some(parent) = kept_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            file::encode_with_jpeg(&original_path, &kept_path)?;
+        }
 
-        self.file_map.insert(string_url, resized_filename.clone());
         Ok(resized_filename)
     }
+
+    async fn write_file(&self, filename: &str, bytes: &[u8]) -> Result<(), PximgResolveError> {
+        let path = self.images_dir.join(filename);
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut file = std::fs::File::create(&path)?;
+        file.write_all(bytes)?;
+        file.flush()?;
+
+        Ok(())
+    }
+}
+
+/// 取得元を変えて `PximgResolver` を作成する (主にテスト用)
+pub struct PximgResolverBuilder {
+    images_dir: PathBuf,
+    version: String,
+    image_base_url: String,
 }
 
-async fn fetch_image(client: &Client, url: &str) -> Result<Vec<u8>, PximgResolveError> {
-    validate_url(url)?;
+impl PximgResolverBuilder {
+    /// `booth.pximg.net` の代わりに画像を取得する URL (例: `http://127.0.0.1:8080`)
+    pub fn image_base_url<S: AsRef<str>>(mut self, image_base_url: S) -> Self {
+        self.image_base_url = image_base_url.as_ref().to_string();
+        self
+    }
+
+    pub fn build(self) -> PximgResolver {
+        PximgResolver {
+            client: get_reqwest_client(&self.version).expect("Failed to create reqwest client"),
+            images_dir: self.images_dir,
+            file_map: HashMap::new(),
+            preview_map: HashMap::new(),
+            image_origin: self.image_base_url.parse().expect("Invalid image base URL"),
+        }
+    }
+}
+
+async fn fetch_image(
+    client: &Client,
+    url: &str,
+    image_origin: &Url,
+) -> Result<Vec<u8>, PximgResolveError> {
+    validate_url(url, image_origin)?;
 
     let bytes = client.get(url).send().await?.bytes().await?;
     Ok(bytes.to_vec())
 }
 
-fn validate_url(url: &str) -> Result<(), PximgResolverValidationError> {
+/// 表示できるように、URL の拡張子を引き継ぐ (不明な場合は `jpg`)
+fn image_extension(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            let extension = Path::new(url.path())
+                .extension()?
+                .to_str()?
+                .to_ascii_lowercase();
+            ["jpg", "jpeg", "png", "gif", "webp"]
+                .contains(&extension.as_str())
+                .then_some(extension)
+        })
+        .unwrap_or_else(|| "jpg".to_string())
+}
+
+fn validate_url(url: &str, image_origin: &Url) -> Result<(), PximgResolverValidationError> {
     let url = url
-        .parse::<reqwest::Url>()
+        .parse::<Url>()
         .map_err(|e| PximgResolverValidationError::ParseError(e.to_string()))?;
 
-    if url.scheme() != "https" {
+    if url.scheme() != image_origin.scheme() {
         return Err(PximgResolverValidationError::InvalidScheme(
             url.scheme().to_string(),
         ));
     }
 
-    let domain = url.domain();
-    if domain != Some("booth.pximg.net") {
+    if url.host_str() != image_origin.host_str()
+        || url.port_or_known_default() != image_origin.port_or_known_default()
+    {
         return Err(PximgResolverValidationError::InvalidDomain(format!(
             "{:?}",
-            domain
+            url.host_str()
         )));
     }
 
@@ -113,8 +247,95 @@ fn validate_url(url: &str) -> Result<(), PximgResolverValidationError> {
 
 #[cfg(test)]
 mod tests {
+    use crate::test_server::MockServer;
+
     use super::*;
 
+    fn default_origin() -> Url {
+        DEFAULT_IMAGE_BASE_URL.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_from_mock_server() {
+        let temp_dir = PathBuf::from("test/temp/pximg-resolve-test/");
+
+        if std::fs::exists(&temp_dir).unwrap() {
+            std::fs::remove_dir_all(&temp_dir).unwrap();
+        }
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let server = MockServer::with_fixtures();
+        let mut resolver = PximgResolver::builder(temp_dir.clone(), "0.0.0+cargo-test")
+            .image_base_url(server.base_url())
+            .build();
+
+        let url = format!("{}/images/thumbnail.jpg", server.base_url());
+        let filename = resolver.resolve(&url).await.unwrap();
+        assert!(std::fs::exists(temp_dir.join(&filename)).unwrap());
+
+        // 2 回目は取得し直さない
+        assert_eq!(resolver.resolve(&url).await.unwrap(), filename);
+        assert_eq!(server.requests(), vec!["/images/thumbnail.jpg"]);
+
+        // 設定した取得元以外の画像は取得しない
+        let result = resolver
+            .resolve("https://booth.pximg.net/dummy-path.jpg")
+            .await;
+        assert!(matches!(
+            result,
+            Err(PximgResolveError::ValidationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_with_crop_from_preview() {
+        let temp_dir = PathBuf::from("test/temp/pximg-crop-test/");
+
+        if std::fs::exists(&temp_dir).unwrap() {
+            std::fs::remove_dir_all(&temp_dir).unwrap();
+        }
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let server = MockServer::with_fixtures();
+        let mut resolver = PximgResolver::builder(temp_dir.clone(), "0.0.0+cargo-test")
+            .image_base_url(server.base_url())
+            .build();
+
+        let url = format!("{}/images/thumbnail.jpg", server.base_url());
+        let preview = resolver.download_preview(&url).await.unwrap();
+        assert!(preview.starts_with("temp_preview_"));
+        assert!(preview.ends_with(".jpg"));
+        assert_eq!(resolver.download_preview(&url).await.unwrap(), preview);
+
+        let crop = CropRect {
+            x: 0.0,
+            y: 0.0,
+            width: 0.5,
+            height: 0.5,
+        };
+        let filename = resolver
+            .resolve_with_crop(&url, Some(crop), true)
+            .await
+            .unwrap();
+
+        assert!(std::fs::exists(temp_dir.join(&filename)).unwrap());
+        assert!(std::fs::exists(file::original_image_path(&temp_dir, &filename)).unwrap());
+
+        // 選択用に取得した画像を使い回す
+        assert_eq!(server.requests(), vec!["/images/thumbnail.jpg"]);
+    }
+
+    #[test]
+    fn test_image_extension() {
+        assert_eq!(image_extension("https://booth.pximg.net/a/b.PNG"), "png");
+        assert_eq!(
+            image_extension("https://booth.pximg.net/a/b.jpg?c=d"),
+            "jpg"
+        );
+        assert_eq!(image_extension("https://booth.pximg.net/a/b"), "jpg");
+        assert_eq!(image_extension("https://booth.pximg.net/a/b.exe"), "jpg");
+    }
+
     #[tokio::test]
     async fn test_encode_and_save_image() {
         let temp_dir = PathBuf::from("test/temp/pximg-encode-test/");
@@ -174,40 +395,60 @@ mod tests {
     fn test_validate_url() {
         // 適切
         matches!(
-            validate_url("https://booth.pximg.net/dummy-path.jpg"),
+            validate_url("https://booth.pximg.net/dummy-path.jpg", &default_origin()),
             Ok(())
         );
         matches!(
-            validate_url("https://booth.pximg.net/path1/path2/something.png"),
+            validate_url(
+                "https://booth.pximg.net/path1/path2/something.png",
+                &default_origin()
+            ),
             Ok(())
         );
 
         // ドメインが不適
         matches!(
-            validate_url("https://dummy.konoasset.dev/dummy-path.jpg"),
+            validate_url(
+                "https://dummy.konoasset.dev/dummy-path.jpg",
+                &default_origin()
+            ),
             Err(PximgResolverValidationError::InvalidDomain(_))
         );
         matches!(
-            validate_url("https://i.pximg.net/dummy-path.jpg"),
+            validate_url("https://i.pximg.net/dummy-path.jpg", &default_origin()),
             Err(PximgResolverValidationError::InvalidDomain(_))
         );
         matches!(
-            validate_url("https://pximg.net/path1/path2/dummy-path.png"),
+            validate_url(
+                "https://pximg.net/path1/path2/dummy-path.png",
+                &default_origin()
+            ),
             Err(PximgResolverValidationError::InvalidDomain(_))
         );
 
         // プロトコルが不適
         matches!(
-            validate_url("http://booth.pximg.net/dummy-path.jpg"),
+            validate_url("http://booth.pximg.net/dummy-path.jpg", &default_origin()),
             Err(PximgResolverValidationError::InvalidScheme(_))
         );
         matches!(
-            validate_url("ftp://booth.pximg.net/dummy-path.jpg"),
+            validate_url("ftp://booth.pximg.net/dummy-path.jpg", &default_origin()),
             Err(PximgResolverValidationError::InvalidScheme(_))
         );
         matches!(
-            validate_url("file://dummy.konoasset.dev/dummy-path.jpg"),
+            validate_url(
+                "file://dummy.konoasset.dev/dummy-path.jpg",
+                &default_origin()
+            ),
             Err(PximgResolverValidationError::InvalidScheme(_))
         );
+
+        // 取得元を変えた場合は、ポートも一致する必要がある
+        let origin: Url = "http://127.0.0.1:8080".parse().unwrap();
+        assert!(validate_url("http://127.0.0.1:8080/dummy-path.jpg", &origin).is_ok());
+        assert!(matches!(
+            validate_url("http://127.0.0.1:9090/dummy-path.jpg", &origin),
+            Err(PximgResolverValidationError::InvalidDomain(_))
+        ));
     }
 }
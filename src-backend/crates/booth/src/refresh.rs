@@ -0,0 +1,197 @@
+use model::{AssetDescription, preference::BoothNameSource};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::definitions::BoothAssetInfo;
+
+/// BOOTH から取得し直して上書きできる項目
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, specta::Type)]
+pub enum BoothMetadataField {
+    Name,
+    Creator,
+    PublishedAt,
+    Image,
+}
+
+/// 比較する項目の値
+///
+/// `image` は、登録済みのアセットではサムネイルのファイル名、BOOTH では 1 枚目の画像の URL になる
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothMetadataValues {
+    pub name: String,
+    pub creator: String,
+    pub published_at: Option<i64>,
+    pub image: Option<String>,
+}
+
+impl BoothMetadataValues {
+    pub fn from_description(description: &AssetDescription) -> Self {
+        Self {
+            name: description.name.clone(),
+            creator: description.creator.clone(),
+            published_at: description.published_at,
+            image: description.image_filename.clone(),
+        }
+    }
+
+    pub fn from_info(info: &BoothAssetInfo, name_source: BoothNameSource) -> Self {
+        Self {
+            name: info.name_for(name_source).to_string(),
+            creator: info.creator.clone(),
+            published_at: Some(info.published_at),
+            image: info.image_urls.first().cloned(),
+        }
+    }
+}
+
+/// 登録済みのアセットと、BOOTH から取得し直した情報の差分
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothMetadataDiff {
+    pub asset_id: Uuid,
+    pub booth_item_id: u64,
+    pub current: BoothMetadataValues,
+    /// 取得に失敗した場合は `None`
+    pub fetched: Option<BoothMetadataValues>,
+    pub changed_fields: Vec<BoothMetadataField>,
+    pub error: Option<String>,
+}
+
+/// アセットに反映する項目
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothMetadataUpdate {
+    pub asset_id: Uuid,
+    pub fields: Vec<BoothMetadataField>,
+}
+
+/// 値が異なる項目を返す
+///
+/// 画像は、サムネイルの作成に使った BOOTH の画像の URL (`source_image_url`) と比較する。
+/// URL が記録されていない場合は内容を比較できないため、アセットにサムネイルがなく BOOTH に画像がある場合のみ変更ありとする
+pub fn diff_booth_metadata(
+    current: &BoothMetadataValues,
+    fetched: &BoothMetadataValues,
+    source_image_url: Option<&str>,
+) -> Vec<BoothMetadataField> {
+    let mut changed_fields = vec![];
+
+    if current.name != fetched.name {
+        changed_fields.push(BoothMetadataField::Name);
+    }
+    if current.creator != fetched.creator {
+        changed_fields.push(BoothMetadataField::Creator);
+    }
+    if current.published_at != fetched.published_at {
+        changed_fields.push(BoothMetadataField::PublishedAt);
+    }
+    if let Some(fetched_image) = &fetched.image {
+        let image_changed = match (&current.image, source_image_url) {
+            (None, _) => true,
+            (Some(_), Some(source_image_url)) => source_image_url != fetched_image,
+            (Some(_), None) => false,
+        };
+
+        if image_changed {
+            changed_fields.push(BoothMetadataField::Image);
+        }
+    }
+
+    changed_fields
+}
+
+/// 選択された項目を取得し直した値で上書きする
+///
+/// 画像は URL から取得してサムネイルにする必要があるため、ここでは扱わない
+pub fn apply_booth_metadata(
+    description: &mut AssetDescription,
+    fetched: &BoothMetadataValues,
+    fields: &[BoothMetadataField],
+) {
+    for field in fields {
+        match field {
+            BoothMetadataField::Name => description.name = fetched.name.clone(),
+            BoothMetadataField::Creator => description.creator = fetched.creator.clone(),
+            BoothMetadataField::PublishedAt => description.published_at = fetched.published_at,
+            BoothMetadataField::Image => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use model::AssetDataLocation;
+
+    use super::*;
+
+    fn create_description() -> AssetDescription {
+        AssetDescription {
+            name: "Old Name".to_string(),
+            creator: "Test Creator".to_string(),
+            image_filename: None,
+            tags: vec!["tag".to_string()],
+            memo: Some("memo".to_string()),
+            booth_item_id: Some(1),
+            dependencies: vec![],
+            created_at: 0,
+            published_at: Some(100),
+            data_location: AssetDataLocation::Local,
+        }
+    }
+
+    #[test]
+    fn test_diff_and_apply_booth_metadata() {
+        let mut description = create_description();
+        let current = BoothMetadataValues::from_description(&description);
+        let fetched = BoothMetadataValues {
+            name: "New Name".to_string(),
+            creator: "Test Creator".to_string(),
+            published_at: Some(200),
+            image: Some("https://booth.pximg.net/dummy.jpg".to_string()),
+        };
+
+        assert_eq!(
+            diff_booth_metadata(&current, &fetched, None),
+            vec![
+                BoothMetadataField::Name,
+                BoothMetadataField::PublishedAt,
+                BoothMetadataField::Image,
+            ]
+        );
+
+        // 選択された項目だけを上書きする
+        apply_booth_metadata(
+            &mut description,
+            &fetched,
+            &[BoothMetadataField::Name, BoothMetadataField::Image],
+        );
+
+        assert_eq!(description.name, "New Name");
+        assert_eq!(description.published_at, Some(100));
+        assert_eq!(description.image_filename, None);
+        assert_eq!(description.memo, Some("memo".to_string()));
+
+        // サムネイルの元の画像が記録されていなければ、画像は比較できないため変更なしとする
+        description.image_filename = Some("image.jpg".to_string());
+        let current = BoothMetadataValues::from_description(&description);
+        assert_eq!(
+            diff_booth_metadata(&current, &fetched, None),
+            vec![BoothMetadataField::PublishedAt]
+        );
+
+        // 記録されている元の画像の URL と比較する
+        assert_eq!(
+            diff_booth_metadata(
+                &current,
+                &fetched,
+                Some("https://booth.pximg.net/dummy.jpg")
+            ),
+            vec![BoothMetadataField::PublishedAt]
+        );
+        assert_eq!(
+            diff_booth_metadata(&current, &fetched, Some("https://booth.pximg.net/old.jpg")),
+            vec![BoothMetadataField::PublishedAt, BoothMetadataField::Image]
+        );
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::purchase::{normalize_text, parse_item_id};
+
+/// 祖先要素をさかのぼって価格を探す最大の深さ
+const MAX_CONTAINER_DEPTH: usize = 8;
+
+/// BOOTH のショップに出品されているアイテム
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothShopItem {
+    pub id: u64,
+    pub name: Option<String>,
+    /// 表示用の価格 (例: `¥ 500`)
+    pub price: Option<String>,
+    pub thumbnail_url: Option<String>,
+}
+
+/// ショップのアイテムと、そのアイテムが紐づいている登録済みのアセット
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothShopItemEntry {
+    pub item: BoothShopItem,
+    pub owned_asset_ids: Vec<Uuid>,
+}
+
+/// `https://shop.booth.pm/` の形式の URL、またはサブドメインそのものからショップのサブドメインを取得する
+pub fn parse_shop_subdomain(input: &str) -> Option<String> {
+    let input = input.trim();
+
+    let subdomain = match Url::parse(input) {
+        Ok(url) => url.host_str()?.strip_suffix(".booth.pm")?.to_string(),
+        Err(_) => input.to_lowercase(),
+    };
+
+    let valid = !subdomain.is_empty()
+        && subdomain
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !["www", "accounts", "checkout", "manage", "asset"].contains(&subdomain.as_str());
+
+    if valid { Some(subdomain) } else { None }
+}
+
+/// 保存されたショップのアイテム一覧 (JSON または HTML) から、アイテムを抽出する (通信は行わない)
+///
+/// HTML の場合、他のショップのアイテムへのリンク (おすすめ等) は含めない
+pub fn parse_shop_items(content: &str, shop_subdomain: &str) -> Vec<BoothShopItem> {
+    if let Ok(value) = serde_json::from_str::<Value>(content) {
+        return parse_json(&value);
+    }
+
+    parse_html(content, shop_subdomain)
+}
+
+/// ショップのアイテムに、同じ BOOTH のアイテムが紐づいている登録済みのアセットを対応付ける
+pub fn cross_reference_shop_items(
+    items: Vec<BoothShopItem>,
+    linked_assets: &HashMap<Uuid, u64>,
+) -> Vec<BoothShopItemEntry> {
+    let mut owned: HashMap<u64, Vec<Uuid>> = HashMap::new();
+    for (asset_id, booth_item_id) in linked_assets {
+        owned.entry(*booth_item_id).or_default().push(*asset_id);
+    }
+
+    items
+        .into_iter()
+        .map(|item| {
+            let mut owned_asset_ids = owned.get(&item.id).cloned().unwrap_or_default();
+            owned_asset_ids.sort();
+
+            BoothShopItemEntry {
+                item,
+                owned_asset_ids,
+            }
+        })
+        .collect()
+}
+
+/// アイテムの配列、または `items` にアイテムの配列を持つオブジェクトを読み取る
+fn parse_json(value: &Value) -> Vec<BoothShopItem> {
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(object) => match object.get("items") {
+            Some(Value::Array(items)) => items,
+            _ => return vec![],
+        },
+        _ => return vec![],
+    };
+
+    let mut result: Vec<BoothShopItem> = vec![];
+
+    for item in items {
+        let id = match item.get("id") {
+            Some(Value::Number(id)) => id.as_u64(),
+            Some(Value::String(id)) => id.parse().ok(),
+            _ => None,
+        };
+        let Some(id) = id else {
+            continue;
+        };
+
+        if result.iter().any(|item| item.id == id) {
+            continue;
+        }
+
+        let price = match item.get("price") {
+            Some(Value::String(price)) => Some(price.clone()),
+            Some(Value::Number(price)) => Some(format!("¥ {}", price)),
+            _ => None,
+        };
+
+        let thumbnail_url = item
+            .get("thumbnail_url")
+            .and_then(Value::as_str)
+            .or_else(|| {
+                item.pointer("/thumbnail_image_urls/0")
+                    .and_then(Value::as_str)
+            })
+            .or_else(|| item.pointer("/images/0/original").and_then(Value::as_str))
+            .map(str::to_string);
+
+        result.push(BoothShopItem {
+            id,
+            name: normalize_text(item.get("name").and_then(Value::as_str)),
+            price,
+            thumbnail_url,
+        });
+    }
+
+    result
+}
+
+fn parse_html(content: &str, shop_subdomain: &str) -> Vec<BoothShopItem> {
+    let Ok(shop_url) = Url::parse(&format!("https://{}.booth.pm/", shop_subdomain)) else {
+        return vec![];
+    };
+
+    let document = Html::parse_document(content);
+    let anchor_selector = Selector::parse("a[href]").unwrap();
+    let image_selector = Selector::parse("img").unwrap();
+    let price_selector = Selector::parse("[class*=\"price\"]").unwrap();
+
+    let mut items: Vec<BoothShopItem> = vec![];
+    let mut indexes: HashMap<u64, usize> = HashMap::new();
+
+    for anchor in document.select(&anchor_selector) {
+        let Some(id) = anchor
+            .value()
+            .attr("href")
+            .and_then(|href| resolve_shop_item_id(&shop_url, href))
+        else {
+            continue;
+        };
+
+        let index = *indexes.entry(id).or_insert_with(|| {
+            items.push(BoothShopItem {
+                id,
+                name: None,
+                price: None,
+                thumbnail_url: None,
+            });
+            items.len() - 1
+        });
+        let item = &mut items[index];
+
+        let image = anchor.select(&image_selector).next();
+
+        if item.name.is_none() {
+            // 画像のみのリンクの場合は alt をアイテム名として扱う
+            item.name = normalize_text(anchor.text())
+                .or_else(|| image.and_then(|image| normalize_text(image.value().attr("alt"))));
+        }
+
+        if item.thumbnail_url.is_none() {
+            // 遅延読み込みの場合は data-original に本来の URL がある
+            item.thumbnail_url = image.and_then(|image| {
+                image
+                    .value()
+                    .attr("data-original")
+                    .or_else(|| image.value().attr("src"))
+                    .map(str::to_string)
+            });
+        }
+
+        if item.price.is_none() {
+            item.price = find_price(anchor, &shop_url, id, &anchor_selector, &price_selector);
+        }
+    }
+
+    items
+}
+
+/// ショップ内のアイテムへのリンク (相対パスを含む) であれば、アイテムIDを返す
+fn resolve_shop_item_id(shop_url: &Url, href: &str) -> Option<u64> {
+    let url = shop_url.join(href.trim()).ok()?;
+
+    if url.host_str() != shop_url.host_str() {
+        return None;
+    }
+
+    parse_item_id(url.as_str())
+}
+
+/// アイテムのリンクを含む要素をさかのぼり、同じ要素内にある価格を探す
+///
+/// 他のアイテムへのリンクを含む要素まで到達した場合は、一覧全体とみなして探索をやめる
+fn find_price(
+    anchor: ElementRef,
+    shop_url: &Url,
+    item_id: u64,
+    anchor_selector: &Selector,
+    price_selector: &Selector,
+) -> Option<String> {
+    let mut current = anchor;
+
+    for _ in 0..MAX_CONTAINER_DEPTH {
+        current = current.parent().and_then(ElementRef::wrap)?;
+
+        let other_item = current.select(anchor_selector).any(|candidate| {
+            candidate
+                .value()
+                .attr("href")
+                .and_then(|href| resolve_shop_item_id(shop_url, href))
+                .is_some_and(|id| id != item_id)
+        });
+        if other_item {
+            return None;
+        }
+
+        if let Some(price) = current
+            .select(price_selector)
+            .find_map(|element| normalize_text(element.text()))
+        {
+            return Some(price);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shop_items_html() {
+        let content = include_str!("../test/shop_items.html");
+
+        let result = parse_shop_items(content, "silolab");
+
+        assert_eq!(
+            result,
+            vec![
+                BoothShopItem {
+                    id: 6641548,
+                    name: Some("KonoAsset - VRChat向けアセット管理ツール".into()),
+                    price: Some("¥ 0".into()),
+                    thumbnail_url: Some(
+                        "https://booth.pximg.net/c/300x300_a2_g5/dummy/6641548_base_resized.jpg"
+                            .into()
+                    ),
+                },
+                BoothShopItem {
+                    id: 1234567,
+                    name: Some("Sample Outfit".into()),
+                    price: Some("¥ 1,500".into()),
+                    thumbnail_url: Some(
+                        "https://booth.pximg.net/c/300x300_a2_g5/dummy/1234567_base_resized.jpg"
+                            .into()
+                    ),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_shop_items_json() {
+        let content = include_str!("../test/shop_items.json");
+
+        let result = parse_shop_items(content, "silolab");
+
+        assert_eq!(
+            result,
+            vec![
+                BoothShopItem {
+                    id: 6641548,
+                    name: Some("KonoAsset - VRChat向けアセット管理ツール".into()),
+                    price: Some("¥ 0".into()),
+                    thumbnail_url: Some(
+                        "https://booth.pximg.net/c/300x300_a2_g5/dummy/6641548_base_resized.jpg"
+                            .into()
+                    ),
+                },
+                BoothShopItem {
+                    id: 1234567,
+                    name: Some("Sample Outfit".into()),
+                    price: Some("¥ 1500".into()),
+                    thumbnail_url: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cross_reference_shop_items() {
+        let items = vec![
+            BoothShopItem {
+                id: 1,
+                name: None,
+                price: None,
+                thumbnail_url: None,
+            },
+            BoothShopItem {
+                id: 2,
+                name: None,
+                price: None,
+                thumbnail_url: None,
+            },
+        ];
+        let asset_id = Uuid::new_v4();
+        let linked_assets = HashMap::from([(asset_id, 2), (Uuid::new_v4(), 3)]);
+
+        let result = cross_reference_shop_items(items, &linked_assets);
+
+        assert_eq!(
+            result
+                .iter()
+                .map(|entry| (entry.item.id, entry.owned_asset_ids.clone()))
+                .collect::<Vec<_>>(),
+            vec![(1, vec![]), (2, vec![asset_id])]
+        );
+    }
+
+    #[test]
+    fn test_parse_shop_subdomain() {
+        assert_eq!(
+            parse_shop_subdomain("https://silolab.booth.pm/"),
+            Some("silolab".into())
+        );
+        assert_eq!(
+            parse_shop_subdomain("https://sample-shop.booth.pm/items"),
+            Some("sample-shop".into())
+        );
+        assert_eq!(parse_shop_subdomain(" silolab "), Some("silolab".into()));
+        assert_eq!(parse_shop_subdomain("https://booth.pm/"), None);
+        assert_eq!(parse_shop_subdomain("https://accounts.booth.pm/"), None);
+        assert_eq!(parse_shop_subdomain("https://example.com/"), None);
+        assert_eq!(parse_shop_subdomain("shop/../items"), None);
+    }
+}
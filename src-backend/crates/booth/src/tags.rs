@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use model::preference::BoothTagMapping;
+
+/// BOOTH のタグを、インポート時に付けるタグに変換する
+///
+/// 規則が設定されているタグは規則に従って置き換え、そうでなければ登録済みのタグと一致するものだけを取り込む
+pub fn map_booth_tags(
+    booth_tags: &[String],
+    mappings: &[BoothTagMapping],
+    known_tags: &HashSet<String>,
+) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+
+    for booth_tag in booth_tags {
+        let booth_tag = booth_tag.trim();
+
+        let mapping = mappings
+            .iter()
+            .find(|mapping| mapping.booth_tag.trim().eq_ignore_ascii_case(booth_tag));
+
+        let tags: Vec<String> = match mapping {
+            Some(mapping) => mapping.tags.clone(),
+            // 登録済みのタグの表記に合わせる
+            None => known_tags
+                .iter()
+                .find(|tag| tag.eq_ignore_ascii_case(booth_tag))
+                .cloned()
+                .into_iter()
+                .collect(),
+        };
+
+        for tag in tags {
+            if !tag.is_empty() && !result.contains(&tag) {
+                result.push(tag);
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_booth_tags() {
+        let booth_tags: Vec<String> = vec![
+            "VRChat".into(),
+            "衣装".into(),
+            "ぷちぷち".into(),
+            "unknown".into(),
+            "Quest対応".into(),
+        ];
+
+        let mappings = vec![
+            BoothTagMapping {
+                booth_tag: "vrchat".into(),
+                tags: vec![],
+            },
+            BoothTagMapping {
+                booth_tag: "衣装".into(),
+                tags: vec!["Outfit".into(), "Clothes".into()],
+            },
+        ];
+
+        let known_tags: HashSet<String> =
+            HashSet::from(["Outfit".to_string(), "quest対応".to_string()]);
+
+        assert_eq!(
+            map_booth_tags(&booth_tags, &mappings, &known_tags),
+            vec!["Outfit", "Clothes", "quest対応"]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn new<B: Into<Vec<u8>>>(status: u16, body: B) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// テスト用に、ローカルで HTTP のレスポンスを返すサーバー
+///
+/// `push` したレスポンスがあれば登録した順に返し、なければパスごとに `route` したレスポンスを返す
+/// どちらにもない場合は 404 を返す
+pub struct MockServer {
+    base_url: String,
+    responses: Arc<Mutex<VecDeque<MockResponse>>>,
+    routes: Arc<Mutex<HashMap<String, MockResponse>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let responses: Arc<Mutex<VecDeque<MockResponse>>> = Arc::default();
+        let routes: Arc<Mutex<HashMap<String, MockResponse>>> = Arc::default();
+        let requests: Arc<Mutex<Vec<String>>> = Arc::default();
+
+        let (cloned_responses, cloned_routes, cloned_requests) =
+            (responses.clone(), routes.clone(), requests.clone());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                // ヘッダーは読み飛ばす
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                }
+
+                let path = request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                cloned_requests.lock().unwrap().push(path.clone());
+
+                let response = cloned_responses
+                    .lock()
+                    .unwrap()
+                    .pop_front()
+                    .or_else(|| cloned_routes.lock().unwrap().get(&path).cloned())
+                    .unwrap_or_else(|| MockResponse::new(404, "Not Found"));
+
+                let mut head = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    response.status,
+                    response.body.len()
+                );
+                for (name, value) in &response.headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&response.body);
+            }
+        });
+
+        Self {
+            base_url,
+            responses,
+            routes,
+            requests,
+        }
+    }
+
+    /// `test/` 以下のフィクスチャを、BOOTH と同じパスで返すサーバーを起動する
+    ///
+    /// - `/ja/items/6641548.json`: アイテムの情報
+    /// - `/images/thumbnail.jpg`: サンプル画像
+    pub fn with_fixtures() -> Self {
+        let server = Self::start();
+
+        server.route(
+            "/ja/items/6641548.json",
+            MockResponse::new(200, include_str!("../test/6641548.json"))
+                .with_header("Content-Type", "application/json"),
+        );
+        server.route(
+            "/images/thumbnail.jpg",
+            MockResponse::new(200, include_bytes!("../../../test/images/thumbnail.jpg"))
+                .with_header("Content-Type", "image/jpeg"),
+        );
+
+        server
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 指定したパスへのリクエストに、常に同じレスポンスを返す
+    pub fn route(&self, path: &str, response: MockResponse) {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), response);
+    }
+
+    pub fn push(&self, response: MockResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    /// 受け取ったリクエストのパス
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,409 @@
+use std::{collections::HashMap, fs::File, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::definitions::BoothAssetInfo;
+
+/// 更新の有無を判定するために記録する、BOOTH のアイテムの状態
+///
+/// 説明文や価格の編集でも公開日時が変わることがあるため、バリエーションの構成と配布されているファイルだけを比較する
+/// (以前に記録した `publishedAt` は読み込み時に無視される)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct BoothItemSnapshot {
+    /// 以前の記録には含まれないため、その場合は `None` (不明) になる
+    #[serde(default)]
+    variations: Option<Vec<BoothVariationSnapshot>>,
+    /// 有料のアイテムでは取得できないため、空の場合は不明として扱う
+    downloadable_files: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct BoothVariationSnapshot {
+    id: u64,
+    name: Option<String>,
+}
+
+impl BoothItemSnapshot {
+    /// 両方で分かっている部分だけを比較し、異なっていれば `true` を返す
+    fn differs_from(&self, other: &Self) -> bool {
+        let variations_changed = match (&self.variations, &other.variations) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        };
+
+        let files_changed = !self.downloadable_files.is_empty()
+            && !other.downloadable_files.is_empty()
+            && self.downloadable_files != other.downloadable_files;
+
+        variations_changed || files_changed
+    }
+
+    /// 不明な部分を `other` の内容で補う
+    fn fill_unknown(&mut self, other: &Self) {
+        if self.variations.is_none() {
+            self.variations = other.variations.clone();
+        }
+
+        if self.downloadable_files.is_empty() {
+            self.downloadable_files = other.downloadable_files.clone();
+        }
+    }
+}
+
+impl From<&BoothAssetInfo> for BoothItemSnapshot {
+    fn from(info: &BoothAssetInfo) -> Self {
+        let mut variations = info
+            .variations
+            .iter()
+            .map(|variation| BoothVariationSnapshot {
+                id: variation.id,
+                name: variation.name.clone(),
+            })
+            .collect::<Vec<_>>();
+        variations.sort_by_key(|variation| variation.id);
+
+        Self {
+            variations: Some(variations),
+            downloadable_files: info.downloadable_file_names(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct BoothItemRecord {
+    booth_item_id: u64,
+    /// インポート時 (または更新を確認済みにした時) の状態
+    recorded: BoothItemSnapshot,
+    /// 最後に確認した時の状態
+    latest: Option<BoothItemSnapshot>,
+    /// サムネイルを BOOTH の画像から作成した場合の、その画像の URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image_url: Option<String>,
+}
+
+impl BoothItemRecord {
+    fn new(booth_item_id: u64, info: &BoothAssetInfo) -> Self {
+        Self {
+            booth_item_id,
+            recorded: info.into(),
+            latest: None,
+            image_url: None,
+        }
+    }
+
+    fn update_available(&self) -> bool {
+        self.latest
+            .as_ref()
+            .is_some_and(|latest| latest.differs_from(&self.recorded))
+    }
+}
+
+/// 所有しているアセットの BOOTH 上での状態を記録し、作者による更新を検出する
+pub struct BoothUpdateTracker {
+    path: PathBuf,
+    records: HashMap<Uuid, BoothItemRecord>,
+}
+
+impl BoothUpdateTracker {
+    pub fn load_or_default(path: PathBuf) -> Self {
+        if !path.exists() {
+            return Self {
+                path,
+                records: HashMap::new(),
+            };
+        }
+
+        let records = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|e| {
+                log::error!("Failed to parse BOOTH update records: {}", e);
+                HashMap::new()
+            }),
+            Err(e) => {
+                log::error!("Failed to open BOOTH update records: {}", e);
+                HashMap::new()
+            }
+        };
+
+        Self { path, records }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let file = File::create(&self.path)
+            .map_err(|e| format!("Failed to create BOOTH update records file: {}", e))?;
+
+        serde_json::to_writer(file, &self.records)
+            .map_err(|e| format!("Failed to write BOOTH update records to file: {}", e))
+    }
+
+    /// インポート時の状態を記録する
+    pub fn record(&mut self, asset_id: Uuid, info: &BoothAssetInfo) {
+        self.records
+            .insert(asset_id, BoothItemRecord::new(info.id, info));
+    }
+
+    /// 取得した最新の状態と記録を比較し、更新があれば `true` を返す
+    ///
+    /// 記録がない場合や、アセットに紐づく BOOTH のアイテムが変わっていた場合は、現在の状態を記録し直す。
+    /// そのため、記録される前に公開された更新は検出されず、次にバリエーションや配布されるファイルが変わった時から検出される
+    pub fn check(&mut self, asset_id: Uuid, info: &BoothAssetInfo) -> bool {
+        match self.records.get_mut(&asset_id) {
+            Some(record) if record.booth_item_id == info.id => {
+                let latest = BoothItemSnapshot::from(info);
+
+                // 記録時に分からなかった部分は、比較できるよう今回の状態を記録する
+                record.recorded.fill_unknown(&latest);
+                record.latest = Some(latest);
+                record.update_available()
+            }
+            _ => {
+                self.record(asset_id, info);
+                false
+            }
+        }
+    }
+
+    pub fn is_update_available(&self, asset_id: Uuid) -> bool {
+        self.records
+            .get(&asset_id)
+            .is_some_and(|record| record.update_available())
+    }
+
+    pub fn get_update_available_ids(&self) -> Vec<Uuid> {
+        self.records
+            .iter()
+            .filter(|(_, record)| record.update_available())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// サムネイルの作成に使った BOOTH の画像の URL を記録する
+    ///
+    /// 記録がない場合や、アセットに紐づく BOOTH のアイテムが変わっていた場合は、現在の状態も記録し直す
+    pub fn record_image_url(&mut self, asset_id: Uuid, info: &BoothAssetInfo, image_url: String) {
+        let record = self
+            .records
+            .entry(asset_id)
+            .and_modify(|record| {
+                if record.booth_item_id != info.id {
+                    *record = BoothItemRecord::new(info.id, info);
+                }
+            })
+            .or_insert_with(|| BoothItemRecord::new(info.id, info));
+
+        record.image_url = Some(image_url);
+    }
+
+    /// サムネイルの作成に使った BOOTH の画像の URL (記録されていない場合は `None`)
+    pub fn get_image_url(&self, asset_id: Uuid) -> Option<&str> {
+        self.records
+            .get(&asset_id)
+            .and_then(|record| record.image_url.as_deref())
+    }
+
+    /// 最後に確認した状態を記録し直し、更新を確認済みにする
+    pub fn acknowledge(&mut self, asset_id: Uuid) -> Result<(), String> {
+        let record = self
+            .records
+            .get_mut(&asset_id)
+            .ok_or_else(|| format!("BOOTH update record not found: {}", asset_id))?;
+
+        if let Some(mut latest) = record.latest.take() {
+            latest.fill_unknown(&record.recorded);
+            record.recorded = latest;
+        }
+
+        Ok(())
+    }
+
+    /// 削除されたアセットの記録を取り除く
+    ///
+    /// 記録は全てのライブラリで共有されるため、現在のライブラリにないアセットの記録をまとめて取り除いてはならない
+    pub fn remove(&mut self, asset_id: Uuid) {
+        self.records.remove(&asset_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BoothVariation;
+
+    use super::*;
+
+    fn create_info(id: u64, published_at: i64, files: &[&str]) -> BoothAssetInfo {
+        create_info_with_variations(id, published_at, &[(1, None)], files)
+    }
+
+    fn create_info_with_variations(
+        id: u64,
+        published_at: i64,
+        variations: &[(u64, Option<&str>)],
+        files: &[&str],
+    ) -> BoothAssetInfo {
+        BoothAssetInfo {
+            id,
+            name: "Test Asset".to_string(),
+            localized_name: None,
+            creator: "Test Creator".to_string(),
+            estimated_asset_type: None,
+            category_id: 0,
+            image_urls: vec![],
+            published_at,
+            price: "¥ 500".to_string(),
+            description: String::new(),
+            tags: vec![],
+            shop_url: "https://test.booth.pm/".to_string(),
+            shop_subdomain: "test".to_string(),
+            variations: variations
+                .iter()
+                .map(|(id, name)| BoothVariation {
+                    id: *id,
+                    name: name.map(|name| name.to_string()),
+                    price: 500,
+                    downloadable_files: files.iter().map(|file| file.to_string()).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_detect_update() {
+        let dir = PathBuf::from("test/temp/booth_update/detect");
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("booth_updates.json");
+        let mut tracker = BoothUpdateTracker::load_or_default(path.clone());
+
+        let (recorded, unknown) = (Uuid::new_v4(), Uuid::new_v4());
+        tracker.record(recorded, &create_info(1, 100, &["v1.zip"]));
+
+        // 変更がなければ更新なし
+        assert!(!tracker.check(recorded, &create_info(1, 100, &["v1.zip"])));
+
+        // 記録がないものは現在の状態を記録するだけ
+        assert!(!tracker.check(unknown, &create_info(2, 100, &["v1.zip"])));
+        assert!(!tracker.check(unknown, &create_info(2, 100, &["v1.zip"])));
+
+        assert!(tracker.check(recorded, &create_info(1, 100, &["v1.zip", "v2.zip"])));
+        assert_eq!(tracker.get_update_available_ids(), vec![recorded]);
+
+        tracker.save().unwrap();
+        let mut tracker = BoothUpdateTracker::load_or_default(path);
+        assert!(tracker.is_update_available(recorded));
+
+        tracker.acknowledge(recorded).unwrap();
+        assert!(!tracker.is_update_available(recorded));
+        assert!(!tracker.check(recorded, &create_info(1, 100, &["v1.zip", "v2.zip"])));
+
+        // 公開日時だけが変わった場合は更新として扱わない
+        assert!(!tracker.check(recorded, &create_info(1, 200, &["v1.zip", "v2.zip"])));
+        assert!(tracker.check(recorded, &create_info(1, 200, &["v2.zip", "v3.zip"])));
+
+        tracker.remove(recorded);
+        assert!(!tracker.is_update_available(recorded));
+        assert!(tracker.acknowledge(recorded).is_err());
+    }
+
+    #[test]
+    fn test_detect_update_of_paid_item() {
+        let mut tracker = BoothUpdateTracker::load_or_default(PathBuf::from(
+            "test/temp/booth_update/paid/booth_updates.json",
+        ));
+
+        // 有料のアイテムは配布ファイルが取得できないため、バリエーションの構成で判定する
+        let asset_id = Uuid::new_v4();
+        tracker.record(
+            asset_id,
+            &create_info_with_variations(1, 100, &[(1, Some("v1.0"))], &[]),
+        );
+
+        assert!(!tracker.check(
+            asset_id,
+            &create_info_with_variations(1, 200, &[(1, Some("v1.0"))], &[])
+        ));
+        assert!(tracker.check(
+            asset_id,
+            &create_info_with_variations(1, 200, &[(1, Some("v1.1"))], &[])
+        ));
+
+        tracker.acknowledge(asset_id).unwrap();
+        assert!(tracker.check(
+            asset_id,
+            &create_info_with_variations(1, 300, &[(1, Some("v1.1")), (2, None)], &[])
+        ));
+
+        // 配布ファイルが分からない場合は、変更なしではなく不明として比較しない
+        tracker.acknowledge(asset_id).unwrap();
+        let variations = [(1, Some("v1.1")), (2, None)];
+        assert!(!tracker.check(
+            asset_id,
+            &create_info_with_variations(1, 300, &variations, &["v2.zip"])
+        ));
+        assert!(tracker.check(
+            asset_id,
+            &create_info_with_variations(1, 300, &variations, &["v3.zip"])
+        ));
+    }
+
+    #[test]
+    fn test_record_image_url() {
+        let dir = PathBuf::from("test/temp/booth_update/image_url");
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("booth_updates.json");
+        let mut tracker = BoothUpdateTracker::load_or_default(path.clone());
+
+        let asset_id = Uuid::new_v4();
+        tracker.record(asset_id, &create_info(1, 100, &["v1.zip"]));
+        assert_eq!(tracker.get_image_url(asset_id), None);
+
+        tracker.record_image_url(asset_id, &create_info(1, 100, &["v1.zip"]), "a.jpg".into());
+        assert!(!tracker.check(asset_id, &create_info(1, 100, &["v1.zip"])));
+
+        tracker.save().unwrap();
+        let mut tracker = BoothUpdateTracker::load_or_default(path);
+        assert_eq!(tracker.get_image_url(asset_id), Some("a.jpg"));
+
+        // 紐づくアイテムが変わった場合は記録し直す
+        assert!(!tracker.check(asset_id, &create_info(2, 100, &["v1.zip"])));
+        assert_eq!(tracker.get_image_url(asset_id), None);
+    }
+
+    #[test]
+    fn test_load_records_with_published_at() {
+        let dir = PathBuf::from("test/temp/booth_update/legacy");
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let asset_id = Uuid::new_v4();
+        let path = dir.join("booth_updates.json");
+        let snapshot = r#"{"publishedAt":100,"downloadableFiles":["v1.zip"]}"#;
+        std::fs::write(
+            &path,
+            format!(
+                r#"{{"{asset_id}":{{"boothItemId":1,"recorded":{snapshot},"latest":{snapshot}}}}}"#
+            ),
+        )
+        .unwrap();
+
+        let mut tracker = BoothUpdateTracker::load_or_default(path);
+
+        // バリエーションが記録されていなくても、更新として扱わない
+        assert!(!tracker.check(asset_id, &create_info(1, 200, &["v1.zip"])));
+        assert!(tracker.check(asset_id, &create_info(1, 200, &["v2.zip"])));
+    }
+}
@@ -4,4 +4,6 @@ pub enum ImageEncodeError {
     IoError(#[from] std::io::Error),
     #[error("Image error: {0}")]
     ImageProcessError(#[from] image::ImageError),
+    #[error("Crop rectangle is outside of the image: {0:?}")]
+    InvalidCropRect(crate::CropRect),
 }
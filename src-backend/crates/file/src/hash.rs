@@ -0,0 +1,58 @@
+use std::{fs::File, io::Read, path::Path};
+
+use sha2::{Digest, Sha256};
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// ファイルの SHA-256 ハッシュを 16 進数の文字列で返す
+pub fn calculate_file_hash<P: AsRef<Path>>(path: P) -> Result<String, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_file_hash() {
+        let dir = "test/temp/calculate_file_hash";
+
+        if std::fs::exists(dir).unwrap() {
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(format!("{dir}/a.txt"), b"dummy").unwrap();
+        std::fs::write(format!("{dir}/b.txt"), b"dummy").unwrap();
+        std::fs::write(format!("{dir}/c.txt"), b"other").unwrap();
+
+        let a = calculate_file_hash(format!("{dir}/a.txt")).unwrap();
+        let b = calculate_file_hash(format!("{dir}/b.txt")).unwrap();
+        let c = calculate_file_hash(format!("{dir}/c.txt")).unwrap();
+
+        assert_eq!(
+            a,
+            "b5a2c96250612366ea272ffac6d9744aaf4b45aacd96aa7cfcb931ee3b558259"
+        );
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
@@ -6,11 +6,86 @@ use std::{
 };
 
 use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, codecs::jpeg::JpegEncoder};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{DeleteOnDrop, ImageEncodeError};
+use crate::{
+    DeleteOnDrop, ImageEncodeError,
+    modify_guard::{self, FileTransferGuard},
+};
+
+/// 元の画像を残す場合の、画像フォルダ内の保存先のフォルダ名
+pub const ORIGINAL_IMAGES_DIR: &str = "originals";
+
+/// サムネイルにする範囲。画像の幅・高さに対する割合 (0.0 ~ 1.0) で指定する
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CropRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CropRect {
+    /// 画像の範囲に収まるように、ピクセル単位の (x, y, width, height) に変換する
+    fn to_pixels(self, image_width: u32, image_height: u32) -> Option<(u32, u32, u32, u32)> {
+        let to_pixel = |ratio: f32, size: u32| (ratio.clamp(0.0, 1.0) * size as f32).round() as u32;
+
+        let x = to_pixel(self.x, image_width).min(image_width);
+        let y = to_pixel(self.y, image_height).min(image_height);
+        let width = to_pixel(self.width, image_width).min(image_width - x);
+        let height = to_pixel(self.height, image_height).min(image_height - y);
+
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        Some((x, y, width, height))
+    }
+}
+
+/// サムネイルの画像に対応する、元の画像のパス
+pub fn original_image_path<P: AsRef<Path>>(images_dir: P, filename: &str) -> PathBuf {
+    images_dir.as_ref().join(ORIGINAL_IMAGES_DIR).join(filename)
+}
+
+/// 元の画像が残っていれば、別の名前のサムネイルに対応する元の画像としてコピーする
+pub async fn copy_original_image<P: AsRef<Path>>(
+    images_dir: P,
+    src_filename: &str,
+    dest_filename: &str,
+) -> Result<(), tokio::io::Error> {
+    let original_src = original_image_path(&images_dir, src_filename);
+
+    if !original_src.exists() {
+        return Ok(());
+    }
+
+    let original_dest = original_image_path(&images_dir, dest_filename);
+    modify_guard::copy_file(
+        &original_src,
+        &original_dest,
+        false,
+        FileTransferGuard::none(),
+    )
+    .await
+}
 
 pub fn resize_and_encode_with_jpeg<P, Q>(src: P, dest: Q) -> Result<(), ImageEncodeError>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    crop_resize_and_encode_with_jpeg(src, dest, None)
+}
+
+/// 指定された範囲を切り抜いてから、サムネイルの大きさに縮小する
+pub fn crop_resize_and_encode_with_jpeg<P, Q>(
+    src: P,
+    dest: Q,
+    crop: Option<CropRect>,
+) -> Result<(), ImageEncodeError>
 where
     P: AsRef<Path>,
     Q: AsRef<Path>,
@@ -19,6 +94,42 @@ where
 
     let dest = dest.as_ref();
 
+    let mut image = open_with_orientation(src)?;
+
+    if let Some(crop) = crop {
+        let (x, y, width, height) = crop
+            .to_pixels(image.width(), image.height())
+            .ok_or(ImageEncodeError::InvalidCropRect(crop))?;
+
+        image = image.crop_imm(x, y, width, height);
+    }
+
+    // 常に横幅を300ピクセル以下にする
+    let image = image.thumbnail(300, 100000);
+
+    write_jpeg(&image, dest)?;
+
+    log::info!(
+        "Image resized and encoded in {}ms ({})",
+        now.elapsed().as_millis(),
+        dest.display(),
+    );
+
+    Ok(())
+}
+
+/// 縮小せずに JPEG に変換する (詳細表示用に元の画像を残す場合に使う)
+pub fn encode_with_jpeg<P, Q>(src: P, dest: Q) -> Result<(), ImageEncodeError>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let image = open_with_orientation(src)?;
+
+    write_jpeg(&image, dest.as_ref())
+}
+
+fn open_with_orientation<P: AsRef<Path>>(src: P) -> Result<DynamicImage, ImageEncodeError> {
     let img = ImageReader::open(src)?.with_guessed_format()?;
 
     let mut decoder = img.into_decoder()?;
@@ -27,20 +138,15 @@ where
 
     image.apply_orientation(orientation);
 
-    // 常に横幅を300ピクセル以下にする
-    let image = image.thumbnail(300, 100000);
+    Ok(image)
+}
 
+fn write_jpeg(image: &DynamicImage, dest: &Path) -> Result<(), ImageEncodeError> {
     let file = File::create(dest)?;
     let encoder = JpegEncoder::new_with_quality(file, 95);
 
     image.write_with_encoder(encoder)?;
 
-    log::info!(
-        "Image resized and encoded in {}ms ({})",
-        now.elapsed().as_millis(),
-        dest.display(),
-    );
-
     Ok(())
 }
 
@@ -129,6 +235,80 @@ mod tests {
         assert!(dest_file_size < size_100kb);
     }
 
+    #[test]
+    fn test_crop_resize_and_encode_with_jpeg() {
+        let temp_dir = PathBuf::from("test/temp/crop_resize_and_encode_with_jpeg/");
+
+        if std::fs::exists(&temp_dir).unwrap() {
+            std::fs::remove_dir_all(&temp_dir).unwrap();
+        }
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let src_bytes = include_bytes!("../../../test/images/description-image.jpg");
+
+        let src_path = temp_dir.join("src.jpg");
+        let cropped_path = temp_dir.join("cropped.jpg");
+        let original_path = original_image_path(&temp_dir, "cropped.jpg");
+
+        std::fs::write(&src_path, src_bytes).unwrap();
+        let (src_width, src_height) = image::image_dimensions(&src_path).unwrap();
+
+        // 左上の正方形を切り抜く
+        let size = src_width.min(src_height) as f32;
+        let crop = CropRect {
+            x: 0.0,
+            y: 0.0,
+            width: size / src_width as f32,
+            height: size / src_height as f32,
+        };
+        crop_resize_and_encode_with_jpeg(&src_path, &cropped_path, Some(crop)).unwrap();
+
+        let (width, height) = image::image_dimensions(&cropped_path).unwrap();
+        assert!(width <= 300);
+        assert!(width.abs_diff(height) <= 1);
+
+        std::fs::create_dir_all(original_path.parent().unwrap()).unwrap();
+        encode_with_jpeg(&src_path, &original_path).unwrap();
+        assert_eq!(
+            image::image_dimensions(&original_path).unwrap(),
+            (src_width, src_height)
+        );
+
+        // 画像の外側だけを指定した場合はエラーにする
+        let result = crop_resize_and_encode_with_jpeg(
+            &src_path,
+            temp_dir.join("invalid.jpg"),
+            Some(CropRect {
+                x: 1.0,
+                y: 0.0,
+                width: 0.5,
+                height: 0.5,
+            }),
+        );
+        assert!(matches!(result, Err(ImageEncodeError::InvalidCropRect(_))));
+    }
+
+    #[test]
+    fn test_crop_rect_to_pixels() {
+        let crop = CropRect {
+            x: 0.25,
+            y: 0.5,
+            width: 0.5,
+            height: 1.0,
+        };
+
+        // 画像からはみ出す部分は切り詰める
+        assert_eq!(crop.to_pixels(400, 200), Some((100, 100, 200, 100)));
+
+        let crop = CropRect {
+            x: -1.0,
+            y: 0.0,
+            width: 2.0,
+            height: 0.0,
+        };
+        assert_eq!(crop.to_pixels(400, 200), None);
+    }
+
     #[tokio::test]
     async fn test_optimize_thumbnails() {
         let temp_dir = PathBuf::from("test/temp/optimize_thumbnails/");
@@ -176,4 +356,30 @@ mod tests {
             assert!(to_filename.ends_with(".jpg"));
         }
     }
+
+    #[tokio::test]
+    async fn test_copy_original_image() {
+        let temp_dir = PathBuf::from("test/temp/copy_original_image/");
+
+        if std::fs::exists(&temp_dir).unwrap() {
+            std::fs::remove_dir_all(&temp_dir).unwrap();
+        }
+        std::fs::create_dir_all(temp_dir.join(ORIGINAL_IMAGES_DIR)).unwrap();
+
+        std::fs::write(original_image_path(&temp_dir, "temp_kept.jpg"), "original").unwrap();
+
+        copy_original_image(&temp_dir, "temp_kept.jpg", "kept.jpg")
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(original_image_path(&temp_dir, "kept.jpg")).unwrap(),
+            "original"
+        );
+
+        // 元の画像がなければ何もしない
+        copy_original_image(&temp_dir, "temp_missing.jpg", "missing.jpg")
+            .await
+            .unwrap();
+        assert!(!original_image_path(&temp_dir, "missing.jpg").exists());
+    }
 }
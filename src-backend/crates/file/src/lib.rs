@@ -2,12 +2,19 @@ pub mod modify_guard;
 
 mod cleanup;
 mod error;
+mod hash;
 mod image_util;
 mod list;
+mod merge;
 mod open;
 
 pub use cleanup::DeleteOnDrop;
 pub use error::*;
-pub use image_util::{optimize_thumbnails, resize_and_encode_with_jpeg};
+pub use hash::calculate_file_hash;
+pub use image_util::{
+    CropRect, ORIGINAL_IMAGES_DIR, copy_original_image, crop_resize_and_encode_with_jpeg,
+    encode_with_jpeg, optimize_thumbnails, original_image_path, resize_and_encode_with_jpeg,
+};
 pub use list::*;
+pub use merge::{ConflictPolicy, MergeAction, MergeReportEntry, copy_modified_times, merge_dir};
 pub use open::open_in_file_manager;
@@ -0,0 +1,459 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hash::calculate_file_hash,
+    modify_guard::{self, DeletionGuard},
+};
+
+/// 追加先に同じ名前のファイルが既に存在する場合の扱い
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// 既存のファイルを残し、追加するファイルを破棄する
+    Skip,
+    /// 既存のファイルを上書きする
+    Overwrite,
+    /// `name (1).ext` のように名前を変えて両方残す
+    #[default]
+    Rename,
+    /// 更新日時が新しい方を残す
+    KeepNewer,
+    /// 内容が同じであれば破棄し、異なる場合は名前を変えて両方残す
+    CompareHash,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeAction {
+    /// 競合せずに追加された
+    Added,
+    /// 既存のファイルを残した
+    Skipped,
+    /// 既存のファイルを上書きした
+    Overwritten,
+    /// 名前を変えて追加した
+    Renamed,
+    /// 既存のファイルと内容が同じため追加しなかった
+    Identical,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeReportEntry {
+    /// 追加先からの相対パス
+    pub path: String,
+    pub action: MergeAction,
+    /// 名前を変えて追加した場合の、追加先からの相対パス
+    pub renamed_to: Option<String>,
+}
+
+/// `src` の中身を `dest` に移動し、競合したファイルは `policy` に従って処理する
+///
+/// `src` は一時的な展開先であることを想定しており、処理後に残ったファイルは呼び出し側で削除する
+pub async fn merge_dir<P, Q>(
+    src: P,
+    dest: Q,
+    policy: ConflictPolicy,
+) -> Result<Vec<MergeReportEntry>, tokio::io::Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let src = src.as_ref();
+    let dest = dest.as_ref();
+
+    if !src.is_dir() {
+        return Err(tokio::io::Error::new(
+            tokio::io::ErrorKind::InvalidInput,
+            "Source path must be a directory",
+        ));
+    }
+
+    tokio::fs::create_dir_all(dest).await?;
+
+    let mut report = vec![];
+    merge_dir_internal(src, dest, dest, policy, &mut report).await?;
+
+    Ok(report)
+}
+
+async fn merge_dir_internal(
+    src: &Path,
+    dest: &Path,
+    dest_root: &Path,
+    policy: ConflictPolicy,
+    report: &mut Vec<MergeReportEntry>,
+) -> Result<(), tokio::io::Error> {
+    let mut entries = tokio::fs::read_dir(src).await?;
+    let mut paths = vec![];
+
+    while let Some(entry) = entries.next_entry().await? {
+        paths.push(entry.path());
+    }
+    paths.sort();
+
+    for src_path in paths {
+        let Some(file_name) = src_path.file_name() else {
+            continue;
+        };
+        let dest_path = dest.join(file_name);
+
+        if src_path.is_dir() && (dest_path.is_dir() || !dest_path.exists()) {
+            tokio::fs::create_dir_all(&dest_path).await?;
+            Box::pin(merge_dir_internal(
+                &src_path, &dest_path, dest_root, policy, report,
+            ))
+            .await?;
+            continue;
+        }
+
+        let relative_path = to_relative_string(&dest_path, dest_root);
+
+        if !dest_path.exists() {
+            move_entry(&src_path, &dest_path).await?;
+            report.push(MergeReportEntry {
+                path: relative_path,
+                action: MergeAction::Added,
+                renamed_to: None,
+            });
+            continue;
+        }
+
+        let action = resolve_conflict(&src_path, &dest_path, policy)?;
+
+        let renamed_to = match action {
+            MergeAction::Overwritten => {
+                delete_entry(&dest_path).await?;
+                move_entry(&src_path, &dest_path).await?;
+                None
+            }
+            MergeAction::Renamed => {
+                let renamed_path = unique_path(&dest_path);
+                move_entry(&src_path, &renamed_path).await?;
+                Some(to_relative_string(&renamed_path, dest_root))
+            }
+            MergeAction::Added | MergeAction::Skipped | MergeAction::Identical => None,
+        };
+
+        report.push(MergeReportEntry {
+            path: relative_path,
+            action,
+            renamed_to,
+        });
+    }
+
+    Ok(())
+}
+
+/// `src` の更新日時を、同じ相対パスにある `dest` のファイルに引き継ぐ
+///
+/// 一時フォルダにコピーしたファイルを `ConflictPolicy::KeepNewer` で比較する前に、コピー元の日時に戻すために使う
+pub fn copy_modified_times<P, Q>(src: P, dest: Q) -> Result<(), std::io::Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let src = src.as_ref();
+    let dest = dest.as_ref();
+
+    if src.is_dir() {
+        if !dest.is_dir() {
+            return Ok(());
+        }
+
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_modified_times(entry.path(), dest.join(entry.file_name()))?;
+        }
+
+        return Ok(());
+    }
+
+    if !src.is_file() || !dest.is_file() {
+        return Ok(());
+    }
+
+    let modified = src.metadata()?.modified()?;
+
+    std::fs::File::options()
+        .write(true)
+        .open(dest)?
+        .set_modified(modified)
+}
+
+fn resolve_conflict(
+    src: &Path,
+    dest: &Path,
+    policy: ConflictPolicy,
+) -> Result<MergeAction, tokio::io::Error> {
+    // ファイルとフォルダが競合した場合は、内容や日時を比較できないため名前を変えて残す
+    let comparable = src.is_file() && dest.is_file();
+
+    let action = match policy {
+        ConflictPolicy::Skip => MergeAction::Skipped,
+        ConflictPolicy::Overwrite => MergeAction::Overwritten,
+        ConflictPolicy::Rename => MergeAction::Renamed,
+        ConflictPolicy::KeepNewer if comparable => {
+            let src_modified = src.metadata()?.modified()?;
+            let dest_modified = dest.metadata()?.modified()?;
+
+            if src_modified > dest_modified {
+                MergeAction::Overwritten
+            } else {
+                MergeAction::Skipped
+            }
+        }
+        ConflictPolicy::CompareHash if comparable => {
+            if calculate_file_hash(src)? == calculate_file_hash(dest)? {
+                MergeAction::Identical
+            } else {
+                MergeAction::Renamed
+            }
+        }
+        ConflictPolicy::KeepNewer | ConflictPolicy::CompareHash => MergeAction::Renamed,
+    };
+
+    Ok(action)
+}
+
+async fn move_entry(src: &Path, dest: &Path) -> Result<(), tokio::io::Error> {
+    if tokio::fs::rename(src, dest).await.is_ok() {
+        return Ok(());
+    }
+
+    // 別のドライブ間では rename できないため、コピーする
+    if src.is_dir() {
+        modify_guard::copy_dir(
+            src.to_path_buf(),
+            dest.to_path_buf(),
+            false,
+            modify_guard::FileTransferGuard::none(),
+            |_, _| {},
+        )
+        .await
+    } else {
+        tokio::fs::copy(src, dest).await.map(|_| ())
+    }
+}
+
+async fn delete_entry(path: &Path) -> Result<(), tokio::io::Error> {
+    modify_guard::delete_recursive_completely(path, &DeletionGuard::new(path)).await
+}
+
+/// `name (1).ext` のように、存在しないパスが見つかるまで番号を増やす
+fn unique_path(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let file_stem = path
+        .file_stem()
+        .unwrap_or(OsStr::new("imported"))
+        .to_string_lossy()
+        .to_string();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut i = 1;
+
+    loop {
+        let candidate = parent.join(format!("{} ({}){}", file_stem, i, extension));
+
+        if !candidate.exists() {
+            return candidate;
+        }
+
+        i += 1;
+    }
+}
+
+fn to_relative_string(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    fn setup(base: &str) -> (PathBuf, PathBuf) {
+        let base = PathBuf::from(base);
+
+        if std::fs::exists(&base).unwrap() {
+            std::fs::remove_dir_all(&base).unwrap();
+        }
+
+        let src = base.join("src");
+        let dest = base.join("dest");
+
+        std::fs::create_dir_all(src.join("Package")).unwrap();
+        std::fs::create_dir_all(dest.join("Package")).unwrap();
+
+        std::fs::write(src.join("Package/new.txt"), b"new").unwrap();
+        std::fs::write(src.join("Package/same.txt"), b"same").unwrap();
+        std::fs::write(src.join("Package/changed.txt"), b"incoming").unwrap();
+
+        std::fs::write(dest.join("Package/same.txt"), b"same").unwrap();
+        std::fs::write(dest.join("Package/changed.txt"), b"existing").unwrap();
+
+        (src, dest)
+    }
+
+    fn find<'a>(report: &'a [MergeReportEntry], path: &str) -> &'a MergeReportEntry {
+        report.iter().find(|entry| entry.path == path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_merge_dir_with_skip() {
+        let (src, dest) = setup("test/temp/merge_dir/skip");
+
+        let report = merge_dir(&src, &dest, ConflictPolicy::Skip).await.unwrap();
+
+        assert_eq!(report.len(), 3);
+        assert_eq!(find(&report, "Package/new.txt").action, MergeAction::Added);
+        assert_eq!(
+            find(&report, "Package/changed.txt").action,
+            MergeAction::Skipped
+        );
+        assert_eq!(
+            std::fs::read(dest.join("Package/changed.txt")).unwrap(),
+            b"existing"
+        );
+        assert!(dest.join("Package/new.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_merge_dir_with_overwrite() {
+        let (src, dest) = setup("test/temp/merge_dir/overwrite");
+
+        let report = merge_dir(&src, &dest, ConflictPolicy::Overwrite)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            find(&report, "Package/changed.txt").action,
+            MergeAction::Overwritten
+        );
+        assert_eq!(
+            std::fs::read(dest.join("Package/changed.txt")).unwrap(),
+            b"incoming"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_dir_with_rename() {
+        let (src, dest) = setup("test/temp/merge_dir/rename");
+
+        let report = merge_dir(&src, &dest, ConflictPolicy::Rename)
+            .await
+            .unwrap();
+
+        let changed = find(&report, "Package/changed.txt");
+        assert_eq!(changed.action, MergeAction::Renamed);
+        assert_eq!(
+            changed.renamed_to,
+            Some("Package/changed (1).txt".to_string())
+        );
+        assert_eq!(
+            std::fs::read(dest.join("Package/changed.txt")).unwrap(),
+            b"existing"
+        );
+        assert_eq!(
+            std::fs::read(dest.join("Package/changed (1).txt")).unwrap(),
+            b"incoming"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_dir_with_keep_newer() {
+        let (src, dest) = setup("test/temp/merge_dir/keep_newer");
+
+        let old = SystemTime::now() - Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(dest.join("Package/changed.txt"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(src.join("Package/same.txt"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let report = merge_dir(&src, &dest, ConflictPolicy::KeepNewer)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            find(&report, "Package/changed.txt").action,
+            MergeAction::Overwritten
+        );
+        assert_eq!(
+            find(&report, "Package/same.txt").action,
+            MergeAction::Skipped
+        );
+    }
+
+    #[tokio::test]
+    async fn test_copy_modified_times() {
+        let (src, dest) = setup("test/temp/merge_dir/copy_modified_times");
+
+        let old = SystemTime::now() - Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(src.join("Package/changed.txt"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        copy_modified_times(&src, &dest).unwrap();
+
+        assert_eq!(
+            std::fs::metadata(dest.join("Package/changed.txt"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            old
+        );
+
+        // コピー元の日時が引き継がれるため、新しい方として既存のファイルは上書きされない
+        let report = merge_dir(&src, &dest, ConflictPolicy::KeepNewer)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            find(&report, "Package/changed.txt").action,
+            MergeAction::Skipped
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_dir_with_compare_hash() {
+        let (src, dest) = setup("test/temp/merge_dir/compare_hash");
+
+        let report = merge_dir(&src, &dest, ConflictPolicy::CompareHash)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            find(&report, "Package/same.txt").action,
+            MergeAction::Identical
+        );
+        assert_eq!(
+            find(&report, "Package/changed.txt").action,
+            MergeAction::Renamed
+        );
+        assert!(!dest.join("Package/same (1).txt").exists());
+        assert!(dest.join("Package/changed (1).txt").exists());
+    }
+}
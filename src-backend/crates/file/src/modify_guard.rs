@@ -3,6 +3,7 @@ use std::{
     sync::Arc,
 };
 
+use filter::FileFilter;
 use tokio::sync::Mutex;
 
 pub struct DeletionGuard {
@@ -257,6 +258,30 @@ pub async fn copy_dir<P, Q>(
     guard: FileTransferGuard,
     progress_callback: impl Fn(f32, String),
 ) -> Result<(), tokio::io::Error>
+where
+    P: AsRef<Path> + Clone + Send + Sync + 'static,
+    Q: AsRef<Path> + Clone + Send + Sync + 'static,
+{
+    copy_dir_with_filter(
+        src,
+        dest,
+        delete_source,
+        guard,
+        &FileFilter::none(),
+        progress_callback,
+    )
+    .await
+}
+
+/// フィルタで除外されたファイル・フォルダを除いてディレクトリをコピーする
+pub async fn copy_dir_with_filter<P, Q>(
+    src: P,
+    dest: Q,
+    delete_source: bool,
+    guard: FileTransferGuard,
+    filter: &FileFilter,
+    progress_callback: impl Fn(f32, String),
+) -> Result<(), tokio::io::Error>
 where
     P: AsRef<Path> + Clone + Send + Sync + 'static,
     Q: AsRef<Path> + Clone + Send + Sync + 'static,
@@ -286,7 +311,7 @@ where
 
     guard.assert(&src, &dest)?;
 
-    let amount_of_entries = count_entries(&src)?;
+    let amount_of_entries = count_entries(src.as_ref(), src.as_ref(), filter)?;
 
     let amount_of_processed_entries = Arc::new(Mutex::new(0));
     let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(32);
@@ -294,7 +319,14 @@ where
     let cloned_amount_of_processed_entries = amount_of_processed_entries.clone();
     tokio::select! {
         result = async {
-            copy_dir_internal(src.clone(), dest.clone(), amount_of_processed_entries, &tx)
+            copy_dir_internal(
+                src.as_ref(),
+                src.as_ref(),
+                dest.as_ref(),
+                filter,
+                amount_of_processed_entries,
+                &tx,
+            )
             .await
             .map_err(|e| {
                 tokio::io::Error::new(
@@ -319,18 +351,20 @@ where
     Ok(())
 }
 
-fn count_entries<P>(path: P) -> Result<u64, std::io::Error>
-where
-    P: AsRef<Path>,
-{
-    let mut entries = std::fs::read_dir(&path)?;
+fn count_entries(root: &Path, path: &Path, filter: &FileFilter) -> Result<u64, std::io::Error> {
+    let mut entries = std::fs::read_dir(path)?;
 
     let mut amount_of_entries = 0;
     while let Some(entry) = entries.next() {
         let entry = entry?;
+        let is_dir = entry.file_type()?.is_dir();
+
+        if is_excluded(root, &entry.path(), is_dir, filter) {
+            continue;
+        }
 
-        if entry.file_type()?.is_dir() {
-            amount_of_entries += count_entries(&entry.path())?;
+        if is_dir {
+            amount_of_entries += count_entries(root, &entry.path(), filter)?;
         }
 
         amount_of_entries += 1;
@@ -339,21 +373,31 @@ where
     Ok(amount_of_entries)
 }
 
-async fn copy_dir_internal<P, Q>(
-    old_path: P,
-    new_path: Q,
+fn is_excluded(root: &Path, path: &Path, is_dir: bool, filter: &FileFilter) -> bool {
+    match path.strip_prefix(root) {
+        Ok(relative_path) => filter.is_excluded(relative_path, is_dir),
+        Err(_) => false,
+    }
+}
+
+async fn copy_dir_internal(
+    root: &Path,
+    old_path: &Path,
+    new_path: &Path,
+    filter: &FileFilter,
     processed_files: Arc<Mutex<u64>>,
     tx: &tokio::sync::mpsc::Sender<String>,
-) -> Result<(), tokio::io::Error>
-where
-    P: AsRef<Path> + Send + Sync + 'static,
-    Q: AsRef<Path> + Send + Sync + 'static,
-{
-    let mut entries = tokio::fs::read_dir(&old_path).await?;
+) -> Result<(), tokio::io::Error> {
+    let mut entries = tokio::fs::read_dir(old_path).await?;
 
     while let Some(entry) = entries.next_entry().await? {
         let path = entry.path();
 
+        if is_excluded(root, &path, path.is_dir(), filter) {
+            log::debug!("Skipping filtered entry: {}", path.display());
+            continue;
+        }
+
         let filename = path.file_name();
         if filename.is_none() {
             return Err(tokio::io::Error::new(
@@ -370,7 +414,7 @@ where
             ));
         }
 
-        let new_path = new_path.as_ref().join(filename.unwrap());
+        let new_path = new_path.join(filename.unwrap());
 
         if path.is_dir() {
             if new_path.exists() {
@@ -380,8 +424,10 @@ where
             tokio::fs::create_dir(&new_path).await?;
 
             Box::pin(copy_dir_internal(
-                path.clone().to_owned(),
-                new_path.clone().to_owned(),
+                root,
+                &path,
+                &new_path,
+                filter,
                 processed_files.clone(),
                 tx,
             ))
@@ -593,4 +639,33 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_copy_dir_with_filter() {
+        let dir = get_test_dir();
+        let src = dir.join("copy_dir_with_filter_src");
+        let dest = dir.join("copy_dir_with_filter_dest");
+
+        for path in [&src, &dest] {
+            if path.exists() {
+                std::fs::remove_dir_all(path).unwrap();
+            }
+        }
+
+        std::fs::create_dir_all(src.join("__MACOSX")).unwrap();
+        std::fs::write(src.join("__MACOSX/._avatar.unitypackage"), b"test").unwrap();
+        std::fs::write(src.join("avatar.unitypackage"), b"test").unwrap();
+        std::fs::write(src.join("Thumbs.db"), b"test").unwrap();
+
+        let filter = FileFilter::new::<&str>(&[], &["__MACOSX", "Thumbs.db"]).unwrap();
+        let guard = FileTransferGuard::both(dir.to_path_buf(), dir.to_path_buf());
+        let result =
+            copy_dir_with_filter(src.clone(), dest.clone(), false, guard, &filter, |_, _| {}).await;
+
+        assert_eq!(result.unwrap(), ());
+
+        assert!(dest.join("avatar.unitypackage").exists());
+        assert!(!dest.join("__MACOSX").exists());
+        assert!(!dest.join("Thumbs.db").exists());
+    }
 }
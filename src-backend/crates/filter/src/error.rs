@@ -0,0 +1,5 @@
+#[derive(Debug, thiserror::Error)]
+pub enum FilterError {
+    #[error("Invalid glob pattern: {0}")]
+    InvalidPattern(#[from] globset::Error),
+}
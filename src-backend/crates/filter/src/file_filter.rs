@@ -0,0 +1,158 @@
+use std::path::{Component, Path};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::FilterError;
+
+/// インポート時に取り込むファイルを絞り込むためのルール
+///
+/// - `/` を含まないパターンは、どの階層にあるファイル名・フォルダ名にもマッチする (例: `__MACOSX`, `*.psd`)
+/// - `/` を含むパターンは、インポート元からの相対パス全体にマッチする (例: `Sample/**`)
+/// - 除外パターンにマッチしたフォルダは、その中身ごと除外される
+/// - 取り込みパターンが 1 つ以上ある場合、いずれかにマッチしたファイルのみ取り込まれる
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl FileFilter {
+    pub fn new<S: AsRef<str>>(include: &[S], exclude: &[S]) -> Result<Self, FilterError> {
+        Ok(Self {
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    /// 何も除外しないフィルタ
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none()
+    }
+
+    /// インポート元からの相対パスが除外対象かどうかを判定する
+    pub fn is_excluded<P: AsRef<Path>>(&self, relative_path: P, is_dir: bool) -> bool {
+        let components = relative_path
+            .as_ref()
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if components.is_empty() {
+            return false;
+        }
+
+        if let Some(exclude) = &self.exclude {
+            // 親フォルダが除外されている場合は中身も除外する
+            for i in 1..=components.len() {
+                if exclude.is_match(components[..i].join("/")) {
+                    return true;
+                }
+            }
+        }
+
+        if is_dir {
+            return false;
+        }
+
+        match &self.include {
+            Some(include) => !include.is_match(components.join("/")),
+            None => false,
+        }
+    }
+}
+
+fn build_glob_set<S: AsRef<str>>(patterns: &[S]) -> Result<Option<GlobSet>, FilterError> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| pattern.as_ref().trim().replace('\\', "/"))
+        .map(|pattern| pattern.trim_matches('/').to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .collect::<Vec<_>>();
+
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        let pattern = if pattern.contains('/') {
+            pattern
+        } else {
+            format!("**/{pattern}")
+        };
+
+        let glob = GlobBuilder::new(&pattern)
+            .case_insensitive(true)
+            .literal_separator(true)
+            .build()?;
+
+        builder.add(glob);
+    }
+
+    Ok(Some(builder.build()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclude_by_name_at_any_depth() {
+        let filter = FileFilter::new::<&str>(&[], &["__MACOSX", ".DS_Store", "._*"]).unwrap();
+
+        assert!(filter.is_excluded("__MACOSX", true));
+        assert!(filter.is_excluded("__MACOSX/._Avatar.unitypackage", false));
+        assert!(filter.is_excluded("Avatar/__macosx/readme.txt", false));
+        assert!(filter.is_excluded("Avatar/.DS_Store", false));
+        assert!(filter.is_excluded("Avatar/._texture.png", false));
+
+        assert!(!filter.is_excluded("Avatar", true));
+        assert!(!filter.is_excluded("Avatar/Avatar.unitypackage", false));
+    }
+
+    #[test]
+    fn test_exclude_by_relative_path() {
+        let filter = FileFilter::new::<&str>(&[], &["Sample/**", "*/docs"]).unwrap();
+
+        assert!(filter.is_excluded("Sample/Assets/scene.unity", false));
+        assert!(filter.is_excluded("Avatar/docs/readme.txt", false));
+
+        assert!(!filter.is_excluded("Avatar/Sample/scene.unity", false));
+        assert!(!filter.is_excluded("docs/readme.txt", false));
+    }
+
+    #[test]
+    fn test_include_only_matching_files() {
+        let filter = FileFilter::new(&["*.unitypackage"], &["__MACOSX"]).unwrap();
+
+        assert!(!filter.is_excluded("Avatar", true));
+        assert!(!filter.is_excluded("Avatar/Avatar.UnityPackage", false));
+
+        assert!(filter.is_excluded("Avatar/readme.txt", false));
+        assert!(filter.is_excluded("__MACOSX/Avatar.unitypackage", false));
+    }
+
+    #[test]
+    fn test_empty_filter_excludes_nothing() {
+        let filter = FileFilter::new::<&str>(&[" ", ""], &[]).unwrap();
+
+        assert!(filter.is_empty());
+        assert!(!filter.is_excluded("__MACOSX/.DS_Store", false));
+        assert!(!filter.is_excluded("", false));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let result = FileFilter::new::<&str>(&[], &["[unclosed"]);
+
+        assert!(matches!(result, Err(FilterError::InvalidPattern(_))));
+    }
+}
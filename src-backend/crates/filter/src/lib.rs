@@ -0,0 +1,5 @@
+mod error;
+mod file_filter;
+
+pub use error::FilterError;
+pub use file_filter::FileFilter;
@@ -1,4 +1,7 @@
-use model::preference::{LanguageCode, PreferenceStore, Theme, UpdateChannel};
+use model::preference::{
+    BoothCachePreference, BoothNameSource, ImportFilterRules, LanguageCode, PreferenceStore, Theme,
+    UpdateChannel, ZipExtractionLimits,
+};
 use std::path::PathBuf;
 
 use monostate::MustBe;
@@ -8,8 +11,12 @@ use serde::{Deserialize, Serialize};
 #[serde(untagged)]
 pub enum VersionedPreferences {
     Preference {
+        version: MustBe!(7u64),
+        data: Box<PreferenceStore>,
+    },
+    LegacyPreferenceV6 {
         version: MustBe!(6u64),
-        data: PreferenceStore,
+        data: LegacyPreferenceStoreV6,
     },
     LegacyPreferenceV5 {
         version: MustBe!(5u64),
@@ -39,21 +46,28 @@ impl TryInto<PreferenceStore> for VersionedPreferences {
 
     fn try_into(self) -> Result<PreferenceStore, Self::Error> {
         match self {
-            VersionedPreferences::Preference { data, .. } => Ok(data),
-            VersionedPreferences::LegacyPreferenceV5 { data, .. } => Ok(data.into()),
+            VersionedPreferences::Preference { data, .. } => Ok(*data),
+            VersionedPreferences::LegacyPreferenceV6 { data, .. } => Ok(data.into()),
+            VersionedPreferences::LegacyPreferenceV5 { data, .. } => {
+                let data: LegacyPreferenceStoreV6 = data.into();
+                Ok(data.into())
+            }
             VersionedPreferences::LegacyPreferenceV4 { data, .. } => {
                 let data: LegacyPreferenceStoreV5 = data.into();
+                let data: LegacyPreferenceStoreV6 = data.into();
                 Ok(data.into())
             }
             VersionedPreferences::LegacyPreferenceV3 { data, .. } => {
                 let data: LegacyPreferenceStoreV4 = data.into();
                 let data: LegacyPreferenceStoreV5 = data.into();
+                let data: LegacyPreferenceStoreV6 = data.into();
                 Ok(data.into())
             }
             VersionedPreferences::LegacyPreferenceV2 { data, .. } => {
                 let data: LegacyPreferenceStoreV3 = data.into();
                 let data: LegacyPreferenceStoreV4 = data.into();
                 let data: LegacyPreferenceStoreV5 = data.into();
+                let data: LegacyPreferenceStoreV6 = data.into();
                 Ok(data.into())
             }
             VersionedPreferences::LegacyPreferenceV1 { data, .. } => {
@@ -61,6 +75,7 @@ impl TryInto<PreferenceStore> for VersionedPreferences {
                 let data: LegacyPreferenceStoreV3 = data.into();
                 let data: LegacyPreferenceStoreV4 = data.into();
                 let data: LegacyPreferenceStoreV5 = data.into();
+                let data: LegacyPreferenceStoreV6 = data.into();
                 Ok(data.into())
             }
             VersionedPreferences::LegacyRawPreference(legacy_raw_preference) => {
@@ -68,6 +83,7 @@ impl TryInto<PreferenceStore> for VersionedPreferences {
                 let data: LegacyPreferenceStoreV3 = data.into();
                 let data: LegacyPreferenceStoreV4 = data.into();
                 let data: LegacyPreferenceStoreV5 = data.into();
+                let data: LegacyPreferenceStoreV6 = data.into();
                 Ok(data.into())
             }
         }
@@ -79,32 +95,76 @@ impl TryFrom<PreferenceStore> for VersionedPreferences {
 
     fn try_from(value: PreferenceStore) -> Result<VersionedPreferences, Self::Error> {
         Ok(VersionedPreferences::Preference {
-            version: MustBe!(6u64),
-            data: value,
+            version: MustBe!(7u64),
+            data: Box::new(value),
         })
     }
 }
 
 /*
- * Version 5
+ * Version 6
  */
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct LegacyPreferenceStoreV5 {
+pub struct LegacyPreferenceStoreV6 {
     pub data_dir_path: PathBuf,
     pub theme: Theme,
     pub language: LanguageCode,
     pub delete_on_import: bool,
     pub zip_extraction: bool,
     pub use_unitypackage_selected_open: bool,
+    pub use_trash_bin: bool,
     pub update_channel: UpdateChannel,
 }
 
-impl Into<PreferenceStore> for LegacyPreferenceStoreV5 {
+impl Into<PreferenceStore> for LegacyPreferenceStoreV6 {
     fn into(self) -> PreferenceStore {
         PreferenceStore {
             file_path: Default::default(),
+            data_dir_path: self.data_dir_path.clone(),
+            theme: self.theme,
+            language: self.language,
+            delete_on_import: self.delete_on_import,
+            zip_extraction: self.zip_extraction,
+            zip_extraction_limits: ZipExtractionLimits::default(),
+            use_unitypackage_selected_open: self.use_unitypackage_selected_open,
+            use_trash_bin: self.use_trash_bin,
+            update_channel: self.update_channel,
+            watch_dirs: vec![],
+            import_concurrency: 2,
+            import_filter: ImportFilterRules::default(),
+            library_profiles: vec![],
+            active_library_profile: None,
+            shared_library_preferences: None,
+            recent_libraries: vec![self.data_dir_path],
+            booth_cache: BoothCachePreference::default(),
+            booth_tag_mappings: vec![],
+            booth_offline_mode: false,
+            booth_name_source: BoothNameSource::Original,
+        }
+    }
+}
+
+/*
+ * Version 5
+ */
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyPreferenceStoreV5 {
+    pub data_dir_path: PathBuf,
+    pub theme: Theme,
+    pub language: LanguageCode,
+    pub delete_on_import: bool,
+    pub zip_extraction: bool,
+    pub use_unitypackage_selected_open: bool,
+    pub update_channel: UpdateChannel,
+}
+
+impl Into<LegacyPreferenceStoreV6> for LegacyPreferenceStoreV5 {
+    fn into(self) -> LegacyPreferenceStoreV6 {
+        LegacyPreferenceStoreV6 {
             data_dir_path: self.data_dir_path,
             theme: self.theme,
             language: self.language,
@@ -230,3 +290,54 @@ impl Into<LegacyPreferenceStoreV2> for LegacyPreferenceStoreV1 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_preference_v6_to_latest_migration() {
+        let legacy = r#"{
+            "version": 6,
+            "data": {
+                "dataDirPath": "/path/to/data",
+                "theme": "dark",
+                "language": "ja-JP",
+                "deleteOnImport": true,
+                "zipExtraction": false,
+                "useUnitypackageSelectedOpen": false,
+                "useTrashBin": false,
+                "updateChannel": "PreRelease"
+            }
+        }"#;
+
+        let versioned: VersionedPreferences = serde_json::from_str(legacy).unwrap();
+        let latest: PreferenceStore = versioned.try_into().unwrap();
+
+        assert_eq!(latest.data_dir_path, PathBuf::from("/path/to/data"));
+        assert_eq!(latest.theme, Theme::Dark);
+        assert_eq!(latest.language, LanguageCode::JaJp);
+        assert!(latest.delete_on_import);
+        assert!(!latest.zip_extraction);
+        assert!(!latest.use_trash_bin);
+        assert_eq!(latest.update_channel, UpdateChannel::PreRelease);
+
+        // 追加された設定には既定値が入る
+        assert_eq!(latest.zip_extraction_limits, ZipExtractionLimits::default());
+        assert_eq!(latest.import_concurrency, 2);
+        assert_eq!(latest.import_filter, ImportFilterRules::default());
+        assert_eq!(
+            latest.recent_libraries,
+            vec![PathBuf::from("/path/to/data")]
+        );
+        assert_eq!(latest.booth_cache, BoothCachePreference::default());
+        assert_eq!(latest.booth_name_source, BoothNameSource::Original);
+
+        // 保存した設定は最新のバージョンとして読み込める
+        let saved =
+            serde_json::to_string(&VersionedPreferences::try_from(latest).unwrap()).unwrap();
+        let reloaded: VersionedPreferences = serde_json::from_str(&saved).unwrap();
+
+        assert!(matches!(reloaded, VersionedPreferences::Preference { .. }));
+    }
+}
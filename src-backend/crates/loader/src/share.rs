@@ -1,4 +1,4 @@
-use model::AssetDescription;
+use model::{AssetDataLocation, AssetDescription};
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -33,6 +33,7 @@ impl TryInto<AssetDescription> for LegacyAssetDescriptionV2 {
             dependencies: vec![],
             created_at: self.created_at,
             published_at: self.published_at,
+            data_location: AssetDataLocation::Local,
         })
     }
 }
@@ -1,9 +1,12 @@
-use std::collections::BTreeSet;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, specta::Type)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash, specta::Type)]
 pub enum AssetType {
     Avatar,
     AvatarWearable,
@@ -55,6 +58,39 @@ pub struct AssetDescription {
     pub dependencies: Vec<Uuid>,
     pub created_at: i64,
     pub published_at: Option<i64>,
+    #[serde(default)] // default = Local
+    pub data_location: AssetDataLocation,
+}
+
+/// アセットのデータがどこにあるか
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetDataLocation {
+    /// データディレクトリ内の data/<id> に保存されている
+    #[default]
+    Local,
+    /// 購入済みだがまだダウンロードしていない
+    NotDownloaded,
+    /// 外付けドライブなど、KonoAsset の管理外の場所に保存されている
+    External { location: String },
+    /// データディレクトリにコピーせず、外部のフォルダをそのまま参照している
+    Linked { path: String },
+}
+
+impl AssetDataLocation {
+    /// データディレクトリ内にアセットのファイルが存在するかどうか
+    pub fn has_local_data(&self) -> bool {
+        matches!(self, AssetDataLocation::Local)
+    }
+
+    /// アセットのファイルが置かれているフォルダを返す。ファイルを持たない場合は None
+    pub fn resolve_data_dir<P: AsRef<Path>>(&self, root_dir: P, id: Uuid) -> Option<PathBuf> {
+        match self {
+            AssetDataLocation::Local => Some(root_dir.as_ref().join("data").join(id.to_string())),
+            AssetDataLocation::Linked { path } => Some(PathBuf::from(path)),
+            AssetDataLocation::NotDownloaded | AssetDataLocation::External { .. } => None,
+        }
+    }
 }
 
 impl Avatar {
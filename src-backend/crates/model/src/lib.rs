@@ -7,6 +7,7 @@ pub mod preference;
 
 pub use base::AssetType;
 
+pub use base::AssetDataLocation;
 pub use base::AssetDescription;
 pub use base::Avatar;
 pub use base::AvatarWearable;
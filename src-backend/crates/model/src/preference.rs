@@ -1,6 +1,11 @@
 use std::path::{Path, PathBuf};
 
+use filter::{FileFilter, FilterError};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 最近使ったライブラリとして保持する件数
+pub const RECENT_LIBRARIES_LIMIT: usize = 10;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
 pub enum Theme {
@@ -63,6 +68,143 @@ impl LanguageCode {
     }
 }
 
+/// インポート時に取り込む・除外するファイルの glob パターン
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFilterRules {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Default for ImportFilterRules {
+    fn default() -> Self {
+        Self {
+            include: vec![],
+            exclude: vec![
+                "__MACOSX".into(),
+                ".DS_Store".into(),
+                "._*".into(),
+                "Thumbs.db".into(),
+                "desktop.ini".into(),
+            ],
+        }
+    }
+}
+
+impl ImportFilterRules {
+    pub fn build_filter(&self) -> Result<FileFilter, FilterError> {
+        FileFilter::new(&self.include, &self.exclude)
+    }
+}
+
+/// zip の展開時に許容する上限 (None の場合は無制限)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipExtractionLimits {
+    /// 展開後の合計サイズの上限 (MB)
+    pub max_total_size_mb: Option<u64>,
+    pub max_entry_count: Option<u32>,
+    /// エントリ毎の圧縮率 (展開後サイズ / 圧縮後サイズ) の上限
+    pub max_compression_ratio: Option<u32>,
+}
+
+impl Default for ZipExtractionLimits {
+    fn default() -> Self {
+        Self {
+            max_total_size_mb: Some(64 * 1024),
+            max_entry_count: Some(100_000),
+            max_compression_ratio: Some(200),
+        }
+    }
+}
+
+/// ライブラリごとに切り替えられる設定
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryPreferences {
+    pub delete_on_import: bool,
+    pub zip_extraction: bool,
+    pub use_trash_bin: bool,
+    pub watch_dirs: Vec<PathBuf>,
+    pub import_concurrency: u32,
+    pub import_filter: ImportFilterRules,
+    #[serde(default)]
+    pub zip_extraction_limits: ZipExtractionLimits,
+}
+
+impl LibraryPreferences {
+    pub fn from_store(store: &PreferenceStore) -> Self {
+        Self {
+            delete_on_import: store.delete_on_import,
+            zip_extraction: store.zip_extraction,
+            use_trash_bin: store.use_trash_bin,
+            watch_dirs: store.watch_dirs.clone(),
+            import_concurrency: store.import_concurrency,
+            import_filter: store.import_filter.clone(),
+            zip_extraction_limits: store.zip_extraction_limits,
+        }
+    }
+
+    pub fn apply_to(&self, store: &mut PreferenceStore) {
+        store.delete_on_import = self.delete_on_import;
+        store.zip_extraction = self.zip_extraction;
+        store.use_trash_bin = self.use_trash_bin;
+        store.watch_dirs = self.watch_dirs.clone();
+        store.import_concurrency = self.import_concurrency;
+        store.import_filter = self.import_filter.clone();
+        store.zip_extraction_limits = self.zip_extraction_limits;
+    }
+}
+
+/// BOOTH から取得したアイテム情報のキャッシュの有効期限
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothCachePreference {
+    /// この時間が経過するまでは、BOOTH に問い合わせずにキャッシュを使う
+    pub ttl_hours: u32,
+    /// 有効期限を過ぎてからこの日数の間は、キャッシュを返しつつ裏で取得し直す
+    pub stale_days: u32,
+}
+
+impl Default for BoothCachePreference {
+    fn default() -> Self {
+        Self {
+            ttl_hours: 24,
+            stale_days: 30,
+        }
+    }
+}
+
+/// インポート時にアセット名として使う BOOTH のアイテム名
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+pub enum BoothNameSource {
+    /// 出品者が付けた名前
+    Original,
+    /// 表示言語で取得した名前 (取得できなかった場合は元の名前)
+    Localized,
+}
+
+/// BOOTH のタグを、インポート時に付けるタグに置き換える規則
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothTagMapping {
+    /// 大文字・小文字を区別せずに比較する
+    pub booth_tag: String,
+    /// 空の場合は、この BOOTH のタグを取り込まない
+    pub tags: Vec<String>,
+}
+
+/// 名前を付けて切り替えられるライブラリ (データフォルダ)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryProfile {
+    pub id: Uuid,
+    pub name: String,
+    pub data_dir_path: PathBuf,
+    /// `None` の場合は、プロファイルを持たない場合と共通の設定を使う
+    pub preferences: Option<LibraryPreferences>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 #[serde(rename_all = "camelCase")]
 pub struct PreferenceStore {
@@ -75,10 +217,28 @@ pub struct PreferenceStore {
 
     pub delete_on_import: bool,
     pub zip_extraction: bool,
+    pub zip_extraction_limits: ZipExtractionLimits,
     pub use_unitypackage_selected_open: bool,
     pub use_trash_bin: bool,
 
     pub update_channel: UpdateChannel,
+
+    pub watch_dirs: Vec<PathBuf>,
+    pub import_concurrency: u32,
+    pub import_filter: ImportFilterRules,
+
+    pub library_profiles: Vec<LibraryProfile>,
+    pub active_library_profile: Option<Uuid>,
+    /// 独自の設定を持つプロファイルを使っている間、共通の設定を退避しておく
+    pub shared_library_preferences: Option<LibraryPreferences>,
+    /// 新しい順に並んだ、最近使ったデータフォルダ
+    pub recent_libraries: Vec<PathBuf>,
+
+    pub booth_cache: BoothCachePreference,
+    pub booth_tag_mappings: Vec<BoothTagMapping>,
+    /// BOOTH に問い合わせず、キャッシュされた情報だけを使う
+    pub booth_offline_mode: bool,
+    pub booth_name_source: BoothNameSource,
 }
 
 impl PreferenceStore {
@@ -105,10 +265,25 @@ impl PreferenceStore {
 
             delete_on_import: false,
             zip_extraction: true,
+            zip_extraction_limits: ZipExtractionLimits::default(),
             use_unitypackage_selected_open: true,
             use_trash_bin: true,
 
             update_channel: UpdateChannel::Stable,
+
+            watch_dirs: vec![],
+            import_concurrency: 2,
+            import_filter: ImportFilterRules::default(),
+
+            library_profiles: vec![],
+            active_library_profile: None,
+            shared_library_preferences: None,
+            recent_libraries: vec![],
+
+            booth_cache: BoothCachePreference::default(),
+            booth_tag_mappings: vec![],
+            booth_offline_mode: false,
+            booth_name_source: BoothNameSource::Original,
         }
     }
 
@@ -116,18 +291,148 @@ impl PreferenceStore {
         &self.data_dir_path
     }
 
+    /// データフォルダを変更し、使用中のプロファイルと最近使ったライブラリにも反映する
     pub fn set_data_dir(&mut self, data_dir_path: PathBuf) {
+        if let Some(active_id) = self.active_library_profile {
+            if let Some(profile) = self
+                .library_profiles
+                .iter_mut()
+                .find(|profile| profile.id == active_id)
+            {
+                profile.data_dir_path = data_dir_path.clone();
+            }
+        }
+
+        self.add_recent_library(data_dir_path.clone());
         self.data_dir_path = data_dir_path;
     }
 
+    pub fn add_recent_library(&mut self, data_dir_path: PathBuf) {
+        self.recent_libraries.retain(|path| path != &data_dir_path);
+        self.recent_libraries.insert(0, data_dir_path);
+        self.recent_libraries.truncate(RECENT_LIBRARIES_LIMIT);
+    }
+
+    pub fn get_library_profile(&self, id: Uuid) -> Option<&LibraryProfile> {
+        self.library_profiles
+            .iter()
+            .find(|profile| profile.id == id)
+    }
+
+    /// 新しいプロファイルを作成する
+    ///
+    /// `own_preferences` が `true` の場合は、現在の設定を初期値とした独自の設定を持たせる
+    pub fn add_library_profile(
+        &mut self,
+        name: String,
+        data_dir_path: PathBuf,
+        own_preferences: bool,
+    ) -> LibraryProfile {
+        let profile = LibraryProfile {
+            id: Uuid::new_v4(),
+            name: name.trim().to_string(),
+            data_dir_path,
+            preferences: own_preferences.then(|| LibraryPreferences::from_store(self)),
+        };
+
+        self.library_profiles.push(profile.clone());
+        profile
+    }
+
+    pub fn rename_library_profile(&mut self, id: Uuid, name: String) -> Result<(), String> {
+        let profile = self
+            .library_profiles
+            .iter_mut()
+            .find(|profile| profile.id == id)
+            .ok_or_else(|| format!("Library profile not found: {}", id))?;
+
+        profile.name = name.trim().to_string();
+        Ok(())
+    }
+
+    pub fn remove_library_profile(&mut self, id: Uuid) -> Result<(), String> {
+        if self.active_library_profile == Some(id) {
+            return Err("Cannot remove the active library profile".into());
+        }
+
+        let count = self.library_profiles.len();
+        self.library_profiles.retain(|profile| profile.id != id);
+
+        if self.library_profiles.len() == count {
+            return Err(format!("Library profile not found: {}", id));
+        }
+
+        Ok(())
+    }
+
+    /// 使用するプロファイルを切り替え、データフォルダとライブラリごとの設定を入れ替える
+    ///
+    /// `AssetStorage` の再読み込みは呼び出し側で行う
+    pub fn switch_library_profile(&mut self, id: Uuid) -> Result<(), String> {
+        let next = self
+            .get_library_profile(id)
+            .cloned()
+            .ok_or_else(|| format!("Library profile not found: {}", id))?;
+
+        self.leave_library_profile();
+
+        if let Some(preferences) = &next.preferences {
+            self.shared_library_preferences = Some(LibraryPreferences::from_store(self));
+            preferences.apply_to(self);
+        }
+
+        self.active_library_profile = Some(next.id);
+        self.add_recent_library(next.data_dir_path.clone());
+        self.data_dir_path = next.data_dir_path;
+
+        Ok(())
+    }
+
+    /// プロファイルを使わずに、指定したデータフォルダを開く
+    pub fn open_library_without_profile(&mut self, data_dir_path: PathBuf) {
+        self.leave_library_profile();
+        self.set_data_dir(data_dir_path);
+    }
+
+    /// 現在のプロファイルが独自の設定を持つ場合は、変更された設定を保存して共通の設定に戻す
+    fn leave_library_profile(&mut self) {
+        let Some(current_id) = self.active_library_profile.take() else {
+            return;
+        };
+
+        let snapshot = LibraryPreferences::from_store(self);
+
+        if let Some(current) = self
+            .library_profiles
+            .iter_mut()
+            .find(|profile| profile.id == current_id)
+        {
+            if current.preferences.is_some() {
+                current.preferences = Some(snapshot);
+            }
+        }
+
+        if let Some(shared) = self.shared_library_preferences.take() {
+            shared.apply_to(self);
+        }
+    }
+
     pub fn overwrite(&mut self, other: &Self) {
         self.data_dir_path = other.data_dir_path.clone();
         self.theme = other.theme;
         self.delete_on_import = other.delete_on_import;
         self.zip_extraction = other.zip_extraction;
+        self.zip_extraction_limits = other.zip_extraction_limits;
         self.use_unitypackage_selected_open = other.use_unitypackage_selected_open;
         self.use_trash_bin = other.use_trash_bin;
         self.update_channel = other.update_channel;
+        self.watch_dirs = other.watch_dirs.clone();
+        self.import_concurrency = other.import_concurrency;
+        self.import_filter = other.import_filter.clone();
+        self.booth_cache = other.booth_cache;
+        self.booth_tag_mappings = other.booth_tag_mappings.clone();
+        self.booth_offline_mode = other.booth_offline_mode;
+        self.booth_name_source = other.booth_name_source;
 
         // If the new language is user-provided, skip updating the language field to prevent corruption.
         if let LanguageCode::UserProvided(_) = other.language {
@@ -137,3 +442,63 @@ impl PreferenceStore {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_library_profile() {
+        let mut store = PreferenceStore::default("preference.json", "personal");
+        store.use_trash_bin = true;
+
+        let team = store.add_library_profile(" Team ".into(), PathBuf::from("team"), true);
+        let shared = store.add_library_profile("Shared".into(), PathBuf::from("shared"), false);
+        assert_eq!(team.name, "Team");
+
+        store.switch_library_profile(team.id).unwrap();
+        assert_eq!(store.get_data_dir(), &PathBuf::from("team"));
+
+        // チームのプロファイルでのみ変更した設定は、共通の設定に影響しない
+        store.use_trash_bin = false;
+        store.zip_extraction_limits.max_entry_count = None;
+
+        store.switch_library_profile(shared.id).unwrap();
+        assert_eq!(store.get_data_dir(), &PathBuf::from("shared"));
+        assert!(store.use_trash_bin);
+        assert_eq!(store.zip_extraction_limits, ZipExtractionLimits::default());
+        assert!(store.shared_library_preferences.is_none());
+
+        store.switch_library_profile(team.id).unwrap();
+        assert!(!store.use_trash_bin);
+        assert_eq!(store.zip_extraction_limits.max_entry_count, None);
+
+        store.open_library_without_profile(PathBuf::from("personal"));
+        assert!(store.use_trash_bin);
+        assert_eq!(store.active_library_profile, None);
+        assert_eq!(
+            store.recent_libraries,
+            vec![
+                PathBuf::from("personal"),
+                PathBuf::from("team"),
+                PathBuf::from("shared")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_active_library_profile() {
+        let mut store = PreferenceStore::default("preference.json", "personal");
+
+        let profile = store.add_library_profile("Team".into(), PathBuf::from("team"), false);
+        store.switch_library_profile(profile.id).unwrap();
+
+        assert!(store.remove_library_profile(profile.id).is_err());
+
+        store.set_data_dir(PathBuf::from("moved"));
+        assert_eq!(
+            store.get_library_profile(profile.id).unwrap().data_dir_path,
+            PathBuf::from("moved")
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::base::{AssetType, Avatar, AvatarWearable, OtherAsset, WorldObject};
+use crate::base::{AssetDataLocation, AssetType, Avatar, AvatarWearable, OtherAsset, WorldObject};
 
 #[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 #[serde(rename_all = "camelCase")]
@@ -15,6 +15,9 @@ pub struct AssetSummary {
     pub dependencies: Vec<Uuid>,
     pub booth_item_id: Option<u64>,
     pub published_at: Option<i64>,
+    pub data_location: AssetDataLocation,
+    /// BOOTH 上でインポート時から更新されている
+    pub update_available: bool,
 }
 
 impl From<Avatar> for AssetSummary {
@@ -29,6 +32,8 @@ impl From<Avatar> for AssetSummary {
             dependencies: asset.description.dependencies,
             booth_item_id: asset.description.booth_item_id,
             published_at: asset.description.published_at,
+            data_location: asset.description.data_location,
+            update_available: false,
         }
     }
 }
@@ -45,6 +50,8 @@ impl From<AvatarWearable> for AssetSummary {
             dependencies: asset.description.dependencies,
             booth_item_id: asset.description.booth_item_id,
             published_at: asset.description.published_at,
+            data_location: asset.description.data_location,
+            update_available: false,
         }
     }
 }
@@ -61,6 +68,8 @@ impl From<WorldObject> for AssetSummary {
             dependencies: asset.description.dependencies,
             booth_item_id: asset.description.booth_item_id,
             published_at: asset.description.published_at,
+            data_location: asset.description.data_location,
+            update_available: false,
         }
     }
 }
@@ -77,6 +86,8 @@ impl From<OtherAsset> for AssetSummary {
             dependencies: asset.description.dependencies,
             booth_item_id: asset.description.booth_item_id,
             published_at: asset.description.published_at,
+            data_location: asset.description.data_location,
+            update_available: false,
         }
     }
 }
@@ -6,17 +6,20 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use file::{
-    DeleteOnDrop,
-    modify_guard::{self, DeletionGuard, FileTransferGuard},
-};
+use file::modify_guard::{self, DeletionGuard, FileTransferGuard};
 use loader::HashSetVersionedLoader;
-use model::{AssetTrait, AssetType, Avatar, AvatarWearable, OtherAsset, WorldObject};
+use model::{
+    AssetDataLocation, AssetTrait, AssetType, Avatar, AvatarWearable, OtherAsset, WorldObject,
+};
 use serde::{Serialize, de::DeserializeOwned};
 use uuid::Uuid;
 
 use crate::{
-    definitions::AssetUpdatePayload, delete::delete_asset_image, utils::execute_image_fixation,
+    definitions::{AssetUpdatePayload, BrokenLinkedAsset, ExternalChangeReport},
+    delete::delete_asset_image,
+    lock::{LibraryLock, LockOwner, LockResult},
+    migration::{self, MIGRATION_TARGET_DIRS, MigrationCheckpoint, MigrationVerification},
+    utils::execute_image_fixation,
 };
 
 use super::json_asset_container::JsonAssetContainer;
@@ -28,6 +31,21 @@ pub struct AssetStorage {
     avatar_wearable_store: JsonAssetContainer<AvatarWearable>,
     world_object_store: JsonAssetContainer<WorldObject>,
     other_asset_store: JsonAssetContainer<OtherAsset>,
+
+    /// ロックを使う場合の、このインスタンスの ID
+    lock_instance_id: Option<Uuid>,
+    lock: Option<LibraryLock>,
+    /// 他のインスタンスがロックを保持しているため、読み取り専用で開いている
+    locked_by: Option<LockOwner>,
+}
+
+/// ライブラリのロックの状態
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryLockStatus {
+    pub read_only: bool,
+    /// 読み取り専用の場合、ロックを保持しているインスタンス
+    pub holder: Option<LockOwner>,
 }
 
 impl AssetStorage {
@@ -49,9 +67,120 @@ impl AssetStorage {
             avatar_wearable_store: avatar_wearable_store,
             world_object_store: world_object_store,
             other_asset_store: other_asset_store,
+
+            lock_instance_id: None,
+            lock: None,
+            locked_by: None,
         })
     }
 
+    /// データフォルダのロックを取得する。他のインスタンスが保持している場合は読み取り専用になる
+    ///
+    /// ロックを取得したインスタンスは、データフォルダを変更するたびに取得し直す
+    pub fn acquire_lock(
+        &mut self,
+        instance_id: Uuid,
+        force: bool,
+    ) -> Result<LibraryLockStatus, String> {
+        self.lock_instance_id = Some(instance_id);
+        self.lock = None;
+
+        match LibraryLock::acquire(&self.data_dir, instance_id, force)? {
+            LockResult::Acquired(lock) => {
+                self.lock = Some(lock);
+                self.locked_by = None;
+            }
+            LockResult::HeldByOther(owner) => {
+                log::warn!(
+                    "Library is locked by {} (pid = {}), opening in read-only mode",
+                    owner.hostname,
+                    owner.pid
+                );
+                self.locked_by = Some(owner);
+            }
+        }
+
+        self.apply_read_only();
+        Ok(self.lock_status())
+    }
+
+    /// ロックを保持していれば更新し、読み取り専用であればロックの取得を再試行する
+    pub fn refresh_lock(&mut self) -> Result<LibraryLockStatus, String> {
+        let Some(instance_id) = self.lock_instance_id else {
+            // ロックを使わずに開かれている
+            return Ok(self.lock_status());
+        };
+
+        if let Some(lock) = self.lock.as_mut() {
+            if lock.refresh()? {
+                return Ok(self.lock_status());
+            }
+
+            log::warn!("Library lock was taken over by another instance");
+        }
+
+        self.acquire_lock(instance_id, false)
+    }
+
+    /// 他のインスタンスが保持しているロックを奪い、書き込み可能にする
+    pub fn take_over_lock(&mut self) -> Result<LibraryLockStatus, String> {
+        let Some(instance_id) = self.lock_instance_id else {
+            return Err("Library lock is not used".into());
+        };
+
+        self.acquire_lock(instance_id, true)
+    }
+
+    pub fn lock_status(&self) -> LibraryLockStatus {
+        LibraryLockStatus {
+            read_only: self.locked_by.is_some(),
+            holder: self.locked_by.clone(),
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.locked_by.is_some()
+    }
+
+    /// 読み取り専用の場合はエラーを返す。ファイルのコピーなど、メタデータの保存より前に行う処理の前に呼び出す
+    pub fn ensure_writable(&self) -> Result<(), String> {
+        match &self.locked_by {
+            Some(owner) => Err(format!(
+                "Library is opened in read-only mode because it is locked by {}",
+                owner.hostname
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// 他のインスタンスや同期ツールによってメタデータが変更されていれば読み込み直す
+    pub async fn reload_if_modified(&self) -> Result<ExternalChangeReport, String> {
+        let mut report = ExternalChangeReport::default();
+
+        reload_store_if_modified(&self.avatar_store, &mut report).await?;
+        reload_store_if_modified(&self.avatar_wearable_store, &mut report).await?;
+        reload_store_if_modified(&self.world_object_store, &mut report).await?;
+        reload_store_if_modified(&self.other_asset_store, &mut report).await?;
+
+        if !report.conflicted_ids.is_empty() {
+            log::warn!(
+                "Kept local changes for externally modified assets: {:?}",
+                report.conflicted_ids
+            );
+        }
+
+        Ok(report)
+    }
+
+    fn apply_read_only(&self) {
+        let read_only = self.is_read_only();
+
+        self.avatar_store.set_read_only(read_only);
+        self.avatar_wearable_store.set_read_only(read_only);
+        self.world_object_store.set_read_only(read_only);
+        self.other_asset_store.set_read_only(read_only);
+    }
+
     pub async fn load_all_assets_from_files(&mut self) -> Result<(), String> {
         match self.avatar_store.load().await {
             Ok(_) => {}
@@ -96,6 +225,16 @@ impl AssetStorage {
         self.data_dir.clone()
     }
 
+    /// エクスポート用に、すべての種類のメタデータのファイル名と内容を返す
+    pub async fn export_metadata(&self) -> Result<Vec<(String, Vec<u8>)>, String> {
+        Ok(vec![
+            self.avatar_store.export_metadata().await?,
+            self.avatar_wearable_store.export_metadata().await?,
+            self.world_object_store.export_metadata().await?,
+            self.other_asset_store.export_metadata().await?,
+        ])
+    }
+
     pub async fn get_used_ids(&self) -> HashSet<Uuid> {
         let mut ids = HashSet::new();
 
@@ -125,6 +264,77 @@ impl AssetStorage {
         ids
     }
 
+    pub async fn get_used_booth_item_ids(&self) -> HashSet<u64> {
+        let mut ids = HashSet::new();
+
+        ids.extend(
+            self.avatar_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id),
+        );
+        ids.extend(
+            self.avatar_wearable_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id),
+        );
+        ids.extend(
+            self.world_object_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id),
+        );
+        ids.extend(
+            self.other_asset_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id),
+        );
+
+        ids
+    }
+
+    /// BOOTH のアイテムが紐づいているアセットの ID と、そのアイテムの ID を返す
+    pub async fn get_booth_linked_assets(&self) -> HashMap<Uuid, u64> {
+        let mut assets = HashMap::new();
+
+        assets.extend(
+            self.avatar_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id.map(|id| (a.id, id))),
+        );
+        assets.extend(
+            self.avatar_wearable_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id.map(|id| (a.id, id))),
+        );
+        assets.extend(
+            self.world_object_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id.map(|id| (a.id, id))),
+        );
+        assets.extend(
+            self.other_asset_store
+                .get_all()
+                .await
+                .iter()
+                .filter_map(|a| a.description.booth_item_id.map(|id| (a.id, id))),
+        );
+
+        assets
+    }
+
     pub async fn get_used_image_filenames(&self) -> HashSet<String> {
         let mut filenames = HashSet::new();
 
@@ -160,11 +370,11 @@ impl AssetStorage {
         filenames
     }
 
-    pub async fn replace_thumbnails(&self, map: HashMap<String, String>) -> Result<(), String> {
-        self.avatar_store.replace_thumbnails(&map).await?;
-        self.avatar_wearable_store.replace_thumbnails(&map).await?;
-        self.world_object_store.replace_thumbnails(&map).await?;
-        self.other_asset_store.replace_thumbnails(&map).await?;
+    pub async fn replace_thumbnails(&self, map: &HashMap<String, String>) -> Result<(), String> {
+        self.avatar_store.replace_thumbnails(map).await?;
+        self.avatar_wearable_store.replace_thumbnails(map).await?;
+        self.world_object_store.replace_thumbnails(map).await?;
+        self.other_asset_store.replace_thumbnails(map).await?;
 
         Ok(())
     }
@@ -174,6 +384,9 @@ impl AssetStorage {
         asset: AssetUpdatePayload,
         use_trash_bin: bool,
     ) -> Result<(), String> {
+        // 種類の変更では保存より前に画像を削除するため、先に確認する
+        self.ensure_writable()?;
+
         match asset {
             AssetUpdatePayload::Avatar(avatar) => {
                 if self.avatar_store.get_asset(avatar.id).await.is_some() {
@@ -240,14 +453,22 @@ impl AssetStorage {
         }
     }
 
+    /// データフォルダを `new_path` に移行する
+    ///
+    /// 進捗は移行先に記録されるため、クラッシュやキャンセルで中断された場合でも同じ移行先を指定すれば再開できる。
+    /// 同じボリューム内であればディレクトリを移動し、そうでなければファイルごとにコピーして `verification` で検証する
     pub async fn migrate_data_dir<P>(
         &mut self,
         new_path: P,
+        verification: MigrationVerification,
         progress_callback: impl Fn(f32, String),
     ) -> Result<MigrateResult, String>
     where
         P: AsRef<Path>,
     {
+        // 他のインスタンスが使用中のデータフォルダを移動しない
+        self.ensure_writable()?;
+
         let new_path = new_path.as_ref();
 
         if !new_path.is_dir() {
@@ -261,126 +482,90 @@ impl AssetStorage {
 
         let old_path = self.data_dir.clone();
 
-        let old_metadata_path = old_path.join("metadata");
-        let new_metadata_path = new_path.join("metadata");
-
-        let old_data_path = old_path.join("data");
-        let new_data_path = new_path.join("data");
-
-        let old_images_path = old_path.join("images");
-        let new_images_path = new_path.join("images");
-
-        let mut dir_cleanups = Vec::new();
-
-        if old_metadata_path.exists() {
-            if new_metadata_path.exists() {
-                if let Err(e) = rename_conflict_dir(&new_metadata_path).await {
+        let mut checkpoint = match MigrationCheckpoint::open(new_path)? {
+            Some(mut checkpoint) => {
+                if checkpoint.source() != old_path {
                     let msg = format!(
-                        "Failed to rename and backup conflicted metadata dir: {:?}",
-                        e
+                        "Another migration from {} is pending in the destination",
+                        checkpoint.source().display()
                     );
                     log::error!("{}", &msg);
                     return Err(msg);
                 }
-            }
 
-            dir_cleanups.push(DeleteOnDrop::new(new_metadata_path.clone()));
-
-            if let Err(e) = modify_guard::copy_dir(
-                old_metadata_path.clone(),
-                new_metadata_path.clone(),
-                false,
-                FileTransferGuard::both(&old_path, &new_path.to_path_buf()),
-                |progress, filename| {
-                    // プログレスバーのうち 1/10 をメタデータのコピーとして扱う
-                    // progress は 0 - 1 の範囲であるため、10倍して % に変換する
-                    let percentage = progress * 10f32;
-
-                    progress_callback(percentage, filename);
-                },
-            )
-            .await
-            {
-                let msg = format!("Failed to copy metadata dir: {:?}", e);
-                log::error!("{}", &msg);
-                return Err(msg);
+                log::info!("Resuming data dir migration to {}", new_path.display());
+                migration::check_renamed_dirs(&old_path, new_path, &mut checkpoint)?;
+                checkpoint
             }
-        }
-
-        if old_data_path.exists() {
-            if new_data_path.exists() {
-                if let Err(e) = rename_conflict_dir(&new_data_path).await {
-                    let msg = format!("Failed to rename and backup conflicted data dir: {:?}", e);
-                    log::error!("{}", &msg);
-                    return Err(msg);
+            None => {
+                for dir in MIGRATION_TARGET_DIRS {
+                    let new_dir = new_path.join(dir);
+
+                    if old_path.join(dir).exists() && new_dir.exists() {
+                        if let Err(e) = rename_conflict_dir(&new_dir).await {
+                            let msg = format!(
+                                "Failed to rename and backup conflicted {} dir: {:?}",
+                                dir, e
+                            );
+                            log::error!("{}", &msg);
+                            return Err(msg);
+                        }
+                    }
                 }
-            }
 
-            dir_cleanups.push(DeleteOnDrop::new(new_data_path.clone()));
+                MigrationCheckpoint::create(new_path, &old_path)?
+            }
+        };
 
-            if let Err(e) = modify_guard::copy_dir(
-                old_data_path.clone(),
-                new_data_path.clone(),
-                false,
-                FileTransferGuard::both(&old_path, &new_path.to_path_buf()),
-                |progress, filename| {
-                    // プログレスバーのうち 8/10 をデータのコピーとして扱う
-                    // progress は 0 - 1 の範囲であるため、80倍して % に変換する
-                    let percentage = 10f32 + (progress * 80f32);
+        migration::rename_dirs_if_possible(
+            &old_path,
+            new_path,
+            &MIGRATION_TARGET_DIRS,
+            &mut checkpoint,
+        )
+        .await?;
 
-                    progress_callback(percentage, filename);
-                },
-            )
-            .await
-            {
-                let msg = format!("Failed to copy data dir: {:?}", e);
-                log::error!("{}", &msg);
-                return Err(msg);
-            }
+        if !checkpoint.renamed_dirs().is_empty() {
+            log::info!(
+                "Moved dirs without copying: {:?}",
+                checkpoint.renamed_dirs()
+            );
         }
 
-        if old_images_path.exists() {
-            if new_images_path.exists() {
-                if let Err(e) = rename_conflict_dir(&new_images_path).await {
-                    let msg = format!("Failed to rename and backup conflicted images dir: {:?}", e);
-                    log::error!("{}", &msg);
-                    return Err(msg);
-                }
-            }
-
-            dir_cleanups.push(DeleteOnDrop::new(new_images_path.clone()));
+        let result = migration::copy_dirs_with_checkpoint(
+            &old_path,
+            new_path,
+            &MIGRATION_TARGET_DIRS,
+            &mut checkpoint,
+            verification,
+            progress_callback,
+        )
+        .await;
 
-            if let Err(e) = modify_guard::copy_dir(
-                old_images_path.clone(),
-                new_images_path.clone(),
-                false,
-                FileTransferGuard::both(&old_path, &new_path.to_path_buf()),
-                |progress, filename| {
-                    // プログレスバーのうち 1/10 を画像のコピーとして扱う
-                    // progress は 0 - 1 の範囲であるため、10倍して % に変換する
-                    let percentage = 90f32 + (progress * 10f32);
+        if let Err(e) = result {
+            // 移動したディレクトリを戻し、コピー済みのファイルは再開できるよう移行先に残す
+            migration::restore_renamed_dirs(&old_path, new_path, &mut checkpoint).await;
 
-                    progress_callback(percentage, filename);
-                },
-            )
-            .await
-            {
-                let msg = format!("Failed to copy images dir: {:?}", e);
-                log::error!("{}", &msg);
-                return Err(msg);
-            }
+            return Err(e);
         }
 
         if let Err(e) = self.set_data_dir_and_reload(new_path).await {
+            // 移動したディレクトリを戻し、移行前の状態で読み込み直す
+            migration::restore_renamed_dirs(&old_path, new_path, &mut checkpoint).await;
+
+            if let Err(e) = self.set_data_dir_and_reload(&old_path).await {
+                log::error!("Failed to reload assets from old data dir: {:?}", e);
+            }
+
             return Err(format!("Failed to load assets: {:?}", e));
         }
 
-        dir_cleanups
-            .iter_mut()
-            .for_each(|cleanup| cleanup.mark_as_completed());
+        if let Err(e) = checkpoint.finish() {
+            log::warn!("Failed to remove migration checkpoint: {}", e);
+        }
 
-        for old in vec![old_metadata_path, old_data_path, old_images_path] {
-            let mut successfully_deleted = true;
+        for dir in MIGRATION_TARGET_DIRS {
+            let old = old_path.join(dir);
 
             if old.exists() {
                 log::info!("Removing old dir: {}", old.display());
@@ -390,13 +575,9 @@ impl AssetStorage {
 
                 if let Err(e) = result {
                     log::warn!("Failed to remove old data dir: {:?}", e);
-                    successfully_deleted = false;
+                    return Ok(MigrateResult::MigratedButFailedToDeleteOldDir);
                 }
             }
-
-            if !successfully_deleted {
-                return Ok(MigrateResult::MigratedButFailedToDeleteOldDir);
-            }
         }
 
         Ok(MigrateResult::Migrated)
@@ -436,6 +617,11 @@ impl AssetStorage {
 
         self.data_dir = new_path;
 
+        // ロックを使っている場合は、新しいデータフォルダのロックに取り替える
+        if let Some(instance_id) = self.lock_instance_id {
+            self.acquire_lock(instance_id, false)?;
+        }
+
         self.load_all_assets_from_files().await
     }
 
@@ -448,6 +634,90 @@ impl AssetStorage {
         return Ok(());
     }
 
+    /// アセットの種類に関わらず、アセットのデータの保存場所を取得する
+    pub async fn get_data_location(&self, id: Uuid) -> Option<AssetDataLocation> {
+        if let Some(asset) = self.avatar_store.get_asset(id).await {
+            return Some(asset.description.data_location);
+        }
+        if let Some(asset) = self.avatar_wearable_store.get_asset(id).await {
+            return Some(asset.description.data_location);
+        }
+        if let Some(asset) = self.world_object_store.get_asset(id).await {
+            return Some(asset.description.data_location);
+        }
+        if let Some(asset) = self.other_asset_store.get_asset(id).await {
+            return Some(asset.description.data_location);
+        }
+
+        None
+    }
+
+    /// アセットの種類に関わらず、アセットを取得する
+    pub async fn get_asset(&self, id: Uuid) -> Option<AssetUpdatePayload> {
+        if let Some(asset) = self.avatar_store.get_asset(id).await {
+            return Some(AssetUpdatePayload::Avatar(asset));
+        }
+        if let Some(asset) = self.avatar_wearable_store.get_asset(id).await {
+            return Some(AssetUpdatePayload::AvatarWearable(asset));
+        }
+        if let Some(asset) = self.world_object_store.get_asset(id).await {
+            return Some(AssetUpdatePayload::WorldObject(asset));
+        }
+        if let Some(asset) = self.other_asset_store.get_asset(id).await {
+            return Some(AssetUpdatePayload::OtherAsset(asset));
+        }
+
+        None
+    }
+
+    /// アセットのファイルが置かれているフォルダを返す。リンクされたアセットの場合は参照先のフォルダ
+    pub async fn resolve_asset_data_dir(&self, id: Uuid) -> Option<PathBuf> {
+        self.get_data_location(id)
+            .await?
+            .resolve_data_dir(&self.data_dir, id)
+    }
+
+    /// 参照先のフォルダが存在しない、リンクされたアセットを探す
+    pub async fn find_broken_linked_assets(&self) -> Vec<BrokenLinkedAsset> {
+        let mut result = vec![];
+
+        result.extend(find_broken_links(&self.avatar_store).await);
+        result.extend(find_broken_links(&self.avatar_wearable_store).await);
+        result.extend(find_broken_links(&self.world_object_store).await);
+        result.extend(find_broken_links(&self.other_asset_store).await);
+
+        result
+    }
+
+    /// アセットの種類に関わらず、アセットのデータの保存場所を変更する
+    pub async fn set_data_location(
+        &self,
+        id: Uuid,
+        location: AssetDataLocation,
+    ) -> Result<(), String> {
+        if self
+            .avatar_store
+            .set_data_location(id, location.clone())
+            .await?
+            || self
+                .avatar_wearable_store
+                .set_data_location(id, location.clone())
+                .await?
+            || self
+                .world_object_store
+                .set_data_location(id, location.clone())
+                .await?
+            || self
+                .other_asset_store
+                .set_data_location(id, location)
+                .await?
+        {
+            return Ok(());
+        }
+
+        Err(format!("Asset not found: {}", id))
+    }
+
     pub async fn create_backup<P: AsRef<Path>>(
         &self,
         metadata_backup_dir: P,
@@ -517,6 +787,7 @@ where
     if T::asset_type() != AssetType::Avatar {
         if let Some(avatar) = storage.avatar_store.get_asset(id).await {
             asset.get_description_as_mut().created_at = avatar.description.created_at;
+            asset.get_description_as_mut().data_location = avatar.description.data_location.clone();
             handle_image_change(
                 &mut asset,
                 &storage.data_dir,
@@ -535,6 +806,8 @@ where
     if T::asset_type() != AssetType::AvatarWearable {
         if let Some(avatar_wearable) = storage.avatar_wearable_store.get_asset(id).await {
             asset.get_description_as_mut().created_at = avatar_wearable.description.created_at;
+            asset.get_description_as_mut().data_location =
+                avatar_wearable.description.data_location.clone();
             handle_image_change(
                 &mut asset,
                 &storage.data_dir,
@@ -556,6 +829,8 @@ where
     if T::asset_type() != AssetType::WorldObject {
         if let Some(world_object) = storage.world_object_store.get_asset(id).await {
             asset.get_description_as_mut().created_at = world_object.description.created_at;
+            asset.get_description_as_mut().data_location =
+                world_object.description.data_location.clone();
             handle_image_change(
                 &mut asset,
                 &storage.data_dir,
@@ -573,6 +848,8 @@ where
 
     if let Some(other_asset) = storage.other_asset_store.get_asset(id).await {
         asset.get_description_as_mut().created_at = other_asset.description.created_at;
+        asset.get_description_as_mut().data_location =
+            other_asset.description.data_location.clone();
         handle_image_change(
             &mut asset,
             &storage.data_dir,
@@ -590,6 +867,50 @@ where
     Err("Asset not found".into())
 }
 
+async fn find_broken_links<T>(store: &JsonAssetContainer<T>) -> Vec<BrokenLinkedAsset>
+where
+    T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + DeserializeOwned + Eq + Hash,
+{
+    store
+        .get_all()
+        .await
+        .iter()
+        .filter_map(|asset| {
+            let description = asset.get_description();
+
+            let AssetDataLocation::Linked { path } = &description.data_location else {
+                return None;
+            };
+
+            if Path::new(path).is_dir() {
+                return None;
+            }
+
+            Some(BrokenLinkedAsset {
+                id: asset.get_id(),
+                asset_type: T::asset_type(),
+                name: description.name.clone(),
+                path: path.clone(),
+            })
+        })
+        .collect()
+}
+
+async fn reload_store_if_modified<T>(
+    store: &JsonAssetContainer<T>,
+    report: &mut ExternalChangeReport,
+) -> Result<(), String>
+where
+    T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + DeserializeOwned + Eq + Hash,
+{
+    if let Some(conflicts) = store.reload_if_modified().await? {
+        report.reloaded_types.push(T::asset_type());
+        report.conflicted_ids.extend(conflicts);
+    }
+
+    Ok(())
+}
+
 async fn handle_image_change<T: AssetTrait>(
     asset: &mut T,
     data_dir: &Path,
@@ -788,6 +1109,156 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_set_data_location() {
+        let from = "../../test/example_root_dir/sample1";
+        let target = "test/temp/asset_storage_set_data_location";
+
+        setup_dir(from, target).await;
+
+        let mut storage = AssetStorage::create(target).unwrap();
+        storage.load_all_assets_from_files().await.unwrap();
+
+        let id = Uuid::from_str("2bde4d66-1843-4250-b929-157f947f5751").unwrap();
+        let location = AssetDataLocation::External {
+            location: "E:/Assets".into(),
+        };
+
+        storage
+            .set_data_location(id, location.clone())
+            .await
+            .unwrap();
+
+        let mut reloaded = AssetStorage::create(target).unwrap();
+        reloaded.load_all_assets_from_files().await.unwrap();
+
+        assert_eq!(reloaded.get_data_location(id).await, Some(location));
+
+        assert!(
+            storage
+                .set_data_location(Uuid::new_v4(), AssetDataLocation::Local)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_asset_keeps_data_location() {
+        let test_dir = "test/temp/update_asset_keeps_data_location";
+
+        if std::fs::exists(test_dir).unwrap() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+        std::fs::create_dir_all(test_dir).unwrap();
+
+        let storage = AssetStorage::create(test_dir).unwrap();
+
+        let location = AssetDataLocation::External {
+            location: "E:/Assets".into(),
+        };
+        let avatar = Avatar {
+            id: Uuid::new_v4(),
+            description: AssetDescription {
+                name: "External Avatar".into(),
+                creator: "Test Creator".into(),
+                image_filename: None,
+                tags: vec![],
+                memo: None,
+                booth_item_id: None,
+                dependencies: vec![],
+                created_at: 1234567890000,
+                published_at: None,
+                data_location: location.clone(),
+            },
+        };
+
+        storage
+            .avatar_store
+            .add_asset_and_save(avatar.clone())
+            .await
+            .unwrap();
+
+        // 編集画面からは data_location が送られないため、既定値の Local になっている
+        let mut edited = avatar.clone();
+        edited.description.name = "Edited Avatar".into();
+        edited.description.data_location = AssetDataLocation::Local;
+
+        storage
+            .update_asset_and_save(AssetUpdatePayload::Avatar(edited), false)
+            .await
+            .unwrap();
+
+        let stored = storage.avatar_store.get_asset(avatar.id).await.unwrap();
+        assert_eq!(stored.description.name, "Edited Avatar");
+        assert_eq!(stored.description.data_location, location);
+
+        // 種類を変更した場合も保存場所を引き継ぐ
+        let other_asset = OtherAsset {
+            id: avatar.id,
+            description: AssetDescription {
+                data_location: AssetDataLocation::Local,
+                ..stored.description
+            },
+            category: "TestCategory".into(),
+        };
+
+        storage
+            .update_asset_and_save(AssetUpdatePayload::OtherAsset(other_asset), false)
+            .await
+            .unwrap();
+
+        assert_eq!(storage.get_data_location(avatar.id).await, Some(location));
+    }
+
+    #[tokio::test]
+    async fn test_find_broken_linked_assets() {
+        let from = "../../test/example_root_dir/sample1";
+        let target = "test/temp/asset_storage_broken_links";
+
+        setup_dir(from, target).await;
+
+        let mut storage = AssetStorage::create(target).unwrap();
+        storage.load_all_assets_from_files().await.unwrap();
+
+        let linked_id = Uuid::from_str("72e89e43-2d29-4910-b24e-9550a6ea7152").unwrap();
+        let broken_id = Uuid::from_str("2bde4d66-1843-4250-b929-157f947f5751").unwrap();
+
+        let linked_path = std::path::absolute(format!("{target}/linked")).unwrap();
+        let broken_path = std::path::absolute(format!("{target}/missing")).unwrap();
+        std::fs::create_dir_all(&linked_path).unwrap();
+
+        storage
+            .set_data_location(
+                linked_id,
+                AssetDataLocation::Linked {
+                    path: linked_path.to_string_lossy().to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        storage
+            .set_data_location(
+                broken_id,
+                AssetDataLocation::Linked {
+                    path: broken_path.to_string_lossy().to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage.resolve_asset_data_dir(linked_id).await,
+            Some(linked_path)
+        );
+
+        let broken = storage.find_broken_linked_assets().await;
+
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].id, broken_id);
+        assert_eq!(broken[0].asset_type, AssetType::AvatarWearable);
+        assert_eq!(broken[0].path, broken_path.to_string_lossy());
+    }
+
     #[tokio::test]
     async fn test_asset_storage_migration() {
         let from = "../../test/example_root_dir/sample1";
@@ -801,11 +1272,216 @@ mod tests {
 
         storage.load_all_assets_from_files().await.unwrap();
 
-        let result = storage.migrate_data_dir(new_path, |_, _| {}).await.unwrap();
+        let result = storage
+            .migrate_data_dir(new_path, MigrationVerification::Size, |_, _| {})
+            .await
+            .unwrap();
 
         assert_eq!(result, MigrateResult::Migrated);
     }
 
+    #[tokio::test]
+    async fn test_resume_asset_storage_migration() {
+        let from = "../../test/example_root_dir/sample1";
+        let current_path = "test/temp/asset_storage_migration_resume/current";
+        let new_path = "test/temp/asset_storage_migration_resume/new";
+
+        setup_dir(from, current_path).await;
+
+        if std::fs::exists(new_path).unwrap() {
+            std::fs::remove_dir_all(new_path).unwrap();
+        }
+
+        // metadata だけコピーした時点で中断されたことを再現する
+        setup_dir(
+            format!("{current_path}/metadata"),
+            format!("{new_path}/metadata"),
+        )
+        .await;
+        MigrationCheckpoint::create(new_path, current_path).unwrap();
+
+        let mut storage = AssetStorage::create(current_path).unwrap();
+        storage.load_all_assets_from_files().await.unwrap();
+
+        let asset_count = storage.get_avatar_wearable_store().get_all().await.len();
+
+        let result = storage
+            .migrate_data_dir(new_path, MigrationVerification::Hash, |_, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(result, MigrateResult::Migrated);
+        assert_eq!(storage.data_dir(), PathBuf::from(new_path));
+        assert_eq!(
+            storage.get_avatar_wearable_store().get_all().await.len(),
+            asset_count
+        );
+
+        // 途中から再開した場合も元の metadata はバックアップされずに使われる
+        assert!(!std::fs::exists(format!("{new_path}/metadata_backup")).unwrap());
+        assert!(std::fs::exists(format!("{new_path}/data")).unwrap());
+        assert!(!std::fs::exists(format!("{current_path}/data")).unwrap());
+        assert!(
+            crate::migration::find_pending_migration(new_path)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_asset_storage_migration_skips_completed_files() {
+        let from = "../../test/example_root_dir/sample1";
+        let current_path = "test/temp/asset_storage_migration_skip/current";
+        let new_path = "test/temp/asset_storage_migration_skip/new";
+
+        setup_dir(from, current_path).await;
+        setup_dir(from, new_path).await;
+
+        let completed = "data/b2003e9a-86c1-4ab6-9e6b-4388497e2226/dummy.txt";
+        let incomplete = "data/c155488e-53bf-4c98-92e5-e5c8f66a1667/dummy.txt";
+
+        // 記録済みのファイルは内容を変えても同じサイズであればコピーし直されない
+        let size = std::fs::metadata(format!("{current_path}/{completed}"))
+            .unwrap()
+            .len();
+        let marker = vec![b'x'; size as usize];
+        std::fs::write(format!("{new_path}/{completed}"), &marker).unwrap();
+        std::fs::write(format!("{new_path}/{incomplete}"), b"").unwrap();
+
+        let mut checkpoint = MigrationCheckpoint::create(new_path, current_path).unwrap();
+        checkpoint.mark_as_completed(completed).unwrap();
+        drop(checkpoint);
+
+        let mut storage = AssetStorage::create(current_path).unwrap();
+        storage.load_all_assets_from_files().await.unwrap();
+
+        let result = storage
+            .migrate_data_dir(new_path, MigrationVerification::Hash, |_, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(result, MigrateResult::Migrated);
+        assert_eq!(
+            std::fs::read(format!("{new_path}/{completed}")).unwrap(),
+            marker
+        );
+        assert_eq!(
+            std::fs::read(format!("{new_path}/{incomplete}")).unwrap(),
+            std::fs::read(format!("{from}/{incomplete}")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_asset_storage_migration_restores_renamed_dirs() {
+        let from = "../../test/example_root_dir/sample1";
+        let current_path = "test/temp/asset_storage_migration_failure/current";
+        let new_path = "test/temp/asset_storage_migration_failure/new";
+
+        setup_dir(from, current_path).await;
+
+        if std::fs::exists(new_path).unwrap() {
+            std::fs::remove_dir_all(new_path).unwrap();
+        }
+
+        // data のコピー中に失敗させるため、ファイルと同じ名前のディレクトリを移行先に作成する
+        let file = "data/b2003e9a-86c1-4ab6-9e6b-4388497e2226/dummy.txt";
+        std::fs::create_dir_all(format!("{new_path}/{file}")).unwrap();
+        MigrationCheckpoint::create(new_path, current_path).unwrap();
+
+        let mut storage = AssetStorage::create(current_path).unwrap();
+        storage.load_all_assets_from_files().await.unwrap();
+
+        let result = storage
+            .migrate_data_dir(new_path, MigrationVerification::Size, |_, _| {})
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(storage.data_dir(), PathBuf::from(current_path));
+
+        // 移動した metadata と images は元の場所に戻っている
+        assert!(std::fs::exists(format!("{current_path}/metadata")).unwrap());
+        assert!(std::fs::exists(format!("{current_path}/images")).unwrap());
+        assert!(!std::fs::exists(format!("{new_path}/metadata")).unwrap());
+        assert!(!std::fs::exists(format!("{new_path}/images")).unwrap());
+        assert!(
+            crate::migration::find_pending_migration(new_path)
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_asset_storage_migration_after_crash_while_renaming() {
+        let from = "../../test/example_root_dir/sample1";
+        let current_path = "test/temp/asset_storage_migration_crash/current";
+        let new_path = "test/temp/asset_storage_migration_crash/new";
+
+        setup_dir(from, current_path).await;
+
+        if std::fs::exists(new_path).unwrap() {
+            std::fs::remove_dir_all(new_path).unwrap();
+        }
+        std::fs::create_dir_all(new_path).unwrap();
+
+        // ディレクトリを移動した後、コピーを始める前に終了したことを再現する
+        {
+            let mut checkpoint = MigrationCheckpoint::create(new_path, current_path).unwrap();
+            crate::migration::rename_dirs_if_possible(
+                Path::new(current_path),
+                Path::new(new_path),
+                &MIGRATION_TARGET_DIRS,
+                &mut checkpoint,
+            )
+            .await
+            .unwrap();
+        }
+
+        let metadata = std::fs::read(format!("{new_path}/metadata/avatarWearables.json")).unwrap();
+
+        // 次の起動時に、移行元に空の metadata が作成される
+        std::fs::create_dir_all(format!("{current_path}/metadata")).unwrap();
+        std::fs::write(
+            format!("{current_path}/metadata/avatarWearables.json"),
+            b"[]",
+        )
+        .unwrap();
+
+        let mut storage = AssetStorage::create(current_path).unwrap();
+        storage.load_all_assets_from_files().await.unwrap();
+
+        let result = storage
+            .migrate_data_dir(new_path, MigrationVerification::Size, |_, _| {})
+            .await;
+
+        // 移動済みの metadata は空の metadata で上書きされない
+        assert!(result.is_err());
+        assert_eq!(
+            std::fs::read(format!("{new_path}/metadata/avatarWearables.json")).unwrap(),
+            metadata
+        );
+
+        // 移行元に作成された metadata がなければ、移動済みのディレクトリを使って再開できる
+        std::fs::remove_dir_all(format!("{current_path}/metadata")).unwrap();
+
+        let result = storage
+            .migrate_data_dir(new_path, MigrationVerification::Size, |_, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(result, MigrateResult::Migrated);
+        assert_eq!(
+            std::fs::read(format!("{new_path}/metadata/avatarWearables.json")).unwrap(),
+            metadata
+        );
+        assert!(
+            !storage
+                .get_avatar_wearable_store()
+                .get_all()
+                .await
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn test_rename_conflict_dir() {
         let base = "test/temp/rename_conflict_dir";
@@ -879,6 +1555,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1234567890000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
         };
 
@@ -912,6 +1589,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1234567890000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
             category: "TestCategory".into(),
             supported_avatars: BTreeSet::new(),
@@ -947,6 +1625,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1234567890000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
             category: "TestCategory".into(),
         };
@@ -984,6 +1663,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1234567890000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
             category: "TestCategory".into(),
         };
@@ -1022,6 +1702,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1234567890000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
         };
 
@@ -1,5 +1,8 @@
-use model::{AssetType, Avatar, AvatarWearable, OtherAsset, WorldObject};
+use model::{
+    AssetDescription, AssetTrait, AssetType, Avatar, AvatarWearable, OtherAsset, WorldObject,
+};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 #[serde(rename_all = "camelCase")]
@@ -10,6 +13,87 @@ pub enum AssetUpdatePayload {
     OtherAsset(OtherAsset),
 }
 
+impl AssetUpdatePayload {
+    pub fn description(&self) -> &AssetDescription {
+        match self {
+            AssetUpdatePayload::Avatar(avatar) => avatar.get_description(),
+            AssetUpdatePayload::AvatarWearable(avatar_wearable) => {
+                avatar_wearable.get_description()
+            }
+            AssetUpdatePayload::WorldObject(world_object) => world_object.get_description(),
+            AssetUpdatePayload::OtherAsset(other_asset) => other_asset.get_description(),
+        }
+    }
+
+    pub fn description_mut(&mut self) -> &mut AssetDescription {
+        match self {
+            AssetUpdatePayload::Avatar(avatar) => avatar.get_description_as_mut(),
+            AssetUpdatePayload::AvatarWearable(avatar_wearable) => {
+                avatar_wearable.get_description_as_mut()
+            }
+            AssetUpdatePayload::WorldObject(world_object) => world_object.get_description_as_mut(),
+            AssetUpdatePayload::OtherAsset(other_asset) => other_asset.get_description_as_mut(),
+        }
+    }
+}
+
+/// 参照先のフォルダが見つからない、リンクされたアセット
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenLinkedAsset {
+    pub id: Uuid,
+    pub asset_type: AssetType,
+    pub name: String,
+    pub path: String,
+}
+
+/// データフォルダの整合性チェックの結果
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    /// どのアセットにも登録されていない `data/` 以下のディレクトリ名
+    pub orphan_data_dirs: Vec<String>,
+    /// データのディレクトリが見つからないアセット
+    pub dead_entries: Vec<DeadAssetEntry>,
+    /// どのアセットからも参照されていない `images/` 以下の画像
+    pub orphan_images: Vec<String>,
+    /// インポートされずに残った `temp_` から始まる画像
+    pub temp_images: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_empty(&self) -> bool {
+        self.orphan_data_dirs.is_empty()
+            && self.dead_entries.is_empty()
+            && self.orphan_images.is_empty()
+            && self.temp_images.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadAssetEntry {
+    pub id: Uuid,
+    pub asset_type: AssetType,
+    pub name: String,
+}
+
+/// 他のインスタンスや同期ツールによるメタデータの変更を読み込んだ結果
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalChangeReport {
+    /// 読み込み直したアセットの種類
+    pub reloaded_types: Vec<AssetType>,
+    /// 手元でも変更されていたため、手元の内容を優先したアセット
+    pub conflicted_ids: Vec<Uuid>,
+}
+
+impl ExternalChangeReport {
+    pub fn is_empty(&self) -> bool {
+        self.reloaded_types.is_empty()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, specta::Type)]
 pub enum MatchType {
     AND,
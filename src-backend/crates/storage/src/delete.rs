@@ -72,17 +72,21 @@ async fn delete_asset_from_store<
         return Ok(false);
     }
 
+    // リンクされたアセットの参照先は削除しない。ファイルを持たないアセットには data/<id> が存在しない
     let path = app_dir.join("data").join(id.to_string());
-    let dir_delete_result = if use_trash_bin {
-        modify_guard::trash_recursive(&path, &DeletionGuard::new(app_dir))
-    } else {
-        modify_guard::delete_recursive_completely(&path, &DeletionGuard::new(app_dir))
-            .await
-            .map_err(|e| e.to_string())
-    };
 
-    if let Err(e) = dir_delete_result {
-        return Err(format!("Failed to delete asset directory: {:?}", e));
+    if path.exists() {
+        let dir_delete_result = if use_trash_bin {
+            modify_guard::trash_recursive(&path, &DeletionGuard::new(app_dir))
+        } else {
+            modify_guard::delete_recursive_completely(&path, &DeletionGuard::new(app_dir))
+                .await
+                .map_err(|e| e.to_string())
+        };
+
+        if let Err(e) = dir_delete_result {
+            return Err(format!("Failed to delete asset directory: {:?}", e));
+        }
     }
 
     let image = &asset.get_description().image_filename;
@@ -106,23 +110,25 @@ pub async fn delete_asset_image(
 
     log::info!("Image file path: {}", image_path.display());
 
-    if !image_path.exists() {
-        return Ok(true);
-    }
+    // 詳細表示用に残した元の画像も一緒に削除する
+    let original_path = file::original_image_path(&images_dir_path, filename);
 
-    let image_delete_result = if use_trash_bin {
-        modify_guard::trash_recursive(&image_path, &DeletionGuard::new(&images_dir_path))
-    } else {
-        modify_guard::delete_recursive_completely(
-            &image_path,
-            &DeletionGuard::new(&images_dir_path),
-        )
-        .await
-        .map_err(|e| e.to_string())
-    };
+    for path in [image_path, original_path] {
+        if !path.exists() {
+            continue;
+        }
 
-    if let Err(e) = image_delete_result {
-        return Err(format!("Failed to delete image file: {:?}", e));
+        let image_delete_result = if use_trash_bin {
+            modify_guard::trash_recursive(&path, &DeletionGuard::new(&images_dir_path))
+        } else {
+            modify_guard::delete_recursive_completely(&path, &DeletionGuard::new(&images_dir_path))
+                .await
+                .map_err(|e| e.to_string())
+        };
+
+        if let Err(e) = image_delete_result {
+            return Err(format!("Failed to delete image file: {:?}", e));
+        }
     }
 
     Ok(true)
@@ -135,8 +141,17 @@ pub async fn delete_temporary_images(app_dir: &PathBuf) -> Result<(), String> {
         return Ok(());
     }
 
-    let entries = std::fs::read_dir(&images_dir_path)
-        .map_err(|e| format!("Failed to read images directory: {:?}", e))?;
+    let mut entries = std::fs::read_dir(&images_dir_path)
+        .map_err(|e| format!("Failed to read images directory: {:?}", e))?
+        .collect::<Vec<_>>();
+
+    let originals_dir_path = images_dir_path.join(file::ORIGINAL_IMAGES_DIR);
+    if originals_dir_path.exists() {
+        entries.extend(
+            std::fs::read_dir(&originals_dir_path)
+                .map_err(|e| format!("Failed to read original images directory: {:?}", e))?,
+        );
+    }
 
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry: {:?}", e))?;
@@ -172,7 +187,7 @@ pub async fn delete_temporary_images(app_dir: &PathBuf) -> Result<(), String> {
 
 #[cfg(test)]
 mod tests {
-    use model::{AssetDescription, Avatar};
+    use model::{AssetDataLocation, AssetDescription, Avatar};
 
     use super::*;
     use std::{fs::File, io::Write};
@@ -226,13 +241,19 @@ mod tests {
         let image_path = app_dir.join("images").join(filename);
         assert!(image_path.exists());
 
+        // Keep the original image for the detail view
+        let original_path = file::original_image_path(app_dir.join("images"), filename);
+        std::fs::create_dir_all(original_path.parent().unwrap()).unwrap();
+        std::fs::write(&original_path, b"original image content").unwrap();
+
         // Delete the image
         let result = delete_asset_image(&app_dir, filename, false).await;
 
-        // Verify the result and that the image was deleted
+        // Verify the result and that the image and its original were deleted
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), true);
         assert!(!image_path.exists());
+        assert!(!original_path.exists());
 
         // Test deleting a non-existent image
         let result = delete_asset_image(&app_dir, "non_existent.jpg", false).await;
@@ -294,6 +315,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1234567890000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
         };
 
@@ -331,4 +353,43 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), false);
     }
+
+    #[tokio::test]
+    async fn test_delete_linked_asset_from_store() {
+        let app_dir = setup_test_dir("test/temp/delete_linked_asset_from_store").await;
+
+        let storage = AssetStorage::create(&app_dir).unwrap();
+        let store = storage.get_avatar_store();
+
+        let link_target = app_dir.join("linked");
+        std::fs::create_dir_all(&link_target).unwrap();
+        std::fs::write(link_target.join("test_file.txt"), b"linked").unwrap();
+
+        let asset_id = Uuid::new_v4();
+        let avatar = Avatar {
+            id: asset_id,
+            description: AssetDescription {
+                name: "Linked Avatar".into(),
+                creator: "Test Creator".into(),
+                image_filename: None,
+                tags: vec![],
+                memo: None,
+                booth_item_id: None,
+                dependencies: vec![],
+                created_at: 1234567890000,
+                published_at: None,
+                data_location: AssetDataLocation::Linked {
+                    path: link_target.to_string_lossy().to_string(),
+                },
+            },
+        };
+
+        store.add_asset_and_save(avatar).await.unwrap();
+
+        let result = delete_asset_from_store(&app_dir, store, asset_id, false).await;
+
+        assert_eq!(result, Ok(true));
+        assert!(store.get_asset(asset_id).await.is_none());
+        assert!(link_target.join("test_file.txt").exists());
+    }
 }
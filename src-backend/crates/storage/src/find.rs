@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 
 const IGNORE_DIRECTORY_NAMES: [&str; 1] = ["__MACOSX"];
 
+/// macOS のリソースフォーク (`._` で始まるファイル) は unitypackage として扱わない
+const RESOURCE_FORK_PREFIX: &str = "._";
+
 #[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 #[serde(rename_all = "camelCase")]
 pub struct FileInfo {
@@ -36,7 +39,6 @@ pub fn find_unitypackage<P: AsRef<Path>>(dir: P) -> Result<HashMap<String, Vec<F
 
         let path = entry.path();
         if path.is_dir() {
-            let mut result = find_unitypackage(&path)?;
             let dir_name = path.file_name();
 
             if dir_name.is_none() {
@@ -48,6 +50,8 @@ pub fn find_unitypackage<P: AsRef<Path>>(dir: P) -> Result<HashMap<String, Vec<F
                 continue;
             }
 
+            let mut result = find_unitypackage(&path)?;
+
             for (key, value) in result.drain() {
                 let unitypackage = unitypackages
                     .entry(format!("{dir_name}/{key}"))
@@ -77,6 +81,10 @@ pub fn find_unitypackage<P: AsRef<Path>>(dir: P) -> Result<HashMap<String, Vec<F
         }
         let file_name = file_name.unwrap();
 
+        if file_name.starts_with(RESOURCE_FORK_PREFIX) {
+            continue;
+        }
+
         let absolute_path = std::path::absolute(&path).map_err(|e| e.to_string())?;
 
         let absolute_path = absolute_path.to_str();
@@ -123,6 +131,7 @@ mod tests {
         std::fs::write(format!("{dir}/test/not-unitypackage.txt"), "").unwrap();
 
         std::fs::write(format!("{dir}/__MACOSX/inside-macosx.unitypackage"), "").unwrap();
+        std::fs::write(format!("{dir}/._normal.unitypackage"), "").unwrap();
 
         let result = find_unitypackage(dir).unwrap();
 
@@ -0,0 +1,429 @@
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    path::{Path, PathBuf},
+};
+
+use loader::HashSetVersionedLoader;
+use model::{AssetDataLocation, AssetDescription, AssetTrait, OtherAsset};
+use serde::{Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::{
+    asset_storage::AssetStorage,
+    definitions::{DeadAssetEntry, IntegrityReport},
+    delete::{delete_asset, delete_asset_image},
+    json_asset_container::JsonAssetContainer,
+};
+
+const IMAGE_EXTENSIONS: [&str; 4] = ["webp", "jpg", "jpeg", "png"];
+
+/// メタデータと `data/`・`images/` の中身を突き合わせ、不整合を検出する
+///
+/// ファイルの変更は行わないため、結果をもとに個別の修復処理を呼び出す
+pub async fn check_integrity(storage: &AssetStorage) -> Result<IntegrityReport, String> {
+    let data_dir = storage.data_dir();
+
+    let used_ids = storage.get_used_ids().await;
+    let used_images = storage.get_used_image_filenames().await;
+
+    let mut report = IntegrityReport::default();
+
+    for name in list_entry_names(&data_dir.join("data"), true)? {
+        let is_used = Uuid::parse_str(&name)
+            .map(|id| used_ids.contains(&id))
+            .unwrap_or(false);
+
+        if !is_used {
+            report.orphan_data_dirs.push(name);
+        }
+    }
+
+    report
+        .dead_entries
+        .extend(find_dead_entries(&data_dir, storage.get_avatar_store()).await);
+    report
+        .dead_entries
+        .extend(find_dead_entries(&data_dir, storage.get_avatar_wearable_store()).await);
+    report
+        .dead_entries
+        .extend(find_dead_entries(&data_dir, storage.get_world_object_store()).await);
+    report
+        .dead_entries
+        .extend(find_dead_entries(&data_dir, storage.get_other_asset_store()).await);
+
+    for name in list_entry_names(&data_dir.join("images"), false)? {
+        if used_images.contains(&name) {
+            continue;
+        }
+
+        if name.starts_with("temp_") {
+            report.temp_images.push(name);
+        } else if is_image_filename(&name) {
+            report.orphan_images.push(name);
+        }
+    }
+
+    Ok(report)
+}
+
+/// どのアセットにも登録されていない `data/<dir_name>` を、その他アセットとして登録する
+///
+/// ディレクトリ名が使われていない UUID であればそのまま ID として使い、そうでなければ新しい ID に名前を変える
+pub async fn adopt_orphan_data_dir(storage: &AssetStorage, dir_name: &str) -> Result<Uuid, String> {
+    storage.ensure_writable()?;
+
+    let data_root = storage.data_dir().join("data");
+    let dir = resolve_child(&data_root, dir_name)?;
+
+    if !dir.is_dir() {
+        return Err(format!("Directory not found: {}", dir.display()));
+    }
+
+    let used_ids = storage.get_used_ids().await;
+
+    if let Ok(id) = Uuid::parse_str(dir_name) {
+        if used_ids.contains(&id) {
+            return Err(format!(
+                "Directory is already used by an asset: {}",
+                dir_name
+            ));
+        }
+    }
+
+    let description = AssetDescription {
+        name: guess_asset_name(&dir, dir_name),
+        creator: String::new(),
+        image_filename: None,
+        tags: vec![],
+        memo: None,
+        booth_item_id: None,
+        dependencies: vec![],
+        created_at: chrono::Local::now().timestamp_millis(),
+        published_at: None,
+        data_location: AssetDataLocation::Local,
+    };
+
+    let mut asset = OtherAsset::create(description, String::new());
+
+    let rename_to = match Uuid::parse_str(dir_name) {
+        Ok(id) => {
+            asset.id = id;
+            None
+        }
+        Err(_) => Some(data_root.join(asset.id.to_string())),
+    };
+
+    let id = asset.id;
+    let store = storage.get_other_asset_store();
+
+    // 失敗した場合にディレクトリを元の名前に戻せなくなることのないよう、登録してから名前を変更する
+    if let Err(e) = store.add_asset_and_save(asset).await {
+        if let Err(e) = store.delete_asset_and_save(id).await {
+            log::error!("Failed to remove the failed registration: {}", e);
+        }
+        return Err(e);
+    }
+
+    if let Some(new_dir) = rename_to {
+        if let Err(e) = tokio::fs::rename(&dir, &new_dir).await {
+            if let Err(e) = store.delete_asset_and_save(id).await {
+                log::error!("Failed to remove the registration of {}: {}", id, e);
+            }
+            return Err(format!("Failed to rename orphan data dir: {:?}", e));
+        }
+    }
+
+    Ok(id)
+}
+
+/// データのディレクトリが見つからないアセットを登録から削除する
+pub async fn remove_dead_entry(
+    storage: &AssetStorage,
+    id: Uuid,
+    use_trash_bin: bool,
+) -> Result<(), String> {
+    let data_dir = storage.data_dir();
+
+    match storage.get_data_location(id).await {
+        Some(AssetDataLocation::Local) => {}
+        Some(_) => return Err(format!("Asset does not store data locally: {}", id)),
+        None => return Err(format!("Asset not found: {}", id)),
+    }
+
+    if data_dir.join("data").join(id.to_string()).exists() {
+        return Err(format!("Data directory of asset exists: {}", id));
+    }
+
+    delete_asset(storage, id, use_trash_bin).await
+}
+
+/// どのアセットからも参照されていない画像をゴミ箱に移動する
+pub async fn trash_unreferenced_image(
+    storage: &AssetStorage,
+    filename: &str,
+) -> Result<(), String> {
+    storage.ensure_writable()?;
+
+    let data_dir = storage.data_dir();
+    let image_path = resolve_child(&data_dir.join("images"), filename)?;
+
+    if !image_path.is_file() {
+        return Err(format!("Image not found: {}", filename));
+    }
+
+    if storage.get_used_image_filenames().await.contains(filename) {
+        return Err(format!("Image is used by an asset: {}", filename));
+    }
+
+    delete_asset_image(&data_dir, filename, true).await?;
+
+    Ok(())
+}
+
+async fn find_dead_entries<T>(data_dir: &Path, store: &JsonAssetContainer<T>) -> Vec<DeadAssetEntry>
+where
+    T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + DeserializeOwned + Eq + Hash,
+{
+    store
+        .get_all()
+        .await
+        .iter()
+        .filter(|asset| asset.get_description().data_location == AssetDataLocation::Local)
+        .filter(|asset| {
+            !data_dir
+                .join("data")
+                .join(asset.get_id().to_string())
+                .is_dir()
+        })
+        .map(|asset| DeadAssetEntry {
+            id: asset.get_id(),
+            asset_type: T::asset_type(),
+            name: asset.get_description().name.clone(),
+        })
+        .collect()
+}
+
+fn list_entry_names(dir: &Path, directories: bool) -> Result<Vec<String>, String> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let read_dir = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {:?}", dir.display(), e))?;
+
+    let mut names: Vec<String> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir() == directories)
+        .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+        .collect();
+    names.sort();
+
+    Ok(names)
+}
+
+/// `name` が `root` の直下を指していることを確認する
+fn resolve_child(root: &Path, name: &str) -> Result<PathBuf, String> {
+    let path = root.join(name);
+
+    if name.is_empty() || path.parent() != Some(root) {
+        return Err(format!("Invalid entry name: {}", name));
+    }
+
+    Ok(path)
+}
+
+fn is_image_filename(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image| extension.eq_ignore_ascii_case(image))
+        })
+        .unwrap_or(false)
+}
+
+/// 中身が 1 つだけであればその名前を、そうでなければディレクトリ名をアセット名とする
+fn guess_asset_name(dir: &Path, dir_name: &str) -> String {
+    let entries: HashSet<String> = std::fs::read_dir(dir)
+        .map(|read_dir| {
+            read_dir
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    entry
+                        .path()
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if entries.len() == 1 {
+        return entries.into_iter().next().unwrap();
+    }
+
+    dir_name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use file::modify_guard::{self, FileTransferGuard};
+
+    use super::*;
+
+    async fn setup(target: &str) -> AssetStorage {
+        let from = "../../test/example_root_dir/sample1";
+
+        if std::fs::exists(target).unwrap() {
+            std::fs::remove_dir_all(target).unwrap();
+        }
+
+        modify_guard::copy_dir(
+            from.to_string(),
+            target.to_string(),
+            false,
+            FileTransferGuard::none(),
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+
+        let mut storage = AssetStorage::create(target).unwrap();
+        storage.load_all_assets_from_files().await.unwrap();
+
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_check_integrity() {
+        let target = "test/temp/integrity/check";
+        let storage = setup(target).await;
+
+        assert!(check_integrity(&storage).await.unwrap().is_empty());
+
+        let orphan_id = Uuid::new_v4();
+        std::fs::create_dir_all(format!("{target}/data/{orphan_id}/Package")).unwrap();
+        std::fs::remove_dir_all(format!(
+            "{target}/data/c155488e-53bf-4c98-92e5-e5c8f66a1667"
+        ))
+        .unwrap();
+        std::fs::write(format!("{target}/images/orphan.png"), b"").unwrap();
+        std::fs::write(format!("{target}/images/temp_image.png"), b"").unwrap();
+        std::fs::write(format!("{target}/images/notes.txt"), b"").unwrap();
+
+        let report = check_integrity(&storage).await.unwrap();
+
+        assert_eq!(report.orphan_data_dirs, vec![orphan_id.to_string()]);
+        assert_eq!(report.dead_entries.len(), 1);
+        assert_eq!(
+            report.dead_entries[0].id,
+            Uuid::from_str("c155488e-53bf-4c98-92e5-e5c8f66a1667").unwrap()
+        );
+        assert_eq!(report.orphan_images, vec!["orphan.png".to_string()]);
+        assert_eq!(report.temp_images, vec!["temp_image.png".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_adopt_orphan_data_dir() {
+        let target = "test/temp/integrity/adopt";
+        let storage = setup(target).await;
+
+        let orphan_id = Uuid::new_v4();
+        std::fs::create_dir_all(format!("{target}/data/{orphan_id}/Package")).unwrap();
+        std::fs::create_dir_all(format!("{target}/data/not-a-uuid")).unwrap();
+
+        let id = adopt_orphan_data_dir(&storage, &orphan_id.to_string())
+            .await
+            .unwrap();
+        assert_eq!(id, orphan_id);
+
+        let asset = storage.get_other_asset_store().get_asset(id).await.unwrap();
+        assert_eq!(asset.description.name, "Package");
+
+        let id = adopt_orphan_data_dir(&storage, "not-a-uuid").await.unwrap();
+        assert!(std::fs::exists(format!("{target}/data/{id}")).unwrap());
+        assert!(!std::fs::exists(format!("{target}/data/not-a-uuid")).unwrap());
+
+        assert!(check_integrity(&storage).await.unwrap().is_empty());
+        assert!(adopt_orphan_data_dir(&storage, "../images").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_dead_entry() {
+        let target = "test/temp/integrity/dead_entry";
+        let storage = setup(target).await;
+
+        let id = Uuid::from_str("c155488e-53bf-4c98-92e5-e5c8f66a1667").unwrap();
+
+        // データが存在する間は削除しない
+        assert!(remove_dead_entry(&storage, id, false).await.is_err());
+
+        std::fs::remove_dir_all(format!("{target}/data/{id}")).unwrap();
+        remove_dead_entry(&storage, id, false).await.unwrap();
+
+        assert!(
+            storage
+                .get_world_object_store()
+                .get_asset(id)
+                .await
+                .is_none()
+        );
+        assert!(check_integrity(&storage).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trash_unreferenced_image_refuses_used_image() {
+        let target = "test/temp/integrity/used_image";
+        let storage = setup(target).await;
+
+        let result =
+            trash_unreferenced_image(&storage, "843e178b-6865-4834-a51c-ac5f9eccbdbf.jpg").await;
+
+        assert!(result.is_err());
+        assert!(
+            std::fs::exists(format!(
+                "{target}/images/843e178b-6865-4834-a51c-ac5f9eccbdbf.jpg"
+            ))
+            .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_storage_is_not_modified() {
+        let target = "test/temp/integrity/read_only";
+        let mut storage = setup(target).await;
+
+        // 他のインスタンスがロックを保持しているため、読み取り専用で開かれる
+        let mut other = AssetStorage::create(target).unwrap();
+        other.acquire_lock(Uuid::new_v4(), false).unwrap();
+        assert!(
+            storage
+                .acquire_lock(Uuid::new_v4(), false)
+                .unwrap()
+                .read_only
+        );
+
+        let orphan_id = Uuid::new_v4();
+        std::fs::create_dir_all(format!("{target}/data/{orphan_id}")).unwrap();
+        std::fs::write(format!("{target}/images/orphan.jpg"), b"").unwrap();
+
+        assert!(
+            adopt_orphan_data_dir(&storage, &orphan_id.to_string())
+                .await
+                .is_err()
+        );
+        assert!(
+            trash_unreferenced_image(&storage, "orphan.jpg")
+                .await
+                .is_err()
+        );
+
+        assert!(!storage.get_used_ids().await.contains(&orphan_id));
+        assert!(std::fs::exists(format!("{target}/images/orphan.jpg")).unwrap());
+    }
+}
@@ -3,10 +3,12 @@ use std::{
     fs::File,
     hash::Hash,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
 };
 
 use loader::HashSetVersionedLoader;
-use model::AssetTrait;
+use model::{AssetDataLocation, AssetTrait};
 use serde::{Serialize, de::DeserializeOwned};
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -20,6 +22,60 @@ pub struct JsonAssetContainer<
 > {
     data_dir: PathBuf,
     assets: Mutex<HashSet<T>>,
+    /// 最後に読み込み・保存した時点の内容。外部で変更された場合のマージに使う
+    base: Mutex<HashSet<T>>,
+    fingerprint: Mutex<Option<FileFingerprint>>,
+    read_only: AtomicBool,
+}
+
+/// 外部からの変更を検知するための、ファイルの状態
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileFingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: String,
+}
+
+impl FileFingerprint {
+    fn read(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let metadata = path
+            .metadata()
+            .map_err(|e| format!("Failed to read metadata of {}: {}", path.display(), e))?;
+        let hash = file::calculate_file_hash(path)
+            .map_err(|e| format!("Failed to hash {}: {}", path.display(), e))?;
+
+        Ok(Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            hash,
+        }))
+    }
+
+    /// 更新日時とサイズが一致すれば変更なしとみなし、異なる場合はハッシュで確認する
+    ///
+    /// ネットワークドライブでは内容が同じでも更新日時がずれることがあるため、ハッシュの一致を優先する
+    fn matches(&self, path: &Path) -> Result<bool, String> {
+        let metadata = path
+            .metadata()
+            .map_err(|e| format!("Failed to read metadata of {}: {}", path.display(), e))?;
+
+        if metadata.len() != self.len {
+            return Ok(false);
+        }
+
+        if self.modified.is_some() && metadata.modified().ok() == self.modified {
+            return Ok(true);
+        }
+
+        let hash = file::calculate_file_hash(path)
+            .map_err(|e| format!("Failed to hash {}: {}", path.display(), e))?;
+
+        Ok(hash == self.hash)
+    }
 }
 
 impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + DeserializeOwned + Eq + Hash>
@@ -38,13 +94,61 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
         Ok(Self {
             data_dir: data_dir.to_path_buf(),
             assets: Mutex::new(HashSet::new()),
+            base: Mutex::new(HashSet::new()),
+            fingerprint: Mutex::new(None),
+            read_only: AtomicBool::new(false),
         })
     }
 
+    /// 読み取り専用の場合、保存しようとするとエラーを返す
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::SeqCst);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    /// 読み取り専用の場合はエラーを返す。メモリ上のアセットや画像を変更する前に呼び出す
+    fn ensure_writable(&self) -> Result<(), String> {
+        if self.is_read_only() {
+            return Err("Library is opened in read-only mode".into());
+        }
+
+        Ok(())
+    }
+
     pub async fn get_all(&self) -> HashSet<T> {
         self.assets.lock().await.clone()
     }
 
+    /// エクスポート用のメタデータのファイル名と内容を返す
+    ///
+    /// 外部のフォルダを参照しているアセットは、そのファイルをデータディレクトリ内に含めてエクスポートするため、
+    /// データディレクトリ内にあるものとして書き出す
+    pub async fn export_metadata(&self) -> Result<(String, Vec<u8>), String> {
+        let assets = self
+            .assets
+            .lock()
+            .await
+            .iter()
+            .cloned()
+            .map(|mut asset| {
+                let description = asset.get_description_as_mut();
+                if matches!(description.data_location, AssetDataLocation::Linked { .. }) {
+                    description.data_location = AssetDataLocation::Local;
+                }
+                asset
+            })
+            .collect::<HashSet<_>>();
+
+        let data = T::VersionedType::try_from(assets)?;
+        let content =
+            serde_json::to_vec(&data).map_err(|e| format!("Failed to serialize file: {}", e))?;
+
+        Ok((T::filename(), content))
+    }
+
     pub async fn get_asset(&self, id: Uuid) -> Option<T> {
         self.assets
             .lock()
@@ -55,33 +159,51 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
     }
 
     pub async fn load(&self) -> Result<(), String> {
-        let filename = T::filename();
-
-        let mut path = self.data_dir.clone();
-        path.push("metadata");
-        path.push(&filename);
+        let path = self.file_path();
 
         if !path.exists() {
             return Ok(());
         }
 
-        let file = File::open(path.clone())
-            .map_err(|e| format!("Failed to open file at {}: {}", path.display(), e))?;
+        let data = read_assets::<T>(&path)?;
 
         {
             let mut assets = self.assets.lock().await;
-            let result: T::VersionedType = serde_json::from_reader(file)
-                .map_err(|e| format!("Failed to deserialize: {:?} ( file: {} )", e, filename))?;
-
-            let data: HashSet<T> = result.try_into()?;
 
+            *self.base.lock().await = data.clone();
+            *self.fingerprint.lock().await = FileFingerprint::read(&path)?;
             *assets = data;
         }
 
         Ok(())
     }
 
+    /// 他のインスタンスや同期ツールによってファイルが変更されていれば、読み込み直してマージする
+    ///
+    /// 変更されていなければ `None` を、変更されていれば両方で変更されていたアセットの ID を返す
+    pub async fn reload_if_modified(&self) -> Result<Option<Vec<Uuid>>, String> {
+        let path = self.file_path();
+        let mut assets = self.assets.lock().await;
+
+        if !self.is_modified_externally(&path).await? {
+            return Ok(None);
+        }
+
+        let theirs = read_assets::<T>(&path)?;
+        let mut base = self.base.lock().await;
+
+        let (merged, conflicts) = merge_assets(&base, &assets, &theirs);
+
+        *assets = merged;
+        *base = theirs;
+        *self.fingerprint.lock().await = FileFingerprint::read(&path)?;
+
+        Ok(Some(conflicts))
+    }
+
     pub async fn add_asset_and_save(&self, asset: T) -> Result<(), String> {
+        self.ensure_writable()?;
+
         {
             let mut assets = self.assets.lock().await;
             assets.insert(asset.clone());
@@ -95,6 +217,8 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
         mut asset: T,
         use_trash_bin: bool,
     ) -> Result<(), String> {
+        self.ensure_writable()?;
+
         {
             let mut assets = self.assets.lock().await;
             let old_asset = assets
@@ -107,8 +231,10 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
             }
             let old_asset = old_asset.unwrap();
 
-            // update の時は created_at を更新しない
+            // update の時は created_at とデータの保存場所を更新しない
             asset.get_description_as_mut().created_at = old_asset.get_description().created_at;
+            asset.get_description_as_mut().data_location =
+                old_asset.get_description().data_location.clone();
 
             if old_asset.get_description().image_filename != asset.get_description().image_filename
             {
@@ -139,6 +265,8 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
     }
 
     pub async fn delete_asset_and_save(&self, id: Uuid) -> Result<bool, String> {
+        self.ensure_writable()?;
+
         {
             let mut assets = self.assets.lock().await;
             let asset = assets.iter().find(|asset| asset.get_id() == id).cloned();
@@ -157,6 +285,8 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
     }
 
     pub async fn delete_dependency(&self, id: Uuid) -> Result<bool, String> {
+        self.ensure_writable()?;
+
         {
             let mut assets = self.assets.lock().await;
             let cloned_assets = assets.clone();
@@ -182,7 +312,37 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
         Ok(true)
     }
 
+    /// アセットのデータの保存場所を変更する。対象のアセットが存在しない場合は false を返す
+    pub async fn set_data_location(
+        &self,
+        id: Uuid,
+        location: AssetDataLocation,
+    ) -> Result<bool, String> {
+        self.ensure_writable()?;
+
+        {
+            let mut assets = self.assets.lock().await;
+            let asset = assets.iter().find(|asset| asset.get_id() == id).cloned();
+
+            let Some(asset) = asset else {
+                return Ok(false);
+            };
+
+            let mut new_asset = asset.clone();
+            new_asset.get_description_as_mut().data_location = location;
+
+            assets.remove(&asset);
+            assets.insert(new_asset);
+        }
+
+        self.save().await?;
+
+        Ok(true)
+    }
+
     pub async fn replace_thumbnails(&self, map: &HashMap<String, String>) -> Result<(), String> {
+        self.ensure_writable()?;
+
         {
             let mut assets = self.assets.lock().await;
             let cloned_assets = assets.clone();
@@ -215,6 +375,8 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
         other: &JsonAssetContainer<T>,
         reassign_map: &HashMap<Uuid, Uuid>,
     ) -> Result<(), String> {
+        self.ensure_writable()?;
+
         {
             let mut assets = self.assets.lock().await;
             let other_assets = other.assets.lock().await.clone();
@@ -232,15 +394,40 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
     }
 
     async fn save(&self) -> Result<(), String> {
-        let path = self.data_dir.join("metadata").join(T::filename());
+        self.ensure_writable()?;
 
-        let file = File::create(&path)
-            .map_err(|e| format!("Failed to create file at {}: {}", path.display(), e))?;
+        let path = self.file_path();
+        let mut assets = self.assets.lock().await;
 
-        let data = {
-            let assets = self.assets.lock().await;
-            T::VersionedType::try_from(assets.clone())?
-        };
+        // 他のインスタンスが保存した内容を上書きしないよう、変更されていればマージしてから保存する
+        if self.is_modified_externally(&path).await? {
+            log::warn!(
+                "Metadata was modified externally, merging before save: {}",
+                path.display()
+            );
+
+            let theirs = read_assets::<T>(&path)?;
+            let base = self.base.lock().await.clone();
+
+            let (merged, conflicts) = merge_assets(&base, &assets, &theirs);
+
+            if !conflicts.is_empty() {
+                log::warn!(
+                    "Overwriting externally modified assets with local changes: {:?}",
+                    conflicts
+                );
+            }
+
+            *assets = merged;
+        }
+
+        let data = T::VersionedType::try_from(assets.clone())?;
+
+        // 書き込み途中で中断されてもファイルが壊れないよう、一時ファイルに書いてから置き換える
+        let temp_path = path.with_extension("json.tmp");
+
+        let file = File::create(&temp_path)
+            .map_err(|e| format!("Failed to create file at {}: {}", temp_path.display(), e))?;
 
         let result = serde_json::to_writer(file, &data);
 
@@ -248,8 +435,102 @@ impl<T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + Deserialize
             return Err(format!("Failed to serialize file: {}", e));
         }
 
+        std::fs::rename(&temp_path, &path)
+            .map_err(|e| format!("Failed to replace file at {}: {}", path.display(), e))?;
+
+        *self.base.lock().await = assets.clone();
+        *self.fingerprint.lock().await = FileFingerprint::read(&path)?;
+
         Ok(())
     }
+
+    fn file_path(&self) -> PathBuf {
+        self.data_dir.join("metadata").join(T::filename())
+    }
+
+    async fn is_modified_externally(&self, path: &Path) -> Result<bool, String> {
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        match &*self.fingerprint.lock().await {
+            Some(fingerprint) => Ok(!fingerprint.matches(path)?),
+            // 読み込んだ時点では存在しなかったファイルが作られている
+            None => Ok(true),
+        }
+    }
+}
+
+fn read_assets<T>(path: &Path) -> Result<HashSet<T>, String>
+where
+    T: AssetTrait + HashSetVersionedLoader<T> + Clone + Serialize + DeserializeOwned + Eq + Hash,
+{
+    let file = File::open(path)
+        .map_err(|e| format!("Failed to open file at {}: {}", path.display(), e))?;
+
+    let result: T::VersionedType = serde_json::from_reader(file)
+        .map_err(|e| format!("Failed to deserialize: {:?} ( file: {} )", e, T::filename()))?;
+
+    result.try_into()
+}
+
+/// `base` から `ours` への変更を `theirs` に適用する
+///
+/// 同じアセットが両方で異なる内容に変更されている場合は `ours` を優先し、その ID を競合として返す
+fn merge_assets<T>(
+    base: &HashSet<T>,
+    ours: &HashSet<T>,
+    theirs: &HashSet<T>,
+) -> (HashSet<T>, Vec<Uuid>)
+where
+    T: AssetTrait + Clone + Eq + Hash,
+{
+    let base_by_id: HashMap<Uuid, &T> = base.iter().map(|asset| (asset.get_id(), asset)).collect();
+    let ours_by_id: HashMap<Uuid, &T> = ours.iter().map(|asset| (asset.get_id(), asset)).collect();
+    let theirs_by_id: HashMap<Uuid, &T> =
+        theirs.iter().map(|asset| (asset.get_id(), asset)).collect();
+
+    let mut merged: HashMap<Uuid, T> = theirs_by_id
+        .iter()
+        .map(|(id, asset)| (*id, (*asset).clone()))
+        .collect();
+    let mut conflicts = vec![];
+
+    // 追加・変更したアセット
+    for (id, asset) in &ours_by_id {
+        let base_asset = base_by_id.get(id);
+
+        if base_asset == Some(asset) {
+            continue;
+        }
+
+        let their_asset = theirs_by_id.get(id);
+
+        if their_asset != base_asset && their_asset != Some(asset) {
+            conflicts.push(*id);
+        }
+
+        merged.insert(*id, (*asset).clone());
+    }
+
+    // 削除したアセット
+    for (id, base_asset) in &base_by_id {
+        if ours_by_id.contains_key(id) {
+            continue;
+        }
+
+        if let Some(their_asset) = theirs_by_id.get(id) {
+            if their_asset != base_asset {
+                conflicts.push(*id);
+            }
+        }
+
+        merged.remove(id);
+    }
+
+    conflicts.sort();
+
+    (merged.into_values().collect(), conflicts)
 }
 
 #[cfg(test)]
@@ -303,6 +584,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1743606000000,
                 published_at: Some(1735657200000),
+                data_location: AssetDataLocation::Local,
             },
         };
 
@@ -322,6 +604,7 @@ mod tests {
                 dependencies: vec![new_avatar_dependency_id.clone()],
                 created_at: 1234560000000,
                 published_at: Some(1234560000000),
+                data_location: AssetDataLocation::Local,
             },
         };
 
@@ -375,4 +658,123 @@ mod tests {
         let fetched_new_avatar = store.get_asset(new_avatar_uuid).await.unwrap();
         assert_eq!(fetched_new_avatar.description.dependencies.len(), 0);
     }
+
+    fn create_avatar(name: &str) -> Avatar {
+        Avatar::create(AssetDescription {
+            name: name.into(),
+            creator: "Creator".into(),
+            image_filename: None,
+            tags: vec![],
+            memo: None,
+            booth_item_id: None,
+            dependencies: vec![],
+            created_at: 0,
+            published_at: None,
+            data_location: AssetDataLocation::Local,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_merge_external_modification_on_save() {
+        let data_dir = "test/temp/json_store/external_modification";
+
+        if std::fs::exists(data_dir).unwrap() {
+            std::fs::remove_dir_all(data_dir).unwrap();
+        }
+
+        let ours: JsonAssetContainer<Avatar> = JsonAssetContainer::create(data_dir).unwrap();
+        let theirs: JsonAssetContainer<Avatar> = JsonAssetContainer::create(data_dir).unwrap();
+
+        let shared = create_avatar("Shared");
+        ours.add_asset_and_save(shared.clone()).await.unwrap();
+        theirs.load().await.unwrap();
+
+        // 別のインスタンスが追加・削除した内容を、保存時に取り込む
+        let their_avatar = create_avatar("Theirs");
+        theirs
+            .add_asset_and_save(their_avatar.clone())
+            .await
+            .unwrap();
+        theirs.delete_asset_and_save(shared.id).await.unwrap();
+
+        let our_avatar = create_avatar("Ours");
+        ours.add_asset_and_save(our_avatar.clone()).await.unwrap();
+
+        let all_assets = ours.get_all().await;
+        assert_eq!(all_assets.len(), 2);
+        assert!(all_assets.contains(&their_avatar));
+        assert!(all_assets.contains(&our_avatar));
+
+        assert_eq!(theirs.reload_if_modified().await.unwrap(), Some(vec![]));
+        assert_eq!(theirs.get_all().await, all_assets);
+        assert_eq!(theirs.reload_if_modified().await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_merge_assets_conflict() {
+        let shared = create_avatar("Shared");
+        let untouched = create_avatar("Untouched");
+
+        let mut ours_shared = shared.clone();
+        ours_shared.description.name = "Ours".into();
+        let mut theirs_shared = shared.clone();
+        theirs_shared.description.name = "Theirs".into();
+        let mut theirs_untouched = untouched.clone();
+        theirs_untouched.description.memo = Some("Edited by sync".into());
+
+        let base = HashSet::from([shared.clone(), untouched.clone()]);
+        let ours = HashSet::from([ours_shared.clone(), untouched.clone()]);
+        let theirs = HashSet::from([theirs_shared, theirs_untouched.clone()]);
+
+        let (merged, conflicts) = merge_assets(&base, &ours, &theirs);
+
+        // 片方だけの変更は取り込み、両方で変更されたものは手元の変更を優先する
+        assert_eq!(conflicts, vec![shared.id]);
+        assert_eq!(merged, HashSet::from([ours_shared, theirs_untouched]));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_container() {
+        let data_dir = "test/temp/json_store/read_only";
+
+        if std::fs::exists(data_dir).unwrap() {
+            std::fs::remove_dir_all(data_dir).unwrap();
+        }
+
+        let store: JsonAssetContainer<Avatar> = JsonAssetContainer::create(data_dir).unwrap();
+        store.set_read_only(true);
+
+        assert!(
+            store
+                .add_asset_and_save(create_avatar("Avatar"))
+                .await
+                .is_err()
+        );
+        assert!(!std::fs::exists(format!("{data_dir}/metadata/avatars.json")).unwrap());
+        assert!(store.get_all().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_container_keeps_assets_in_memory() {
+        let data_dir = "test/temp/json_store/read_only_in_memory";
+
+        if std::fs::exists(data_dir).unwrap() {
+            std::fs::remove_dir_all(data_dir).unwrap();
+        }
+
+        let store: JsonAssetContainer<Avatar> = JsonAssetContainer::create(data_dir).unwrap();
+        let avatar = create_avatar("Avatar");
+        store.add_asset_and_save(avatar.clone()).await.unwrap();
+
+        store.set_read_only(true);
+
+        let mut updated = avatar.clone();
+        updated.description.name = "Updated".into();
+
+        assert!(store.update_asset_and_save(updated, false).await.is_err());
+        assert!(store.delete_asset_and_save(avatar.id).await.is_err());
+
+        // 保存できなかった変更がメモリ上に残らない
+        assert_eq!(store.get_asset(avatar.id).await, Some(avatar));
+    }
 }
@@ -4,5 +4,8 @@ pub mod asset_storage;
 pub mod definitions;
 pub mod delete;
 pub mod find;
+pub mod integrity;
 pub mod json_asset_container;
+pub mod lock;
+pub mod migration;
 pub mod search;
@@ -0,0 +1,303 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// データフォルダに作成する、書き込み中であることを示すファイル
+pub const LOCK_FILENAME: &str = ".konoasset.lock";
+
+/// ロックファイルの更新日時からこの時間以上経過したロックは、終了し損ねたインスタンスのものとみなす
+pub const LOCK_STALE_MS: u64 = 5 * 60 * 1000;
+
+/// ロックを保持している間に、更新日時を書き換える間隔
+pub const LOCK_REFRESH_INTERVAL_MS: u64 = 60 * 1000;
+
+/// ロックファイルに記録する、ロックを保持しているインスタンスの情報
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LockOwner {
+    pub instance_id: Uuid,
+    pub hostname: String,
+    pub pid: u32,
+    pub acquired_at: i64,
+    pub updated_at: i64,
+}
+
+impl LockOwner {
+    fn new(instance_id: Uuid) -> Self {
+        let now = chrono::Local::now().timestamp_millis();
+
+        Self {
+            instance_id,
+            hostname: hostname(),
+            pid: std::process::id(),
+            acquired_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// 共有フォルダ上のライブラリを複数人で同時に編集しないための、アドバイザリロック
+///
+/// ロックを尊重しないプロセスからの書き込みは防げないため、保存時の変更検知と併用する
+pub struct LibraryLock {
+    path: PathBuf,
+    owner: LockOwner,
+}
+
+pub enum LockResult {
+    Acquired(LibraryLock),
+    /// 他のインスタンスがロックを保持している
+    HeldByOther(LockOwner),
+}
+
+impl LibraryLock {
+    /// ロックの取得を試みる。`force` が `true` の場合は、他のインスタンスのロックを奪う
+    pub fn acquire<P: AsRef<Path>>(
+        data_dir: P,
+        instance_id: Uuid,
+        force: bool,
+    ) -> Result<LockResult, String> {
+        let path = data_dir.as_ref().join(LOCK_FILENAME);
+
+        if let Some(current) = read_lock_file(&path)? {
+            let is_ours = current.instance_id == instance_id;
+
+            if !is_ours && !is_stale(&path)? && !force {
+                return Ok(LockResult::HeldByOther(current));
+            }
+
+            if !is_ours {
+                log::warn!(
+                    "Taking over library lock held by {} (pid = {})",
+                    current.hostname,
+                    current.pid
+                );
+            }
+
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove old lock file: {:?}", e))?;
+        }
+
+        let owner = LockOwner::new(instance_id);
+
+        // 書き込み途中のロックファイルを他のインスタンスが読まないよう、書き込み済みのファイルを配置する。
+        // ハードリンクは既に存在する場合に失敗するため、同時に取得しようとした場合も片方だけが成功する
+        let temp_path = write_temp_lock_file(&path, &owner)?;
+        let result = std::fs::hard_link(&temp_path, &path);
+
+        if let Err(e) = std::fs::remove_file(&temp_path) {
+            log::warn!("Failed to remove temporary lock file: {:?}", e);
+        }
+
+        match result {
+            Ok(()) => Ok(LockResult::Acquired(Self { path, owner })),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                match read_lock_file(&path)? {
+                    Some(current) => Ok(LockResult::HeldByOther(current)),
+                    None => Err("Lock file was modified while acquiring".into()),
+                }
+            }
+            Err(e) => Err(format!("Failed to create lock file: {:?}", e)),
+        }
+    }
+
+    pub fn owner(&self) -> &LockOwner {
+        &self.owner
+    }
+
+    /// ロックの更新日時を書き換える。他のインスタンスに奪われていた場合は `false` を返す
+    pub fn refresh(&mut self) -> Result<bool, String> {
+        match read_lock_file(&self.path)? {
+            Some(current) if current.instance_id == self.owner.instance_id => {}
+            _ => return Ok(false),
+        }
+
+        self.owner.updated_at = chrono::Local::now().timestamp_millis();
+
+        // 置き換えることで、書き込み途中の内容を読まれないようにしつつ更新日時を新しくする
+        let temp_path = write_temp_lock_file(&self.path, &self.owner)?;
+        std::fs::rename(&temp_path, &self.path)
+            .map_err(|e| format!("Failed to replace lock file: {:?}", e))?;
+
+        Ok(true)
+    }
+}
+
+impl Drop for LibraryLock {
+    fn drop(&mut self) {
+        // 他のインスタンスに奪われたロックは削除しない
+        match read_lock_file(&self.path) {
+            Ok(Some(current)) if current.instance_id == self.owner.instance_id => {}
+            _ => return,
+        }
+
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::warn!("Failed to release library lock: {:?}", e);
+        }
+    }
+}
+
+/// ロックファイルを読み取る。壊れている場合はロックされていないものとして扱う
+pub fn read_lock_file<P: AsRef<Path>>(path: P) -> Result<Option<LockOwner>, String> {
+    let path = path.as_ref();
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read lock file: {:?}", e))?;
+
+    match serde_json::from_str(&content) {
+        Ok(owner) => Ok(Some(owner)),
+        Err(e) => {
+            log::warn!("Ignoring broken lock file {}: {:?}", path.display(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// ロックファイルの更新日時をもとに、ロックが放置されているかを判定する
+///
+/// 他のマシンの時計はずれている可能性があるため、記録された日時ではなくファイルシステムの更新日時を使う
+fn is_stale(path: &Path) -> Result<bool, String> {
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| format!("Failed to read modified time of lock file: {:?}", e))?;
+
+    // 更新日時が未来の場合は、更新されたばかりとみなす
+    let elapsed = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+
+    Ok(elapsed > Duration::from_millis(LOCK_STALE_MS))
+}
+
+fn write_temp_lock_file(path: &Path, owner: &LockOwner) -> Result<PathBuf, String> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(".{}.tmp", owner.instance_id));
+    let temp_path = path.with_file_name(temp_name);
+
+    let serialized = serde_json::to_string(owner)
+        .map_err(|e| format!("Failed to serialize lock owner: {:?}", e))?;
+    std::fs::write(&temp_path, serialized)
+        .map_err(|e| format!("Failed to write lock file: {:?}", e))?;
+
+    Ok(temp_path)
+}
+
+fn hostname() -> String {
+    gethostname::gethostname().to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(dir: &str) -> PathBuf {
+        let dir = PathBuf::from(dir);
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn test_library_lock() {
+        let dir = setup("test/temp/library_lock/acquire");
+
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+
+        let LockResult::Acquired(mut lock) = LibraryLock::acquire(&dir, first, false).unwrap()
+        else {
+            panic!("First instance should acquire the lock");
+        };
+
+        match LibraryLock::acquire(&dir, second, false).unwrap() {
+            LockResult::HeldByOther(owner) => assert_eq!(owner.instance_id, first),
+            LockResult::Acquired(_) => panic!("Second instance should not acquire the lock"),
+        }
+
+        assert!(lock.refresh().unwrap());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        drop(lock);
+        assert!(!dir.join(LOCK_FILENAME).exists());
+
+        assert!(matches!(
+            LibraryLock::acquire(&dir, second, false).unwrap(),
+            LockResult::Acquired(_)
+        ));
+    }
+
+    #[test]
+    fn test_take_over_stale_lock() {
+        let dir = setup("test/temp/library_lock/stale");
+
+        // 記録された日時ではなく、ロックファイルの更新日時で判定する
+        let stale = LockOwner::new(Uuid::new_v4());
+        std::fs::write(
+            dir.join(LOCK_FILENAME),
+            serde_json::to_string(&stale).unwrap(),
+        )
+        .unwrap();
+
+        let other = Uuid::new_v4();
+        assert!(matches!(
+            LibraryLock::acquire(&dir, other, false).unwrap(),
+            LockResult::HeldByOther(_)
+        ));
+
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join(LOCK_FILENAME))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_millis(LOCK_STALE_MS + 1000))
+            .unwrap();
+
+        let instance_id = Uuid::new_v4();
+        let LockResult::Acquired(lock) = LibraryLock::acquire(&dir, instance_id, false).unwrap()
+        else {
+            panic!("Stale lock should be taken over");
+        };
+
+        assert_eq!(lock.owner().instance_id, instance_id);
+    }
+
+    #[test]
+    fn test_refresh_lock_taken_over() {
+        let dir = setup("test/temp/library_lock/taken_over");
+
+        let LockResult::Acquired(mut lock) =
+            LibraryLock::acquire(&dir, Uuid::new_v4(), false).unwrap()
+        else {
+            panic!("Lock should be acquired");
+        };
+
+        let other = Uuid::new_v4();
+        let LockResult::Acquired(_other_lock) = LibraryLock::acquire(&dir, other, true).unwrap()
+        else {
+            panic!("Forced acquisition should succeed");
+        };
+
+        assert!(!lock.refresh().unwrap());
+
+        // 奪われたロックを解放しても、他のインスタンスのロックファイルは残る
+        drop(lock);
+        assert_eq!(
+            read_lock_file(dir.join(LOCK_FILENAME))
+                .unwrap()
+                .unwrap()
+                .instance_id,
+            other
+        );
+    }
+}
@@ -0,0 +1,578 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use file::modify_guard::{self, FileTransferGuard};
+use serde::{Deserialize, Serialize};
+
+/// 移行先に作成する、進捗を記録するためのディレクトリ
+pub const MIGRATION_STATE_DIR: &str = ".migration";
+
+const STATE_FILENAME: &str = "state.json";
+const PROGRESS_FILENAME: &str = "completed.log";
+
+/// 移行対象となるデータフォルダ内のディレクトリ
+pub(crate) const MIGRATION_TARGET_DIRS: [&str; 3] = ["metadata", "data", "images"];
+
+/// コピーしたファイルの検証方法
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum MigrationVerification {
+    /// ファイルサイズのみを比較する
+    #[default]
+    Size,
+    /// ファイルサイズに加えて SHA-256 ハッシュを比較する
+    Hash,
+}
+
+/// 中断されたまま残っている移行の情報
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingMigration {
+    /// 移行元のデータフォルダ
+    pub source: PathBuf,
+    pub started_at: i64,
+    /// コピーが完了しているファイルの数
+    pub completed_files: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct MigrationState {
+    source: PathBuf,
+    started_at: i64,
+    /// コピーせずに移行先へ移動したディレクトリ
+    #[serde(default)]
+    renamed_dirs: Vec<String>,
+}
+
+/// 移行の進捗を移行先に記録し、クラッシュやキャンセルの後に再開できるようにする
+///
+/// コピーが完了したファイルは 1 行ずつ追記するため、途中で中断されても記録済みの分は失われない
+pub(crate) struct MigrationCheckpoint {
+    state_dir: PathBuf,
+    state: MigrationState,
+    completed: HashSet<String>,
+    progress_file: File,
+}
+
+impl MigrationCheckpoint {
+    pub fn create<P, Q>(dest: P, source: Q) -> Result<Self, String>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let state_dir = dest.as_ref().join(MIGRATION_STATE_DIR);
+
+        fs::create_dir_all(&state_dir)
+            .map_err(|e| format!("Failed to create migration state dir: {:?}", e))?;
+
+        let state = MigrationState {
+            source: source.as_ref().to_path_buf(),
+            started_at: chrono::Local::now().timestamp_millis(),
+            renamed_dirs: vec![],
+        };
+
+        write_state(&state_dir, &state)?;
+
+        let progress_file = File::create(state_dir.join(PROGRESS_FILENAME))
+            .map_err(|e| format!("Failed to create migration progress file: {:?}", e))?;
+
+        Ok(Self {
+            state_dir,
+            state,
+            completed: HashSet::new(),
+            progress_file,
+        })
+    }
+
+    pub fn open<P: AsRef<Path>>(dest: P) -> Result<Option<Self>, String> {
+        let state_dir = dest.as_ref().join(MIGRATION_STATE_DIR);
+
+        let Some(state) = read_state(&state_dir)? else {
+            return Ok(None);
+        };
+
+        let completed = read_completed(&state_dir)?;
+
+        let progress_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(state_dir.join(PROGRESS_FILENAME))
+            .map_err(|e| format!("Failed to open migration progress file: {:?}", e))?;
+
+        Ok(Some(Self {
+            state_dir,
+            state,
+            completed,
+            progress_file,
+        }))
+    }
+
+    pub fn source(&self) -> &Path {
+        &self.state.source
+    }
+
+    pub fn is_completed(&self, relative_path: &str) -> bool {
+        self.completed.contains(relative_path)
+    }
+
+    pub fn mark_as_completed(&mut self, relative_path: &str) -> Result<(), String> {
+        // ファイル名に改行が含まれていても 1 行に収まるよう、JSON 文字列として記録する
+        let line = serde_json::to_string(relative_path)
+            .map_err(|e| format!("Failed to serialize migration progress: {:?}", e))?;
+
+        writeln!(self.progress_file, "{}", line)
+            .map_err(|e| format!("Failed to write migration progress: {:?}", e))?;
+
+        self.completed.insert(relative_path.to_string());
+
+        Ok(())
+    }
+
+    pub fn renamed_dirs(&self) -> &[String] {
+        &self.state.renamed_dirs
+    }
+
+    fn add_renamed_dir(&mut self, dir: &str) -> Result<(), String> {
+        self.state.renamed_dirs.push(dir.to_string());
+        write_state(&self.state_dir, &self.state)
+    }
+
+    fn remove_renamed_dir(&mut self, dir: &str) -> Result<(), String> {
+        self.state.renamed_dirs.retain(|renamed| renamed != dir);
+        write_state(&self.state_dir, &self.state)
+    }
+
+    /// 移行が完了したため、進捗の記録を削除する
+    pub fn finish(self) -> Result<(), String> {
+        let state_dir = self.state_dir.clone();
+        drop(self);
+
+        fs::remove_dir_all(&state_dir)
+            .map_err(|e| format!("Failed to remove migration state dir: {:?}", e))
+    }
+}
+
+/// 移行先に中断された移行が残っているかを確認する
+pub fn find_pending_migration<P: AsRef<Path>>(dest: P) -> Result<Option<PendingMigration>, String> {
+    let state_dir = dest.as_ref().join(MIGRATION_STATE_DIR);
+
+    let Some(state) = read_state(&state_dir)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(PendingMigration {
+        source: state.source,
+        started_at: state.started_at,
+        completed_files: read_completed(&state_dir)?.len(),
+    }))
+}
+
+fn read_state(state_dir: &Path) -> Result<Option<MigrationState>, String> {
+    let state_path = state_dir.join(STATE_FILENAME);
+
+    if !state_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&state_path)
+        .map_err(|e| format!("Failed to read migration state: {:?}", e))?;
+    let state = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse migration state: {:?}", e))?;
+
+    Ok(Some(state))
+}
+
+fn write_state(state_dir: &Path, state: &MigrationState) -> Result<(), String> {
+    let serialized = serde_json::to_string(state)
+        .map_err(|e| format!("Failed to serialize migration state: {:?}", e))?;
+
+    // 書き込み途中でクラッシュしても移動済みのディレクトリの記録が失われないよう、置き換えて保存する
+    let state_path = state_dir.join(STATE_FILENAME);
+    let temp_path = state_path.with_extension("json.tmp");
+
+    fs::write(&temp_path, serialized)
+        .map_err(|e| format!("Failed to write migration state: {:?}", e))?;
+    fs::rename(&temp_path, &state_path)
+        .map_err(|e| format!("Failed to replace migration state: {:?}", e))
+}
+
+fn read_completed(state_dir: &Path) -> Result<HashSet<String>, String> {
+    let progress_path = state_dir.join(PROGRESS_FILENAME);
+
+    if !progress_path.exists() {
+        return Ok(HashSet::new());
+    }
+
+    let file = File::open(&progress_path)
+        .map_err(|e| format!("Failed to open migration progress file: {:?}", e))?;
+
+    let mut completed = HashSet::new();
+
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Failed to read migration progress: {:?}", e))?;
+
+        // 書き込み途中でクラッシュした行は読み飛ばし、そのファイルはコピーし直す
+        if let Ok(path) = serde_json::from_str::<String>(&line) {
+            completed.insert(path);
+        }
+    }
+
+    Ok(completed)
+}
+
+/// 同じボリューム内であれば、コピーせずにディレクトリごと移動する
+///
+/// 移行先に既に存在するディレクトリや、別のボリュームのため移動できなかったディレクトリはそのまま残す。
+/// 移動したディレクトリは中断後の再開時にも分かるよう、移動する前に `checkpoint` に記録する
+pub(crate) async fn rename_dirs_if_possible(
+    src_root: &Path,
+    dest_root: &Path,
+    dirs: &[&str],
+    checkpoint: &mut MigrationCheckpoint,
+) -> Result<(), String> {
+    for dir in dirs {
+        let src = src_root.join(dir);
+        let dest = dest_root.join(dir);
+
+        if !src.exists() || dest.exists() {
+            continue;
+        }
+
+        checkpoint.add_renamed_dir(dir)?;
+
+        if let Err(e) = tokio::fs::rename(&src, &dest).await {
+            // 別のボリュームであれば残りのディレクトリも移動できないため、すべてコピーする
+            log::info!(
+                "Failed to rename dir, falling back to copy: {} ({:?})",
+                src.display(),
+                e
+            );
+            checkpoint.remove_renamed_dir(dir)?;
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// 中断された移行を再開する前に、移動済みのディレクトリの状態を確認する
+///
+/// 移動済みのディレクトリが移行元にも存在する場合 (中断後に移行元で新しく作成された場合など)、
+/// 移行元の内容で移動済みのデータを上書きしないよう再開を中止する
+pub(crate) fn check_renamed_dirs(
+    src_root: &Path,
+    dest_root: &Path,
+    checkpoint: &mut MigrationCheckpoint,
+) -> Result<(), String> {
+    for dir in checkpoint.renamed_dirs().to_vec() {
+        if !dest_root.join(&dir).exists() {
+            // 記録した直後に中断され、移動されなかった
+            checkpoint.remove_renamed_dir(&dir)?;
+            continue;
+        }
+
+        if src_root.join(&dir).exists() {
+            let msg = format!(
+                "{} was already moved to {}, but it also exists in {}",
+                dir,
+                dest_root.display(),
+                src_root.display()
+            );
+            log::error!("{}", &msg);
+            return Err(msg);
+        }
+    }
+
+    Ok(())
+}
+
+/// [`rename_dirs_if_possible`] で移動したディレクトリを元の場所に戻す
+pub(crate) async fn restore_renamed_dirs(
+    src_root: &Path,
+    dest_root: &Path,
+    checkpoint: &mut MigrationCheckpoint,
+) {
+    for dir in checkpoint.renamed_dirs().to_vec() {
+        let src = src_root.join(&dir);
+        let dest = dest_root.join(&dir);
+
+        if !dest.exists() || src.exists() {
+            continue;
+        }
+
+        if let Err(e) = tokio::fs::rename(&dest, &src).await {
+            log::error!("Failed to restore renamed dir {}: {:?}", dest.display(), e);
+            continue;
+        }
+
+        if let Err(e) = checkpoint.remove_renamed_dir(&dir) {
+            log::error!("{}", e);
+        }
+    }
+}
+
+/// `dirs` 以下のファイルを移行先にコピーし、完了したファイルを `checkpoint` に記録する
+///
+/// 記録済みのファイルはサイズが一致していればコピーを省略する
+pub(crate) async fn copy_dirs_with_checkpoint(
+    src_root: &Path,
+    dest_root: &Path,
+    dirs: &[&str],
+    checkpoint: &mut MigrationCheckpoint,
+    verification: MigrationVerification,
+    progress_callback: impl Fn(f32, String),
+) -> Result<(), String> {
+    let mut directories = vec![];
+    let mut files = vec![];
+
+    for dir in dirs {
+        let src = src_root.join(dir);
+
+        if src.is_dir() {
+            directories.push(dir.to_string());
+            collect_entries(&src, dir, &mut directories, &mut files)?;
+        }
+    }
+
+    for directory in &directories {
+        fs::create_dir_all(dest_root.join(directory))
+            .map_err(|e| format!("Failed to create directory {}: {:?}", directory, e))?;
+    }
+
+    let total = files.len().max(1) as f32;
+
+    for (index, relative_path) in files.iter().enumerate() {
+        progress_callback(index as f32 / total * 100f32, relative_path.clone());
+
+        let src = src_root.join(relative_path);
+        let dest = dest_root.join(relative_path);
+
+        if checkpoint.is_completed(relative_path)
+            && verify_copied_file(&src, &dest, MigrationVerification::Size)?
+        {
+            continue;
+        }
+
+        modify_guard::copy_file(
+            &src,
+            &dest,
+            false,
+            FileTransferGuard::both(src_root, dest_root),
+        )
+        .await
+        .map_err(|e| format!("Failed to copy file {}: {:?}", relative_path, e))?;
+
+        if !verify_copied_file(&src, &dest, verification)? {
+            let msg = format!("Copied file does not match the source: {}", relative_path);
+            log::error!("{}", &msg);
+            return Err(msg);
+        }
+
+        checkpoint.mark_as_completed(relative_path)?;
+    }
+
+    progress_callback(100f32, String::new());
+
+    Ok(())
+}
+
+fn collect_entries(
+    dir: &Path,
+    relative_dir: &str,
+    directories: &mut Vec<String>,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    let read_dir = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {:?}", dir.display(), e))?;
+
+    let mut paths = read_dir
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+        let relative_path = format!("{}/{}", relative_dir, file_name.to_string_lossy());
+
+        if path.is_dir() {
+            directories.push(relative_path.clone());
+            collect_entries(&path, &relative_path, directories, files)?;
+        } else {
+            files.push(relative_path);
+        }
+    }
+
+    Ok(())
+}
+
+fn verify_copied_file(
+    src: &Path,
+    dest: &Path,
+    verification: MigrationVerification,
+) -> Result<bool, String> {
+    if !dest.is_file() {
+        return Ok(false);
+    }
+
+    let src_size = src
+        .metadata()
+        .map_err(|e| format!("Failed to read metadata of {}: {:?}", src.display(), e))?
+        .len();
+    let dest_size = dest
+        .metadata()
+        .map_err(|e| format!("Failed to read metadata of {}: {:?}", dest.display(), e))?
+        .len();
+
+    if src_size != dest_size {
+        return Ok(false);
+    }
+
+    if verification == MigrationVerification::Hash {
+        let src_hash = file::calculate_file_hash(src)
+            .map_err(|e| format!("Failed to hash {}: {:?}", src.display(), e))?;
+        let dest_hash = file::calculate_file_hash(dest)
+            .map_err(|e| format!("Failed to hash {}: {:?}", dest.display(), e))?;
+
+        return Ok(src_hash == dest_hash);
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(base: &str) -> (PathBuf, PathBuf) {
+        let base = PathBuf::from(base);
+
+        if std::fs::exists(&base).unwrap() {
+            std::fs::remove_dir_all(&base).unwrap();
+        }
+
+        let src = base.join("src");
+        let dest = base.join("dest");
+
+        std::fs::create_dir_all(src.join("metadata")).unwrap();
+        std::fs::create_dir_all(src.join("data/asset/empty")).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+
+        std::fs::write(src.join("metadata/avatar.json"), b"[]").unwrap();
+        std::fs::write(src.join("data/asset/file.txt"), b"content").unwrap();
+        std::fs::write(src.join("data/asset/other.txt"), b"other").unwrap();
+
+        (src, dest)
+    }
+
+    #[tokio::test]
+    async fn test_copy_dirs_with_checkpoint() {
+        let (src, dest) = setup("test/temp/migration/copy");
+
+        let mut checkpoint = MigrationCheckpoint::create(&dest, &src).unwrap();
+
+        copy_dirs_with_checkpoint(
+            &src,
+            &dest,
+            &MIGRATION_TARGET_DIRS,
+            &mut checkpoint,
+            MigrationVerification::Hash,
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read(dest.join("data/asset/file.txt")).unwrap(),
+            b"content"
+        );
+        assert!(dest.join("data/asset/empty").is_dir());
+
+        let pending = find_pending_migration(&dest).unwrap().unwrap();
+        assert_eq!(pending.source, src);
+        assert_eq!(pending.completed_files, 3);
+
+        checkpoint.finish().unwrap();
+        assert!(find_pending_migration(&dest).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resume_copy_dirs_with_checkpoint() {
+        let (src, dest) = setup("test/temp/migration/resume");
+
+        {
+            let mut checkpoint = MigrationCheckpoint::create(&dest, &src).unwrap();
+
+            // 1 ファイル目のコピー後、2 ファイル目の途中で中断されたことを再現する
+            std::fs::create_dir_all(dest.join("data/asset")).unwrap();
+            std::fs::write(dest.join("data/asset/file.txt"), b"content").unwrap();
+            checkpoint.mark_as_completed("data/asset/file.txt").unwrap();
+            std::fs::write(dest.join("data/asset/other.txt"), b"ot").unwrap();
+        }
+
+        let mut checkpoint = MigrationCheckpoint::open(&dest).unwrap().unwrap();
+        assert_eq!(checkpoint.source(), src);
+        assert!(checkpoint.is_completed("data/asset/file.txt"));
+
+        copy_dirs_with_checkpoint(
+            &src,
+            &dest,
+            &MIGRATION_TARGET_DIRS,
+            &mut checkpoint,
+            MigrationVerification::Size,
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read(dest.join("data/asset/other.txt")).unwrap(),
+            b"other"
+        );
+        assert_eq!(
+            std::fs::read(dest.join("metadata/avatar.json")).unwrap(),
+            b"[]"
+        );
+        assert_eq!(
+            find_pending_migration(&dest)
+                .unwrap()
+                .unwrap()
+                .completed_files,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_dirs_if_possible() {
+        let (src, dest) = setup("test/temp/migration/rename");
+
+        std::fs::create_dir_all(dest.join("data")).unwrap();
+
+        let mut checkpoint = MigrationCheckpoint::create(&dest, &src).unwrap();
+        rename_dirs_if_possible(&src, &dest, &MIGRATION_TARGET_DIRS, &mut checkpoint)
+            .await
+            .unwrap();
+
+        // 移行先に既に存在する data は移動せず、コピーの対象として残す
+        assert_eq!(checkpoint.renamed_dirs(), ["metadata"]);
+        assert!(!src.join("metadata").exists());
+        assert!(dest.join("metadata/avatar.json").exists());
+        assert!(src.join("data/asset/file.txt").exists());
+
+        // 移動したディレクトリは再開時にも分かるよう記録されている
+        let mut reopened = MigrationCheckpoint::open(&dest).unwrap().unwrap();
+        assert_eq!(reopened.renamed_dirs(), ["metadata"]);
+
+        restore_renamed_dirs(&src, &dest, &mut reopened).await;
+
+        assert!(src.join("metadata/avatar.json").exists());
+        assert!(!dest.join("metadata").exists());
+        assert!(reopened.renamed_dirs().is_empty());
+    }
+}
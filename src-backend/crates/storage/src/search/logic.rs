@@ -318,6 +318,8 @@ fn unify_text(text: &str) -> String {
 
 #[cfg(test)]
 mod tests {
+    use model::AssetDataLocation;
+
     use super::*;
 
     #[test]
@@ -338,6 +340,7 @@ mod tests {
             dependencies: vec![],
             created_at: chrono::Local::now().timestamp_millis(),
             published_at: Some(chrono::Local::now().timestamp_millis()),
+            data_location: AssetDataLocation::Local,
         };
 
         assert_eq!(check_text_contains(&description, &vec!["アセット"]), true);
@@ -395,6 +398,7 @@ mod tests {
             dependencies: vec![],
             created_at: chrono::Local::now().timestamp_millis(),
             published_at: Some(chrono::Local::now().timestamp_millis()),
+            data_location: AssetDataLocation::Local,
         };
 
         // Basic NOT search
@@ -45,5 +45,12 @@ where
         return Err(e.to_string());
     }
 
+    // 詳細表示用に元の画像を残している場合は、同じ名前で確定させる
+    if let Some(images_dir) = src.parent() {
+        if let Err(e) = file::copy_original_image(images_dir, file_name, new_filename).await {
+            return Err(e.to_string());
+        }
+    }
+
     return Ok(Some(new_filename.to_string()));
 }
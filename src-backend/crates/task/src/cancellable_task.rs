@@ -15,12 +15,10 @@ pub struct CancellableTask {
 }
 
 impl CancellableTask {
-    fn create<F>(status_callback: Option<TaskStatusCallback>, task: F) -> Self
+    fn create<F>(id: Uuid, status_callback: Option<TaskStatusCallback>, task: F) -> Self
     where
         F: Future<Output = Result<(), String>> + Send + 'static,
     {
-        let id = Uuid::new_v4();
-
         let task = tokio::spawn(task);
         let abort_handle = task.abort_handle();
         let status = Arc::new(Mutex::new(TaskStatus::Running));
@@ -98,7 +96,17 @@ impl TaskContainer {
     where
         F: Future<Output = Result<(), String>> + Send + 'static,
     {
-        let task = CancellableTask::create(self.on_task_status_changed.clone(), task);
+        self.run_with_id(|_| task)
+    }
+
+    /// 実行するタスク自身が ID を必要とする場合 (進捗の通知など) に使用する
+    pub fn run_with_id<F, Fut>(&mut self, task_fn: F) -> Result<Uuid, String>
+    where
+        F: FnOnce(Uuid) -> Fut,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let id = Uuid::new_v4();
+        let task = CancellableTask::create(id, self.on_task_status_changed.clone(), task_fn(id));
 
         let id = task.id;
         self.tasks.push(Arc::new(Mutex::new(task)));
@@ -147,4 +155,22 @@ mod tests {
             assert_eq!(status, TaskStatus::Completed);
         }
     }
+
+    #[tokio::test]
+    async fn test_task_container_run_with_id() {
+        let mut task_container = TaskContainer::new(Arc::new(|_, _| {}));
+        let received_id = Arc::new(Mutex::new(None));
+
+        let cloned_received_id = Arc::clone(&received_id);
+        let task_id = task_container
+            .run_with_id(|id| async move {
+                *cloned_received_id.lock().await = Some(id);
+                Ok(())
+            })
+            .unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        assert_eq!(*received_id.lock().await, Some(task_id));
+    }
 }
@@ -0,0 +1,103 @@
+use std::path::Path;
+
+const CANDIDATE_EXTENSIONS: [&str; 2] = ["zip", "unitypackage"];
+
+/// インポート対象として扱うファイルかどうか
+pub fn is_import_candidate<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    let extension = path
+        .as_ref()
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension {
+        Some(extension) => CANDIDATE_EXTENSIONS.contains(&extension.as_str()),
+        None => false,
+    }
+}
+
+/// ファイル名からアセット名の初期値を作成する
+pub fn suggest_asset_name<P>(path: P) -> String
+where
+    P: AsRef<Path>,
+{
+    path.as_ref()
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().trim().to_string())
+        .unwrap_or_default()
+}
+
+/// ファイル名に含まれる BOOTH のアイテムIDを探す
+///
+/// `items/123456` や `booth_123456` / `booth-123456` / `booth123456` の形式に対応する
+pub fn extract_booth_item_id(filename: &str) -> Option<u64> {
+    let lowercase = filename.to_ascii_lowercase();
+
+    for keyword in ["items/", "booth"] {
+        for (index, _) in lowercase.match_indices(keyword) {
+            let rest = &lowercase[index + keyword.len()..];
+            let rest = rest.trim_start_matches(['_', '-', ' ', '#']);
+
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+
+            if digits.is_empty() {
+                continue;
+            }
+
+            if let Ok(id) = digits.parse::<u64>() {
+                return Some(id);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_import_candidate() {
+        assert!(is_import_candidate("Downloads/Avatar_v1.0.zip"));
+        assert!(is_import_candidate("Downloads/Avatar_v1.0.ZIP"));
+        assert!(is_import_candidate("Downloads/Avatar.unitypackage"));
+
+        assert!(!is_import_candidate("Downloads/Avatar_v1.0.zip.crdownload"));
+        assert!(!is_import_candidate("Downloads/Avatar_v1.0.zip.part"));
+        assert!(!is_import_candidate("Downloads/readme.txt"));
+        assert!(!is_import_candidate("Downloads/Avatar"));
+    }
+
+    #[test]
+    fn test_suggest_asset_name() {
+        assert_eq!(
+            suggest_asset_name("Downloads/Avatar_v1.0.zip"),
+            "Avatar_v1.0"
+        );
+        assert_eq!(
+            suggest_asset_name("Downloads/Avatar Outfit .unitypackage"),
+            "Avatar Outfit"
+        );
+    }
+
+    #[test]
+    fn test_extract_booth_item_id() {
+        assert_eq!(extract_booth_item_id("booth_6641548.zip"), Some(6641548));
+        assert_eq!(
+            extract_booth_item_id("Avatar-BOOTH-6641548.zip"),
+            Some(6641548)
+        );
+        assert_eq!(extract_booth_item_id("booth6641548_v1.zip"), Some(6641548));
+        assert_eq!(
+            extract_booth_item_id("https___booth.pm_ja_items/6641548.zip"),
+            Some(6641548)
+        );
+
+        assert_eq!(extract_booth_item_id("Avatar_v1.0.zip"), None);
+        assert_eq!(extract_booth_item_id("booth.zip"), None);
+    }
+}
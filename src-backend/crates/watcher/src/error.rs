@@ -0,0 +1,5 @@
+#[derive(Debug, thiserror::Error)]
+pub enum WatchError {
+    #[error("Failed to initialize file system watcher: {0}")]
+    NotifyError(#[from] notify::Error),
+}
@@ -0,0 +1,125 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{WatchError, candidate::is_import_candidate, stability::StabilityTracker};
+
+/// サイズがこの時間変化しなければダウンロードが完了したとみなす
+const STABLE_DURATION: Duration = Duration::from_secs(3);
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 指定されたフォルダを監視し、ダウンロードが完了した zip / unitypackage を通知する
+pub struct FolderWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl FolderWatcher {
+    /// 監視を開始する (tokio ランタイム上で呼び出す必要がある)
+    pub fn start<F>(dirs: &[PathBuf], on_detected: F) -> Result<Self, WatchError>
+    where
+        F: Fn(PathBuf) + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+                Ok(event) => {
+                    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        return;
+                    }
+
+                    for path in event.paths {
+                        if is_import_candidate(&path) {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Error while watching folders: {}", e),
+            })?;
+
+        for dir in dirs {
+            if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                log::error!("Failed to watch directory ({}): {}", dir.display(), e);
+                continue;
+            }
+
+            log::info!("Watching directory: {}", dir.display());
+        }
+
+        let task = tokio::spawn(async move {
+            let mut tracker = StabilityTracker::new(STABLE_DURATION);
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    path = rx.recv() => match path {
+                        Some(path) => tracker.track(path, Instant::now()),
+                        None => break,
+                    },
+                    _ = interval.tick() => {
+                        let completed = tracker.poll(Instant::now(), |path| {
+                            std::fs::metadata(path).ok().map(|metadata| metadata.len())
+                        });
+
+                        for path in completed {
+                            log::info!("Detected new download: {}", path.display());
+                            on_detected(path);
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            _watcher: watcher,
+            task,
+        })
+    }
+}
+
+impl Drop for FolderWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_detect_completed_download() {
+        let dir = PathBuf::from("test/temp/detect_completed_download");
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let dir = std::path::absolute(dir).unwrap();
+
+        let detected = Arc::new(Mutex::new(vec![]));
+        let detected_clone = detected.clone();
+
+        let _watcher = FolderWatcher::start(std::slice::from_ref(&dir), move |path| {
+            detected_clone.lock().unwrap().push(path);
+        })
+        .unwrap();
+
+        std::fs::write(dir.join("Avatar.zip"), b"dummy").unwrap();
+        std::fs::write(dir.join("readme.txt"), b"dummy").unwrap();
+
+        tokio::time::sleep(STABLE_DURATION + Duration::from_secs(2)).await;
+
+        let detected = detected.lock().unwrap();
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].file_name().unwrap(), "Avatar.zip");
+    }
+}
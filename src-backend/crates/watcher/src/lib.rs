@@ -0,0 +1,10 @@
+mod candidate;
+mod error;
+mod folder_watcher;
+mod metadata_watcher;
+mod stability;
+
+pub use candidate::{extract_booth_item_id, is_import_candidate, suggest_asset_name};
+pub use error::WatchError;
+pub use folder_watcher::FolderWatcher;
+pub use metadata_watcher::MetadataWatcher;
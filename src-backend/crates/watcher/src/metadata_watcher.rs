@@ -0,0 +1,198 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tokio::{sync::mpsc, task::JoinHandle, time::Instant};
+
+use crate::WatchError;
+
+/// 同期ツールは複数回に分けて書き込むことがあるため、変更がこの時間止まってから通知する
+const DEBOUNCE_DURATION: Duration = Duration::from_secs(1);
+
+/// メタデータのフォルダを監視し、JSON ファイルが変更された際に通知する
+///
+/// 自身の保存による変更も通知されるため、変更の有無は呼び出し側で確認する
+pub struct MetadataWatcher {
+    task: JoinHandle<()>,
+}
+
+impl MetadataWatcher {
+    /// 監視を開始する (tokio ランタイム上で呼び出す必要がある)
+    ///
+    /// `dir` は最初にアセットを保存した時に作成されるため、存在しない間は親のフォルダを監視して作成を待つ
+    pub fn start<F>(dir: &Path, on_changed: F) -> Result<Self, WatchError>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+        let dir = dir.to_path_buf();
+        let dir_name = dir.file_name().map(|name| name.to_os_string());
+
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+                Ok(event) => {
+                    if !matches!(
+                        event.kind,
+                        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                    ) {
+                        return;
+                    }
+
+                    for path in event.paths {
+                        let in_dir = path.parent().and_then(|parent| parent.file_name())
+                            == dir_name.as_deref();
+
+                        if (in_dir && is_metadata_file(&path))
+                            || path.file_name() == dir_name.as_deref()
+                        {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Error while watching metadata: {}", e),
+            })?;
+
+        // 同期ツールによってフォルダごと置き換えられた場合にも監視し直せるよう、親のフォルダも監視する
+        if let Some(parent) = dir.parent().filter(|parent| parent.is_dir()) {
+            watcher.watch(parent, RecursiveMode::NonRecursive)?;
+        }
+
+        if dir.is_dir() {
+            watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+            log::info!("Watching metadata directory: {}", dir.display());
+        } else {
+            log::info!(
+                "Metadata directory does not exist yet, waiting for creation: {}",
+                dir.display()
+            );
+        }
+
+        let task = tokio::spawn(async move {
+            let mut deadline: Option<Instant> = None;
+
+            loop {
+                let wait = async {
+                    match deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline).await,
+                        None => std::future::pending().await,
+                    }
+                };
+
+                tokio::select! {
+                    path = rx.recv() => match path {
+                        Some(path) => {
+                            // 作成されたフォルダに既にファイルが含まれている場合もあるため、変更として扱う
+                            if path.file_name() == dir.file_name() && dir.is_dir() {
+                                watch_created_dir(&mut watcher, &dir);
+                            }
+
+                            log::debug!("Metadata file changed: {}", path.display());
+                            deadline = Some(Instant::now() + DEBOUNCE_DURATION);
+                        }
+                        None => break,
+                    },
+                    _ = wait => {
+                        deadline = None;
+                        on_changed();
+                    }
+                }
+            }
+        });
+
+        Ok(Self { task })
+    }
+}
+
+impl Drop for MetadataWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn watch_created_dir(watcher: &mut impl Watcher, dir: &Path) {
+    match watcher.watch(dir, RecursiveMode::NonRecursive) {
+        Ok(()) => log::info!("Watching metadata directory: {}", dir.display()),
+        Err(e) => log::error!("Failed to watch metadata directory: {}", e),
+    }
+}
+
+/// 保存時の一時ファイル (`*.json.tmp`) やバックアップは対象外とする
+fn is_metadata_file(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("json"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn expect_notified(rx: &mut mpsc::UnboundedReceiver<()>) {
+        tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .expect("change was not notified")
+            .unwrap();
+    }
+
+    async fn expect_not_notified(rx: &mut mpsc::UnboundedReceiver<()>) {
+        let result = tokio::time::timeout(DEBOUNCE_DURATION * 2, rx.recv()).await;
+        assert!(result.is_err(), "unexpected change notification");
+    }
+
+    fn setup(dir: &str) -> PathBuf {
+        let dir = PathBuf::from(dir);
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::path::absolute(dir).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_detect_metadata_change() {
+        let dir = setup("test/temp/detect_metadata_change/metadata");
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _watcher = MetadataWatcher::start(&dir, move || {
+            let _ = tx.send(());
+        })
+        .unwrap();
+
+        std::fs::write(dir.join("avatars.json.tmp"), b"{}").unwrap();
+        expect_not_notified(&mut rx).await;
+
+        // 連続した書き込みは 1 回にまとめて通知する
+        std::fs::write(dir.join("avatars.json"), b"{").unwrap();
+        std::fs::write(dir.join("avatars.json"), b"{}").unwrap();
+        expect_notified(&mut rx).await;
+        expect_not_notified(&mut rx).await;
+    }
+
+    #[tokio::test]
+    async fn test_detect_metadata_dir_creation() {
+        let data_dir = setup("test/temp/detect_metadata_dir_creation");
+        let dir = data_dir.join("metadata");
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _watcher = MetadataWatcher::start(&dir, move || {
+            let _ = tx.send(());
+        })
+        .unwrap();
+
+        // データフォルダ直下のファイルは対象外
+        std::fs::write(data_dir.join("other.json"), b"{}").unwrap();
+        expect_not_notified(&mut rx).await;
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("avatars.json"), b"{}").unwrap();
+        expect_notified(&mut rx).await;
+
+        // 作成後のフォルダ内の変更も通知する
+        std::fs::write(dir.join("avatars.json"), b"{ }").unwrap();
+        expect_notified(&mut rx).await;
+    }
+}
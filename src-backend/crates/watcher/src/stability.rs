@@ -0,0 +1,105 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+struct Observation {
+    size: Option<u64>,
+    since: Instant,
+}
+
+/// ダウンロード中のファイルを追跡し、サイズが一定時間変化しなくなったものを完了とみなす
+pub(crate) struct StabilityTracker {
+    candidates: HashMap<PathBuf, Observation>,
+    stable_duration: Duration,
+}
+
+impl StabilityTracker {
+    pub fn new(stable_duration: Duration) -> Self {
+        Self {
+            candidates: HashMap::new(),
+            stable_duration,
+        }
+    }
+
+    pub fn track(&mut self, path: PathBuf, now: Instant) {
+        // 既に追跡中のファイルはサイズの変化として poll で検出する
+        self.candidates.entry(path).or_insert(Observation {
+            size: None,
+            since: now,
+        });
+    }
+
+    /// 完了したファイルを返し、追跡対象から外す
+    pub fn poll(&mut self, now: Instant, size_of: impl Fn(&Path) -> Option<u64>) -> Vec<PathBuf> {
+        let mut completed = vec![];
+
+        self.candidates.retain(|path, observation| {
+            let size = match size_of(path) {
+                Some(size) => size,
+                // 削除またはリネームされたファイルは追跡しない
+                None => return false,
+            };
+
+            if observation.size != Some(size) {
+                observation.size = Some(size);
+                observation.since = now;
+                return true;
+            }
+
+            if size == 0 || now.duration_since(observation.since) < self.stable_duration {
+                return true;
+            }
+
+            completed.push(path.clone());
+            false
+        });
+
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stability_tracker() {
+        let mut tracker = StabilityTracker::new(Duration::from_secs(2));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let path = PathBuf::from("Downloads/Avatar.zip");
+        tracker.track(path.clone(), at(0));
+
+        assert!(tracker.poll(at(0), |_| Some(100)).is_empty());
+        // サイズが変化している間は完了とみなさない
+        assert!(tracker.poll(at(1), |_| Some(200)).is_empty());
+        assert!(tracker.poll(at(2), |_| Some(200)).is_empty());
+
+        assert_eq!(tracker.poll(at(3), |_| Some(200)), vec![path.clone()]);
+        // 完了したファイルは追跡対象から外れる
+        assert!(tracker.poll(at(10), |_| Some(200)).is_empty());
+    }
+
+    #[test]
+    fn test_stability_tracker_removed_file() {
+        let mut tracker = StabilityTracker::new(Duration::from_secs(2));
+        let start = Instant::now();
+
+        tracker.track(PathBuf::from("Downloads/Avatar.zip"), start);
+
+        assert!(tracker.poll(start, |_| Some(100)).is_empty());
+        assert!(
+            tracker
+                .poll(start + Duration::from_secs(1), |_| None)
+                .is_empty()
+        );
+        assert!(
+            tracker
+                .poll(start + Duration::from_secs(5), |_| Some(100))
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use filter::FileFilter;
+use tokio::sync::mpsc;
+use zip_rs::{ZipArchive, result::ZipError};
+
+use crate::{
+    ExtractLimits, ZipExtractError,
+    extractor::{decode_filename, resolve_entry_path, to_system_time},
+    validation::{EntryInfo, validate_entries},
+};
+
+/// async_zip は暗号化されたエントリを扱えないため、zip クレートで暗号化の有無を判定する
+pub(crate) async fn is_encrypted(src: &Path) -> bool {
+    let src = src.to_path_buf();
+
+    let result = tokio::task::spawn_blocking(move || -> Result<bool, ZipError> {
+        let mut archive = ZipArchive::new(BufReader::new(File::open(&src)?))?;
+
+        for i in 0..archive.len() {
+            if archive.by_index_raw(i)?.encrypted() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(encrypted)) => encrypted,
+        Ok(Err(e)) => {
+            log::warn!("Failed to inspect zip file for encryption: {}", e);
+            false
+        }
+        Err(e) => {
+            log::warn!("Failed to inspect zip file for encryption: {}", e);
+            false
+        }
+    }
+}
+
+pub(crate) async fn extract_encrypted_zip(
+    src: &Path,
+    absolute_dest: &Path,
+    password: &str,
+    limits: &ExtractLimits,
+    filter: &FileFilter,
+    progress_callback: impl Fn(f32, String),
+) -> Result<(), ZipExtractError> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<(f32, String)>();
+
+    let src = src.to_path_buf();
+    let absolute_dest = absolute_dest.to_path_buf();
+    let password = password.to_string();
+    let limits = limits.clone();
+    let filter = filter.clone();
+
+    let handle = tokio::task::spawn_blocking(move || {
+        extract_blocking(
+            &src,
+            &absolute_dest,
+            password.as_bytes(),
+            &limits,
+            &filter,
+            tx,
+        )
+    });
+
+    while let Some((progress, filename)) = rx.recv().await {
+        progress_callback(progress, filename);
+    }
+
+    handle.await.map_err(std::io::Error::other)?
+}
+
+fn extract_blocking(
+    src: &Path,
+    absolute_dest: &Path,
+    password: &[u8],
+    limits: &ExtractLimits,
+    filter: &FileFilter,
+    tx: mpsc::UnboundedSender<(f32, String)>,
+) -> Result<(), ZipExtractError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(src)?))?;
+    let entry_length = archive.len();
+
+    let mut entries = Vec::with_capacity(entry_length);
+
+    for i in 0..entry_length {
+        let entry = archive.by_index_raw(i)?;
+
+        entries.push(EntryInfo {
+            name: decode_filename(entry.name_raw()),
+            compressed_size: entry.compressed_size(),
+            uncompressed_size: entry.size(),
+            is_symlink: entry.is_symlink(),
+        });
+    }
+
+    validate_entries(&entries, limits)?;
+
+    // 書き込みを始める前に全ての暗号化エントリでパスワードを検証する
+    let mut smallest_encrypted_file: Option<(usize, u64)> = None;
+
+    for i in 0..entry_length {
+        let entry = archive.by_index_raw(i)?;
+        if !entry.encrypted() {
+            continue;
+        }
+
+        if !entry.is_dir() && smallest_encrypted_file.is_none_or(|(_, size)| entry.size() < size) {
+            smallest_encrypted_file = Some((i, entry.size()));
+        }
+        drop(entry);
+
+        archive
+            .by_index_decrypt(i, password)
+            .map_err(map_decrypt_error)?;
+    }
+
+    // ZipCrypto のヘッダーによる検証は 1 バイトしかなく、誤ったパスワードでも 1/256 の確率で通過するため、
+    // 最も小さいエントリを実際に展開して CRC を確認する
+    if let Some((i, _)) = smallest_encrypted_file {
+        let mut entry = archive
+            .by_index_decrypt(i, password)
+            .map_err(map_decrypt_error)?;
+
+        std::io::copy(&mut entry, &mut std::io::sink()).map_err(map_decrypted_read_error)?;
+    }
+
+    if !absolute_dest.exists() {
+        std::fs::create_dir_all(absolute_dest)?;
+    }
+
+    for (i, entry_info) in entries.into_iter().enumerate() {
+        let entry = archive
+            .by_index_decrypt(i, password)
+            .map_err(map_decrypt_error)?;
+
+        let is_dir = entry.is_dir();
+        let modified = entry.last_modified().and_then(|date| {
+            to_system_time(
+                date.year().into(),
+                date.month().into(),
+                date.day().into(),
+                date.hour().into(),
+                date.minute().into(),
+                date.second().into(),
+            )
+        });
+        let uncompressed_size = entry_info.uncompressed_size;
+        let filename = entry_info.name;
+
+        if filename.is_empty() {
+            log::warn!("Ignoring empty filename");
+            continue;
+        }
+
+        let (filename, absolute_path) = resolve_entry_path(absolute_dest, &filename)?;
+
+        if filter.is_excluded(&filename, is_dir) {
+            log::debug!("Skipping filtered entry: {}", filename);
+        } else if is_dir {
+            log::debug!("Extracting: {}", absolute_path.display());
+            std::fs::create_dir_all(&absolute_path)?;
+        } else {
+            log::debug!("Extracting: {}", absolute_path.display());
+            std::fs::create_dir_all(absolute_path.parent().unwrap())?;
+
+            let mut writer = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&absolute_path)?;
+
+            // 宣言されたサイズを超えて展開されるエントリは拒否する
+            let mut entry = entry.take(uncompressed_size + 1);
+            let written =
+                std::io::copy(&mut entry, &mut writer).map_err(map_decrypted_read_error)?;
+
+            if written > uncompressed_size {
+                return Err(ZipExtractError::EntrySizeMismatch(filename));
+            }
+
+            if let Some(modified) = modified {
+                writer.set_modified(modified)?;
+            }
+        }
+
+        // 受信側が破棄されている場合は展開が中断されたとみなす
+        if tx
+            .send((((i + 1) as f32) / entry_length as f32, filename))
+            .is_err()
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "Extraction was cancelled",
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// 復号後のデータが壊れている (CRC が一致しない、展開できない) 場合は、パスワードの誤りとみなす
+fn map_decrypted_read_error(e: std::io::Error) -> ZipExtractError {
+    match e.kind() {
+        std::io::ErrorKind::InvalidData | std::io::ErrorKind::InvalidInput => {
+            ZipExtractError::InvalidPassword
+        }
+        _ => e.into(),
+    }
+}
+
+fn map_decrypt_error(e: ZipError) -> ZipExtractError {
+    match e {
+        ZipError::InvalidPassword => ZipExtractError::InvalidPassword,
+        e => e.into(),
+    }
+}
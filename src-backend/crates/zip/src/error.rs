@@ -0,0 +1,40 @@
+#[derive(Debug, thiserror::Error)]
+pub enum ZipExtractError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to read zip file: {0}")]
+    ReadError(#[from] async_zip::error::ZipError),
+    #[error("Failed to read encrypted zip file: {0}")]
+    EncryptedReadError(#[from] zip_rs::result::ZipError),
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+    #[error("Password is required to extract this zip file")]
+    PasswordRequired,
+    #[error("Provided password is incorrect")]
+    InvalidPassword,
+    #[error("Entry points outside of the destination: {0}")]
+    UnsafeEntryPath(String),
+    #[error("Symbolic link entries are not allowed: {0}")]
+    SymlinkEntry(String),
+    #[error("Too many entries: {count} (limit: {limit})")]
+    TooManyEntries { count: usize, limit: usize },
+    #[error("Total uncompressed size exceeds the limit: {size} bytes (limit: {limit} bytes)")]
+    TotalSizeExceeded { size: u64, limit: u64 },
+    #[error("Compression ratio of {name} exceeds the limit: {ratio:.1} (limit: {limit})")]
+    CompressionRatioExceeded {
+        name: String,
+        ratio: f64,
+        limit: f64,
+    },
+    #[error("Entry is larger than its declared size: {0}")]
+    EntrySizeMismatch(String),
+}
+
+impl ZipExtractError {
+    pub fn is_password_error(&self) -> bool {
+        matches!(
+            self,
+            ZipExtractError::PasswordRequired | ZipExtractError::InvalidPassword
+        )
+    }
+}
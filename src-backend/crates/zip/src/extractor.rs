@@ -1,146 +1,194 @@
-use std::{path::Path, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
 use async_read_progress::AsyncReadProgressExt;
 use async_zip::{error::ZipError, tokio::read::seek::ZipFileReader};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio_util::compat::FuturesAsyncReadCompatExt;
 
+use filter::FileFilter;
+
+use crate::{
+    ExtractLimits, ZipExtractError, encrypted,
+    validation::{EntryInfo, validate_entries},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// 暗号化された zip を展開する際に使用するパスワード (ZipCrypto / AES)
+    pub password: Option<String>,
+    /// 展開後のサイズやエントリ数などの上限
+    pub limits: ExtractLimits,
+    /// 展開しないエントリを判定するフィルタ (上限の検査は全エントリに対して行われる)
+    pub filter: FileFilter,
+}
+
 pub async fn extract_zip<P, Q>(
     src: P,
     dest: Q,
     progress_callback: impl Fn(f32, String),
-) -> Result<(), String>
+) -> Result<(), ZipExtractError>
 where
     P: AsRef<Path>,
     Q: AsRef<Path>,
 {
-    let mut file = tokio::io::BufReader::new(
-        tokio::fs::File::open(src)
-            .await
-            .map_err(|e| format!("Failed to open zip file: {}", e))?,
-    );
-    let mut zip = ZipFileReader::with_tokio(&mut file)
-        .await
-        .map_err(|e| format!("Failed to read zip file: {}", e))?;
+    extract_zip_with_options(src, dest, &ExtractOptions::default(), progress_callback).await
+}
 
-    let absolute_dest =
-        std::path::absolute(dest).map_err(|e| format!("Failed to get absolute path: {}", e))?;
+pub async fn extract_zip_with_options<P, Q>(
+    src: P,
+    dest: Q,
+    options: &ExtractOptions,
+    progress_callback: impl Fn(f32, String),
+) -> Result<(), ZipExtractError>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let src = src.as_ref();
+
+    // パスワードの検証はファイルを書き込む前に行う
+    let encrypted = encrypted::is_encrypted(src).await;
+    if encrypted && options.password.is_none() {
+        return Err(ZipExtractError::PasswordRequired);
+    }
+
+    let absolute_dest = std::path::absolute(dest)?;
 
     if absolute_dest.is_file() {
-        return Err(format!("Invalid path: {}", absolute_dest.display()));
+        return Err(ZipExtractError::InvalidPath(
+            absolute_dest.display().to_string(),
+        ));
+    }
+
+    if let (true, Some(password)) = (encrypted, &options.password) {
+        return encrypted::extract_encrypted_zip(
+            src,
+            &absolute_dest,
+            password,
+            &options.limits,
+            &options.filter,
+            progress_callback,
+        )
+        .await;
     }
 
+    let mut file = tokio::io::BufReader::new(tokio::fs::File::open(src).await?);
+    let mut zip = ZipFileReader::with_tokio(&mut file).await?;
+
+    let entries = zip
+        .file()
+        .entries()
+        .iter()
+        .map(|entry| {
+            let name = match entry.filename().as_str() {
+                Ok(name) => name.to_string(),
+                Err(ZipError::StringNotUtf8) => decode_as_shift_jis(entry.filename().as_bytes()),
+                Err(e) => return Err(e.into()),
+            };
+
+            Ok(EntryInfo {
+                name,
+                compressed_size: entry.compressed_size(),
+                uncompressed_size: entry.uncompressed_size(),
+                is_symlink: entry
+                    .unix_permissions()
+                    .is_some_and(|mode| mode & 0o170000 == 0o120000),
+            })
+        })
+        .collect::<Result<Vec<_>, ZipExtractError>>()?;
+
+    validate_entries(&entries, &options.limits)?;
+
     if !absolute_dest.exists() {
-        tokio::fs::create_dir_all(&absolute_dest)
-            .await
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+        tokio::fs::create_dir_all(&absolute_dest).await?;
     }
 
-    let entry_length = zip.file().entries().len();
+    let entry_length = entries.len();
 
-    for i in 0..entry_length {
+    for (i, entry_info) in entries.into_iter().enumerate() {
         let entry = zip.file().entries().get(i).unwrap();
-        let uncompressed_size = entry.uncompressed_size();
-
-        let filename = match entry.filename().as_str() {
-            Ok(name) => name.to_string(),
-            Err(e) => {
-                let decoded_as_shift_jis = match e {
-                    ZipError::StringNotUtf8 => decode_as_shift_jis(entry.filename().as_bytes()),
-                    _ => {
-                        return Err(format!("Failed to get file name: {}", e));
-                    }
-                };
-
-                decoded_as_shift_jis
-            }
-        };
+        let uncompressed_size = entry_info.uncompressed_size;
+        let filename = entry_info.name;
 
-        if filename.len() == 0 {
+        if filename.is_empty() {
             log::warn!("Ignoring empty filename");
             continue;
         }
 
-        let filename = filename
-            .replace("\\", "/")
-            .split("/")
-            .map(|s| sanitize_filename::sanitize(s))
-            .collect::<Vec<String>>()
-            .join("/");
+        let (filename, absolute_path) = resolve_entry_path(&absolute_dest, &filename)?;
 
         let entry_is_dir = match entry.dir() {
             Ok(is_dir) => is_dir,
-            Err(e) => match e {
-                ZipError::StringNotUtf8 => filename.ends_with('/'),
-                _ => {
-                    return Err(format!("Failed to get file type: {}", e));
-                }
-            },
+            Err(ZipError::StringNotUtf8) => filename.ends_with('/'),
+            Err(e) => return Err(e.into()),
         };
 
-        let path = absolute_dest.join(&filename);
-        let absolute_path =
-            std::path::absolute(path).map_err(|e| format!("Failed to get absolute path: {}", e))?;
-
-        let absolute_path_str = absolute_path.to_str();
-        if absolute_path_str.is_none() {
-            return Err(format!(
-                "Failed to get absolute path as string: {}",
-                absolute_path.display()
-            ));
-        }
-        let absolute_path_str = absolute_path_str.unwrap();
-
-        let dest_str = absolute_dest.to_str();
-        if dest_str.is_none() {
-            return Err(format!(
-                "Failed to get destination path as string: {}",
-                absolute_dest.display()
-            ));
+        if options.filter.is_excluded(&filename, entry_is_dir) {
+            log::debug!("Skipping filtered entry: {}", filename);
+            progress_callback(((i + 1) as f32) / entry_length as f32, filename);
+            continue;
         }
-        let dest_str = dest_str.unwrap();
 
-        if !absolute_path_str.starts_with(dest_str) {
-            return Err(format!("Invalid path: {}", absolute_path.display()));
-        }
+        let date = entry.last_modification_date();
+        let modified = to_system_time(
+            date.year(),
+            date.month(),
+            date.day(),
+            date.hour(),
+            date.minute(),
+            date.second(),
+        );
 
         log::debug!("Extracting: {}", absolute_path.display());
 
         if entry_is_dir {
-            tokio::fs::create_dir_all(&absolute_path)
-                .await
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
+            tokio::fs::create_dir_all(&absolute_path).await?;
         } else {
-            tokio::fs::create_dir_all(&absolute_path.parent().unwrap())
-                .await
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
+            tokio::fs::create_dir_all(&absolute_path.parent().unwrap()).await?;
 
-            let entry_reader = zip
-                .reader_without_entry(i)
-                .await
-                .map_err(|e| format!("Failed to read zip entry: {}", e))?
-                .report_progress(Duration::from_millis(100), |bytes_read| {
-                    if uncompressed_size == 0 {
-                        return;
-                    }
+            let entry_reader = match zip.reader_without_entry(i).await {
+                Ok(reader) => reader,
+                // 事前の検査をすり抜けた暗号化エントリ
+                Err(ZipError::FeatureNotSupported("encryption")) => {
+                    return Err(ZipExtractError::PasswordRequired);
+                }
+                Err(e) => return Err(e.into()),
+            }
+            .report_progress(Duration::from_millis(100), |bytes_read| {
+                if uncompressed_size == 0 {
+                    return;
+                }
 
-                    let completed = bytes_read as f32 / uncompressed_size as f32;
+                let completed = bytes_read as f32 / uncompressed_size as f32;
 
-                    progress_callback(
-                        (i as f32 + completed) / entry_length as f32,
-                        filename.clone(),
-                    );
-                });
+                progress_callback(
+                    (i as f32 + completed) / entry_length as f32,
+                    filename.clone(),
+                );
+            });
 
             let mut writer = tokio::fs::OpenOptions::new()
                 .write(true)
                 .create_new(true)
                 .open(&absolute_path)
-                .await
-                .map_err(|e| format!("Failed to create file: {}", e))?;
+                .await?;
 
-            tokio::io::copy(&mut entry_reader.compat(), &mut writer)
-                .await
-                .map_err(|e| format!("Failed to write file: {}", e))?;
+            // 宣言されたサイズを超えて展開されるエントリは拒否する
+            let mut entry_reader = entry_reader.compat().take(uncompressed_size + 1);
+            let written = tokio::io::copy(&mut entry_reader, &mut writer).await?;
+
+            if written > uncompressed_size {
+                return Err(ZipExtractError::EntrySizeMismatch(filename));
+            }
+
+            // 既存のファイルと更新日時で比較できるよう、zip に記録された更新日時を引き継ぐ
+            if let Some(modified) = modified {
+                writer.flush().await?;
+                writer.into_std().await.set_modified(modified)?;
+            }
         }
 
         progress_callback(((i + 1) as f32) / entry_length as f32, filename);
@@ -149,6 +197,58 @@ where
     Ok(())
 }
 
+/// 区切り文字を `/` に揃え、パスの各部分をファイル名として使える形に正規化する
+pub(crate) fn sanitize_entry_name(filename: &str) -> String {
+    filename
+        .replace("\\", "/")
+        .split("/")
+        .map(sanitize_filename::sanitize)
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// エントリ名を正規化し、展開先ディレクトリ配下の絶対パスに解決する
+pub(crate) fn resolve_entry_path(
+    absolute_dest: &Path,
+    filename: &str,
+) -> Result<(String, PathBuf), ZipExtractError> {
+    let filename = sanitize_entry_name(filename);
+
+    let absolute_path = std::path::absolute(absolute_dest.join(&filename))?;
+
+    if !absolute_path.starts_with(absolute_dest) {
+        return Err(ZipExtractError::InvalidPath(
+            absolute_path.display().to_string(),
+        ));
+    }
+
+    Ok((filename, absolute_path))
+}
+
+/// zip に記録された更新日時を変換する (タイムゾーンを持たないため、ローカル時刻として扱う)
+pub(crate) fn to_system_time(
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+) -> Option<SystemTime> {
+    let date_time = chrono::NaiveDate::from_ymd_opt(year, month, day)?
+        .and_hms_opt(hour, minute, second)?
+        .and_local_timezone(chrono::Local)
+        .earliest()?;
+
+    Some(date_time.into())
+}
+
+pub(crate) fn decode_filename(name: &[u8]) -> String {
+    match std::str::from_utf8(name) {
+        Ok(name) => name.to_string(),
+        Err(_) => decode_as_shift_jis(name),
+    }
+}
+
 fn decode_as_shift_jis(name: &[u8]) -> String {
     encoding_rs::SHIFT_JIS.decode(name).0.to_string()
 }
@@ -178,6 +278,15 @@ mod tests {
 
         assert_eq!(std::fs::read_to_string(&dummy1_txt_path).unwrap(), "dummy1");
         assert_eq!(std::fs::read_to_string(&dummy2_txt_path).unwrap(), "dummy2");
+
+        // zip に記録された更新日時が引き継がれる
+        assert_eq!(
+            std::fs::metadata(&dummy1_txt_path)
+                .unwrap()
+                .modified()
+                .unwrap(),
+            to_system_time(2025, 4, 1, 1, 26, 2).unwrap()
+        );
     }
 
     #[tokio::test]
@@ -206,4 +315,255 @@ mod tests {
         assert_eq!(std::fs::read_to_string(&dummy1_txt_path).unwrap(), "dummy1");
         assert_eq!(std::fs::read_to_string(&dummy2_txt_path).unwrap(), "dummy2");
     }
+
+    async fn extract_encrypted(
+        src: &str,
+        dest: &str,
+        password: Option<&str>,
+    ) -> Result<(), ZipExtractError> {
+        if std::fs::exists(dest).unwrap() {
+            tokio::fs::remove_dir_all(dest).await.unwrap();
+        }
+
+        let options = ExtractOptions {
+            password: password.map(|p| p.to_string()),
+            ..Default::default()
+        };
+
+        extract_zip_with_options(src, dest, &options, |_, _| {}).await
+    }
+
+    #[tokio::test]
+    async fn test_extract_encrypted_zip() {
+        let sources = [
+            (
+                "test/encrypted-zipcrypto.zip",
+                "test/temp/extracted-encrypted-zipcrypto",
+            ),
+            (
+                "test/encrypted-aes.zip",
+                "test/temp/extracted-encrypted-aes",
+            ),
+        ];
+
+        for (src, dest) in sources {
+            extract_encrypted(src, dest, Some("konoasset"))
+                .await
+                .unwrap();
+
+            let dummy1_txt_path = format!("{dest}/dummy1.txt");
+            let dummy2_txt_path = format!("{dest}/dummy-dir/dummy2.txt");
+
+            assert_eq!(std::fs::read_to_string(&dummy1_txt_path).unwrap(), "dummy1");
+            assert_eq!(std::fs::read_to_string(&dummy2_txt_path).unwrap(), "dummy2");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_encrypted_zip_without_password() {
+        let dest = "test/temp/extracted-encrypted-no-password";
+        let result = extract_encrypted("test/encrypted-aes.zip", dest, None).await;
+
+        assert!(matches!(result, Err(ZipExtractError::PasswordRequired)));
+        // パスワードが無い場合は展開先を作成しない
+        assert!(!std::fs::exists(dest).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_extract_encrypted_zip_with_wrong_password() {
+        let sources = [
+            (
+                "test/encrypted-zipcrypto.zip",
+                "test/temp/extracted-encrypted-wrong-zipcrypto",
+            ),
+            (
+                "test/encrypted-aes.zip",
+                "test/temp/extracted-encrypted-wrong-aes",
+            ),
+        ];
+
+        for (src, dest) in sources {
+            let result = extract_encrypted(src, dest, Some("wrong-password")).await;
+
+            assert!(matches!(result, Err(ZipExtractError::InvalidPassword)));
+            // パスワードが誤っている場合は何も書き込まない
+            assert!(!std::fs::exists(format!("{dest}/dummy1.txt")).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_encrypted_zip_with_password_passing_header_check() {
+        let src = "test/encrypted-zipcrypto.zip";
+        let dest = "test/temp/extracted-encrypted-header-collision";
+
+        // ZipCrypto のヘッダーによる検証を偶然通過する誤ったパスワードを探す
+        let mut archive = zip_rs::ZipArchive::new(std::fs::File::open(src).unwrap()).unwrap();
+        let password = (0..10000)
+            .map(|i| format!("wrong-password-{i}"))
+            .find(|password| {
+                (0..archive.len()).all(|i| {
+                    !archive.by_index_raw(i).unwrap().encrypted()
+                        || archive.by_index_decrypt(i, password.as_bytes()).is_ok()
+                })
+            })
+            .unwrap();
+
+        let result = extract_encrypted(src, dest, Some(&password)).await;
+
+        assert!(matches!(result, Err(ZipExtractError::InvalidPassword)));
+        assert!(!std::fs::exists(format!("{dest}/dummy1.txt")).unwrap());
+    }
+
+    async fn extract_with_limits(
+        src: &str,
+        dest: &str,
+        limits: ExtractLimits,
+    ) -> Result<(), ZipExtractError> {
+        if std::fs::exists(dest).unwrap() {
+            tokio::fs::remove_dir_all(dest).await.unwrap();
+        }
+
+        let options = ExtractOptions {
+            limits,
+            ..Default::default()
+        };
+
+        extract_zip_with_options(src, dest, &options, |_, _| {}).await
+    }
+
+    #[tokio::test]
+    async fn test_reject_unsafe_entry_path() {
+        let sources = [
+            (
+                "test/path-traversal.zip",
+                "test/temp/extracted-path-traversal",
+            ),
+            (
+                "test/absolute-path.zip",
+                "test/temp/extracted-absolute-path",
+            ),
+            ("test/drive-letter.zip", "test/temp/extracted-drive-letter"),
+        ];
+
+        for (src, dest) in sources {
+            let result = extract_with_limits(src, dest, ExtractLimits::default()).await;
+
+            assert!(matches!(result, Err(ZipExtractError::UnsafeEntryPath(_))));
+            // 安全でないエントリを含む場合は何も展開しない
+            assert!(!std::fs::exists(format!("{dest}/dummy1.txt")).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_symlink_entry() {
+        let dest = "test/temp/extracted-symlink";
+        let result = extract_with_limits("test/symlink.zip", dest, ExtractLimits::default()).await;
+
+        assert!(matches!(result, Err(ZipExtractError::SymlinkEntry(_))));
+        assert!(!std::fs::exists(format!("{dest}/dummy1.txt")).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_reject_high_compression_ratio() {
+        let src = "test/high-compression-ratio.zip";
+        let dest = "test/temp/extracted-high-compression-ratio";
+
+        let result = extract_with_limits(src, dest, ExtractLimits::default()).await;
+        assert!(matches!(
+            result,
+            Err(ZipExtractError::CompressionRatioExceeded { .. })
+        ));
+
+        let limits = ExtractLimits {
+            max_compression_ratio: None,
+            ..Default::default()
+        };
+        extract_with_limits(src, dest, limits).await.unwrap();
+        assert_eq!(
+            std::fs::metadata(format!("{dest}/zeros.bin"))
+                .unwrap()
+                .len(),
+            16 * 1024 * 1024
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reject_entry_larger_than_declared() {
+        let dest = "test/temp/extracted-size-mismatch";
+        let result =
+            extract_with_limits("test/size-mismatch.zip", dest, ExtractLimits::default()).await;
+
+        assert!(matches!(result, Err(ZipExtractError::EntrySizeMismatch(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reject_by_configured_limits() {
+        let src = "test/normal.zip";
+        let dest = "test/temp/extracted-configured-limits";
+
+        let limits = ExtractLimits {
+            max_entry_count: Some(2),
+            ..ExtractLimits::unlimited()
+        };
+        let result = extract_with_limits(src, dest, limits).await;
+        assert!(matches!(
+            result,
+            Err(ZipExtractError::TooManyEntries { count: 3, limit: 2 })
+        ));
+
+        let limits = ExtractLimits {
+            max_total_size: Some(8),
+            ..ExtractLimits::unlimited()
+        };
+        let result = extract_with_limits(src, dest, limits).await;
+        assert!(matches!(
+            result,
+            Err(ZipExtractError::TotalSizeExceeded { limit: 8, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_extract_with_filter() {
+        let src = "test/normal.zip";
+        let dest = "test/temp/extracted-with-filter";
+
+        if std::fs::exists(dest).unwrap() {
+            tokio::fs::remove_dir_all(dest).await.unwrap();
+        }
+
+        let options = ExtractOptions {
+            filter: FileFilter::new::<&str>(&[], &["dummy-dir"]).unwrap(),
+            ..Default::default()
+        };
+
+        extract_zip_with_options(src, dest, &options, |_, _| {})
+            .await
+            .unwrap();
+
+        assert!(std::fs::exists(format!("{dest}/dummy1.txt")).unwrap());
+        assert!(!std::fs::exists(format!("{dest}/dummy-dir")).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_extract_encrypted_zip_with_filter() {
+        let src = "test/encrypted-aes.zip";
+        let dest = "test/temp/extracted-encrypted-with-filter";
+
+        if std::fs::exists(dest).unwrap() {
+            tokio::fs::remove_dir_all(dest).await.unwrap();
+        }
+
+        let options = ExtractOptions {
+            password: Some("konoasset".into()),
+            filter: FileFilter::new::<&str>(&["dummy1.txt"], &[]).unwrap(),
+            ..Default::default()
+        };
+
+        extract_zip_with_options(src, dest, &options, |_, _| {})
+            .await
+            .unwrap();
+
+        assert!(std::fs::exists(format!("{dest}/dummy1.txt")).unwrap());
+        assert!(!std::fs::exists(format!("{dest}/dummy-dir/dummy2.txt")).unwrap());
+    }
 }
@@ -1,3 +1,10 @@
+mod encrypted;
+mod error;
 mod extractor;
+mod list;
+mod validation;
 
-pub use extractor::extract_zip;
+pub use error::ZipExtractError;
+pub use extractor::{ExtractOptions, extract_zip, extract_zip_with_options};
+pub use list::{ZipEntry, list_entries};
+pub use validation::ExtractLimits;
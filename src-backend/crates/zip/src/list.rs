@@ -0,0 +1,80 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use zip_rs::{ZipArchive, result::ZipError};
+
+use crate::{
+    ZipExtractError,
+    extractor::{decode_filename, sanitize_entry_name},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    /// 展開時と同じく正規化したエントリ名
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// 展開せずに zip 内のエントリ一覧を取得する (暗号化された zip でもパスワードは不要)
+pub async fn list_entries<P>(src: P) -> Result<Vec<ZipEntry>, ZipExtractError>
+where
+    P: AsRef<Path>,
+{
+    let src = src.as_ref().to_path_buf();
+
+    let result = tokio::task::spawn_blocking(move || -> Result<Vec<ZipEntry>, ZipError> {
+        let mut archive = ZipArchive::new(BufReader::new(File::open(&src)?))?;
+        let mut entries = Vec::with_capacity(archive.len());
+
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i)?;
+
+            entries.push(ZipEntry {
+                name: sanitize_entry_name(&decode_filename(entry.name_raw())),
+                is_dir: entry.is_dir(),
+            });
+        }
+
+        Ok(entries)
+    })
+    .await
+    .map_err(std::io::Error::other)?;
+
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_entries() {
+        let entries = list_entries("test/normal.zip").await.unwrap();
+
+        assert!(entries.contains(&ZipEntry {
+            name: "dummy1.txt".into(),
+            is_dir: false,
+        }));
+        assert!(entries.contains(&ZipEntry {
+            name: "dummy-dir/dummy2.txt".into(),
+            is_dir: false,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_list_entries_of_encrypted_zip() {
+        let entries = list_entries("test/encrypted-aes.zip").await.unwrap();
+
+        assert!(!entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_entries_with_sanitized_names() {
+        let entries = list_entries("test/drive-letter.zip").await.unwrap();
+
+        // 展開時と同じ名前で列挙する
+        assert!(entries.contains(&ZipEntry {
+            name: "C/evil.txt".into(),
+            is_dir: false,
+        }));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::ZipExtractError;
+
+/// 圧縮率の検査対象とする展開後サイズの下限
+///
+/// 小さなファイルは高い圧縮率になりやすいため検査しない
+const COMPRESSION_RATIO_CHECK_THRESHOLD: u64 = 1024 * 1024;
+
+/// 展開時に許容するアーカイブの上限 (None の場合は無制限)
+#[derive(Debug, Clone)]
+pub struct ExtractLimits {
+    /// 展開後の合計サイズの上限 (バイト)
+    pub max_total_size: Option<u64>,
+    /// エントリ数の上限
+    pub max_entry_count: Option<usize>,
+    /// エントリ毎の圧縮率 (展開後サイズ / 圧縮後サイズ) の上限
+    pub max_compression_ratio: Option<f64>,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_total_size: Some(64 * 1024 * 1024 * 1024),
+            max_entry_count: Some(100_000),
+            max_compression_ratio: Some(200.0),
+        }
+    }
+}
+
+impl ExtractLimits {
+    pub fn unlimited() -> Self {
+        Self {
+            max_total_size: None,
+            max_entry_count: None,
+            max_compression_ratio: None,
+        }
+    }
+}
+
+/// 展開前に検査するエントリの情報 (サイズはアーカイブ内で宣言された値)
+pub(crate) struct EntryInfo {
+    pub name: String,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub is_symlink: bool,
+}
+
+/// 全エントリを検査し、安全に展開できない場合はファイルを書き込む前にエラーを返す
+pub(crate) fn validate_entries(
+    entries: &[EntryInfo],
+    limits: &ExtractLimits,
+) -> Result<(), ZipExtractError> {
+    if let Some(limit) = limits.max_entry_count {
+        if entries.len() > limit {
+            return Err(ZipExtractError::TooManyEntries {
+                count: entries.len(),
+                limit,
+            });
+        }
+    }
+
+    let mut total_size: u64 = 0;
+
+    for entry in entries {
+        validate_entry_name(&entry.name)?;
+
+        if entry.is_symlink {
+            return Err(ZipExtractError::SymlinkEntry(entry.name.clone()));
+        }
+
+        total_size = total_size.saturating_add(entry.uncompressed_size);
+
+        if let Some(limit) = limits.max_total_size {
+            if total_size > limit {
+                return Err(ZipExtractError::TotalSizeExceeded {
+                    size: total_size,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = limits.max_compression_ratio {
+            if entry.uncompressed_size >= COMPRESSION_RATIO_CHECK_THRESHOLD {
+                let ratio = entry.uncompressed_size as f64 / entry.compressed_size.max(1) as f64;
+
+                if ratio > limit {
+                    return Err(ZipExtractError::CompressionRatioExceeded {
+                        name: entry.name.clone(),
+                        ratio,
+                        limit,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 展開先の外を指し得るエントリ名 (`..` / 絶対パス / ドライブレター) を拒否する
+fn validate_entry_name(name: &str) -> Result<(), ZipExtractError> {
+    let bytes = name.as_bytes();
+
+    let is_absolute = name.starts_with('/') || name.starts_with('\\');
+    let has_drive_letter = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    let has_parent_dir = name.split(['/', '\\']).any(|component| component == "..");
+
+    if is_absolute || has_drive_letter || has_parent_dir {
+        return Err(ZipExtractError::UnsafeEntryPath(name.to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_entry_name() {
+        assert!(validate_entry_name("dummy-dir/dummy2.txt").is_ok());
+        assert!(validate_entry_name("dummy..txt").is_ok());
+
+        assert!(validate_entry_name("../evil.txt").is_err());
+        assert!(validate_entry_name("dummy-dir\\..\\..\\evil.txt").is_err());
+        assert!(validate_entry_name("/tmp/evil.txt").is_err());
+        assert!(validate_entry_name("\\\\server\\share\\evil.txt").is_err());
+        assert!(validate_entry_name("C:/evil.txt").is_err());
+        assert!(validate_entry_name("c:evil.txt").is_err());
+    }
+}
@@ -205,7 +205,7 @@ impl Into<AvatarExplorerItemBuilder> for OtherAsset {
 
 #[cfg(test)]
 mod tests {
-    use model::AssetDescription;
+    use model::{AssetDataLocation, AssetDescription};
     use std::collections::BTreeSet;
 
     use super::*;
@@ -274,6 +274,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1735689600000, // 2025-01-01 00:00:00
                 published_at: Some(1735689600000),
+                data_location: AssetDataLocation::Local,
             },
         };
 
@@ -303,6 +304,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1735689600000, // 2025-01-01 00:00:00
                 published_at: Some(1735689600000),
+                data_location: AssetDataLocation::Local,
             },
             category: "Accessory".to_string(),
             supported_avatars: BTreeSet::new(),
@@ -331,6 +333,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1735689600000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
             category: "".to_string(),
             supported_avatars: BTreeSet::new(),
@@ -354,6 +357,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1735689600000, // 2025-01-01 00:00:00
                 published_at: Some(1735689600000),
+                data_location: AssetDataLocation::Local,
             },
             category: "Prop".to_string(),
         };
@@ -381,6 +385,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1735689600000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
             category: "".to_string(),
         };
@@ -403,6 +408,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1735689600000, // 2025-01-01 00:00:00
                 published_at: Some(1735689600000),
+                data_location: AssetDataLocation::Local,
             },
             category: "Category".to_string(),
         };
@@ -430,6 +436,7 @@ mod tests {
                 dependencies: vec![],
                 created_at: 1735689600000,
                 published_at: None,
+                data_location: AssetDataLocation::Local,
             },
             category: "".to_string(),
         };
@@ -100,11 +100,10 @@ where
 
         let dest = unique_destination(&items_dir, &sanitized_name);
 
-        modify_guard::copy_dir(
-            avatar.data_dir.clone(),
+        copy_asset_data(
+            avatar.data_dir.as_ref(),
             dest.clone(),
-            false,
-            FileTransferGuard::dest(path),
+            path,
             |progress, filename| {
                 if app.is_none() {
                     return;
@@ -120,14 +119,7 @@ where
                 }
             },
         )
-        .await
-        .map_err(|e| {
-            format!(
-                "Failed to copy asset data directory ({}): {}",
-                avatar.data_dir.display(),
-                e
-            )
-        })?;
+        .await?;
 
         if booth_id.is_some() && image_file_path.is_some() {
             let booth_id = booth_id.unwrap();
@@ -168,11 +160,10 @@ where
 
         let dest = unique_destination(&items_dir, &sanitized_name);
 
-        modify_guard::copy_dir(
-            item.data_dir.clone(),
+        copy_asset_data(
+            item.data_dir.as_ref(),
             dest.clone(),
-            false,
-            FileTransferGuard::dest(path),
+            path,
             |progress, filename| {
                 if app.is_none() {
                     return;
@@ -188,14 +179,7 @@ where
                 }
             },
         )
-        .await
-        .map_err(|e| {
-            format!(
-                "Failed to copy asset data directory ({}): {}",
-                item.data_dir.display(),
-                e
-            )
-        })?;
+        .await?;
 
         if booth_id.is_some() && image_file_path.is_some() {
             let booth_id = booth_id.unwrap();
@@ -242,11 +226,10 @@ where
 
         let dest = unique_destination(&items_dir, &sanitized_name);
 
-        modify_guard::copy_dir(
-            item.data_dir.clone(),
+        copy_asset_data(
+            item.data_dir.as_ref(),
             dest.clone(),
-            false,
-            FileTransferGuard::dest(path),
+            path,
             |progress, filename| {
                 if app.is_none() {
                     return;
@@ -262,14 +245,7 @@ where
                 }
             },
         )
-        .await
-        .map_err(|e| {
-            format!(
-                "Failed to copy asset data directory ({}): {}",
-                item.data_dir.display(),
-                e
-            )
-        })?;
+        .await?;
 
         if booth_id.is_some() && image_file_path.is_some() {
             let booth_id = booth_id.unwrap();
@@ -314,11 +290,10 @@ where
 
         let dest = unique_destination(&items_dir, &sanitized_name);
 
-        modify_guard::copy_dir(
-            item.data_dir.clone(),
+        copy_asset_data(
+            item.data_dir.as_ref(),
             dest.clone(),
-            false,
-            FileTransferGuard::dest(path),
+            path,
             |progress, filename| {
                 if app.is_none() {
                     return;
@@ -334,14 +309,7 @@ where
                 }
             },
         )
-        .await
-        .map_err(|e| {
-            format!(
-                "Failed to copy asset data directory ({}): {}",
-                item.data_dir.display(),
-                e
-            )
-        })?;
+        .await?;
 
         if booth_id.is_some() && image_file_path.is_some() {
             let booth_id = booth_id.unwrap();
@@ -461,6 +429,36 @@ where
     Ok(())
 }
 
+/// アセットのファイルをコピーする。ファイルを持たないアセットの場合は空のフォルダのみ作成する
+async fn copy_asset_data(
+    data_dir: Option<&PathBuf>,
+    dest: PathBuf,
+    export_root: &Path,
+    progress_callback: impl Fn(f32, String),
+) -> Result<(), String> {
+    let Some(data_dir) = data_dir else {
+        return tokio::fs::create_dir_all(&dest)
+            .await
+            .map_err(|e| format!("Failed to create directory ({}): {}", dest.display(), e));
+    };
+
+    modify_guard::copy_dir(
+        data_dir.clone(),
+        dest,
+        false,
+        FileTransferGuard::dest(export_root),
+        progress_callback,
+    )
+    .await
+    .map_err(|e| {
+        format!(
+            "Failed to copy asset data directory ({}): {}",
+            data_dir.display(),
+            e
+        )
+    })
+}
+
 fn unique_destination<P, S>(parent: P, preferred_name: S) -> PathBuf
 where
     P: AsRef<Path>,
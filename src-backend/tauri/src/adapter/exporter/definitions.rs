@@ -29,7 +29,8 @@ impl CategoryBasedAssets {
 
 pub struct AssetExportOverview<A: AssetTrait> {
     pub asset: A,
-    pub data_dir: PathBuf,
+    /// ファイルを持たないアセットの場合は None
+    pub data_dir: Option<PathBuf>,
     pub image_path: Option<PathBuf>,
 }
 
@@ -39,9 +40,10 @@ impl AssetExportOverview<Avatar> {
         P: AsRef<Path>,
     {
         let root_dir = root_dir.as_ref();
-        let id = asset.id.to_string();
-
-        let data_dir = root_dir.join("data").join(id);
+        let data_dir = asset
+            .description
+            .data_location
+            .resolve_data_dir(root_dir, asset.id);
         let image_path = match asset.description.image_filename.as_ref() {
             Some(image_filename) => Some(root_dir.join("images").join(image_filename)),
             None => None,
@@ -61,9 +63,10 @@ impl AssetExportOverview<AvatarWearable> {
         P: AsRef<Path>,
     {
         let root_dir = root_dir.as_ref();
-        let id = asset.id.to_string();
-
-        let data_dir = root_dir.join("data").join(id);
+        let data_dir = asset
+            .description
+            .data_location
+            .resolve_data_dir(root_dir, asset.id);
         let image_path = match asset.description.image_filename.as_ref() {
             Some(image_filename) => Some(root_dir.join("images").join(image_filename)),
             None => None,
@@ -83,9 +86,10 @@ impl AssetExportOverview<WorldObject> {
         P: AsRef<Path>,
     {
         let root_dir = root_dir.as_ref();
-        let id = asset.id.to_string();
-
-        let data_dir = root_dir.join("data").join(id);
+        let data_dir = asset
+            .description
+            .data_location
+            .resolve_data_dir(root_dir, asset.id);
         let image_path = match asset.description.image_filename.as_ref() {
             Some(image_filename) => Some(root_dir.join("images").join(image_filename)),
             None => None,
@@ -105,9 +109,10 @@ impl AssetExportOverview<OtherAsset> {
         P: AsRef<Path>,
     {
         let root_dir = root_dir.as_ref();
-        let id = asset.id.to_string();
-
-        let data_dir = root_dir.join("data").join(id);
+        let data_dir = asset
+            .description
+            .data_location
+            .resolve_data_dir(root_dir, asset.id);
         let image_path = match asset.description.image_filename.as_ref() {
             Some(image_filename) => Some(root_dir.join("images").join(image_filename)),
             None => None,
@@ -75,7 +75,9 @@ where
                 .map_err(|e| e.to_string())?;
         }
 
-        write_asset_data(&mut writer, item_path, avatar.data_dir).await?;
+        if let Some(data_dir) = avatar.data_dir {
+            write_asset_data(&mut writer, item_path, data_dir).await?;
+        }
 
         processed_assets += 1;
     }
@@ -214,7 +216,9 @@ where
                 .map_err(|e| e.to_string())?;
         }
 
-        write_asset_data(writer, item_path, &item.data_dir).await?;
+        if let Some(data_dir) = &item.data_dir {
+            write_asset_data(writer, item_path, data_dir).await?;
+        }
     }
 
     Ok(())
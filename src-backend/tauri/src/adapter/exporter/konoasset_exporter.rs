@@ -4,7 +4,7 @@ use std::sync::Arc;
 use async_zip::base::write::ZipFileWriter;
 use async_zip::{Compression, ZipEntryBuilder};
 use file::DeleteOnDrop;
-use storage::asset_storage::AssetStorage;
+use storage::{asset_storage::AssetStorage, lock::LOCK_FILENAME, migration::MIGRATION_STATE_DIR};
 use tauri::AppHandle;
 use tauri_specta::Event;
 use tokio::fs::File;
@@ -14,6 +14,9 @@ use crate::definitions::entities::ProgressEvent;
 
 use super::util::new_zip_dir;
 
+/// データフォルダの直下にあっても、そのままエクスポートしないエントリ
+const EXCLUDED_ROOT_ENTRIES: [&str; 4] = ["data", "metadata", LOCK_FILENAME, MIGRATION_STATE_DIR];
+
 pub async fn export_as_konoasset_structured_zip<P>(
     store_provider: Arc<Mutex<AssetStorage>>,
     path: P,
@@ -29,23 +32,78 @@ where
         .map_err(|e| e.to_string())?;
     let mut writer = ZipFileWriter::with_tokio(&mut file);
 
-    let root_dir = store_provider.lock().await.data_dir();
+    let (root_dir, metadata, asset_data_dirs) = {
+        let store = store_provider.lock().await;
 
-    let dir_entries = get_flattened_dir_entries(root_dir.clone()).await?;
+        let mut asset_data_dirs = vec![];
+        for id in store.get_used_ids().await {
+            if let Some(data_dir) = store.resolve_asset_data_dir(id).await {
+                if data_dir.is_dir() {
+                    asset_data_dirs.push((id, data_dir));
+                }
+            }
+        }
+        asset_data_dirs.sort();
 
-    let total_count = dir_entries.len();
-    let mut processed_count = 0;
+        (
+            store.data_dir(),
+            store.export_metadata().await?,
+            asset_data_dirs,
+        )
+    };
+
+    let mut entries = vec![];
 
-    for entry in dir_entries {
+    for entry in get_flattened_dir_entries(root_dir.clone()).await? {
         let relative_path = entry.strip_prefix(&root_dir).map_err(|e| e.to_string())?;
-        let relative_path_str = relative_path.to_string_lossy().to_string();
+        let root_entry_name = relative_path
+            .components()
+            .next()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // アセットのファイルとメタデータは、参照先を解決したものを後で追加する
+        if EXCLUDED_ROOT_ENTRIES.contains(&root_entry_name.as_str()) {
+            continue;
+        }
 
-        let relative_path_str = if MAIN_SEPARATOR == '\\' {
-            relative_path_str.replace(MAIN_SEPARATOR, "/")
-        } else {
-            relative_path_str
-        };
+        entries.push((to_zip_path(relative_path), entry));
+    }
+
+    for (id, data_dir) in asset_data_dirs {
+        let asset_dir = format!("data/{}", id);
+
+        entries.push((asset_dir.clone(), data_dir.clone()));
+
+        for entry in get_flattened_dir_entries(data_dir.clone()).await? {
+            let relative_path = entry.strip_prefix(&data_dir).map_err(|e| e.to_string())?;
+            entries.push((
+                format!("{}/{}", asset_dir, to_zip_path(relative_path)),
+                entry,
+            ));
+        }
+    }
+
+    new_zip_dir(&mut writer, "metadata/").await?;
+
+    for (file_name, content) in metadata {
+        let builder = ZipEntryBuilder::new(
+            format!("metadata/{}", file_name).into(),
+            Compression::Stored,
+        );
 
+        writer
+            .write_entry_whole(builder, &content)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    new_zip_dir(&mut writer, "data/").await?;
+
+    let total_count = entries.len();
+    let mut processed_count = 0;
+
+    for (relative_path_str, entry) in entries {
         if entry.is_dir() {
             new_zip_dir(&mut writer, format!("{}/", relative_path_str)).await?;
         } else {
@@ -89,6 +147,16 @@ where
     Ok(())
 }
 
+fn to_zip_path(relative_path: &Path) -> String {
+    let relative_path_str = relative_path.to_string_lossy().to_string();
+
+    if MAIN_SEPARATOR == '\\' {
+        relative_path_str.replace(MAIN_SEPARATOR, "/")
+    } else {
+        relative_path_str
+    }
+}
+
 async fn get_flattened_dir_entries<P>(path: P) -> Result<Vec<PathBuf>, String>
 where
     P: AsRef<Path> + Send + Sync + 'static,
@@ -113,6 +181,8 @@ where
 #[cfg(test)]
 mod tests {
     use file::modify_guard::{self, FileTransferGuard};
+    use model::AssetDataLocation;
+    use uuid::Uuid;
 
     use super::*;
 
@@ -178,4 +248,88 @@ mod tests {
 
         assert_eq!(src_entries, extracted_entries);
     }
+
+    #[tokio::test]
+    async fn test_konoasset_export_resolves_linked_data() {
+        let dest = "test/temp/export/konoasset_linked";
+        let zip = format!("{dest}/exported.zip");
+        let provider = format!("{dest}/provider");
+        let linked = format!("{dest}/linked");
+        let extracted = format!("{dest}/extracted");
+
+        if std::fs::exists(dest).unwrap() {
+            std::fs::remove_dir_all(dest).unwrap();
+        }
+        std::fs::create_dir_all(&extracted).unwrap();
+
+        modify_guard::copy_dir(
+            "../test/example_root_dir/sample1",
+            provider.clone(),
+            false,
+            FileTransferGuard::none(),
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+
+        // アセットのファイルを外部のフォルダに移し、参照するようにする
+        let linked_id = Uuid::parse_str("2bde4d66-1843-4250-b929-157f947f5751").unwrap();
+        std::fs::rename(format!("{provider}/data/{linked_id}"), &linked).unwrap();
+
+        std::fs::write(format!("{provider}/{LOCK_FILENAME}"), "lock").unwrap();
+        std::fs::create_dir_all(format!("{provider}/{MIGRATION_STATE_DIR}")).unwrap();
+
+        let mut store_provider = AssetStorage::create(&provider).unwrap();
+        store_provider.load_all_assets_from_files().await.unwrap();
+        store_provider
+            .set_data_location(
+                linked_id,
+                AssetDataLocation::Linked {
+                    path: std::path::absolute(&linked)
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let store_provider = Arc::new(Mutex::new(store_provider));
+
+        export_as_konoasset_structured_zip(store_provider, &zip, None)
+            .await
+            .unwrap();
+
+        zip::extract_zip(&zip, &extracted, |_, _| {}).await.unwrap();
+
+        // 参照先のファイルは、データディレクトリ内のファイルとしてエクスポートされる
+        assert_eq!(
+            trim_base(
+                get_flattened_dir_entries(format!("{extracted}/data/{linked_id}"))
+                    .await
+                    .unwrap(),
+                format!("{extracted}/data/{linked_id}"),
+            ),
+            trim_base(
+                get_flattened_dir_entries(linked.clone()).await.unwrap(),
+                linked.clone(),
+            )
+        );
+
+        let mut exported = AssetStorage::create(&extracted).unwrap();
+        exported.load_all_assets_from_files().await.unwrap();
+        assert_eq!(
+            exported
+                .get_asset(linked_id)
+                .await
+                .unwrap()
+                .description()
+                .data_location,
+            AssetDataLocation::Local
+        );
+
+        // 内部で使うファイルはエクスポートしない
+        assert!(!std::fs::exists(format!("{extracted}/{LOCK_FILENAME}")).unwrap());
+        assert!(!std::fs::exists(format!("{extracted}/{MIGRATION_STATE_DIR}")).unwrap());
+    }
 }
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use booth::{
+    BoothFetcher, BoothMetadataDiff, BoothMetadataField, BoothMetadataUpdate, BoothMetadataValues,
+    BoothUpdateTracker, PximgResolver,
+};
+use model::preference::{BoothNameSource, PreferenceStore};
+use storage::asset_storage::AssetStorage;
+use tauri::{AppHandle, Manager, async_runtime::Mutex};
+use uuid::Uuid;
+
+/// BOOTH から情報を取得し直し、登録済みのアセットとの差分を返す
+///
+/// `asset_ids` が空の場合は、BOOTH のアイテムが紐づいているすべてのアセットを対象にする
+pub async fn get_booth_metadata_diffs(
+    app_handle: &AppHandle,
+    asset_ids: Vec<Uuid>,
+) -> Result<Vec<BoothMetadataDiff>, String> {
+    let name_source = {
+        let preference = app_handle.state::<Arc<Mutex<PreferenceStore>>>();
+        preference.lock().await.booth_name_source
+    };
+
+    let targets = {
+        let basic_store = app_handle.state::<Arc<Mutex<AssetStorage>>>();
+        let basic_store = basic_store.lock().await;
+
+        let asset_ids = if asset_ids.is_empty() {
+            basic_store
+                .get_booth_linked_assets()
+                .await
+                .into_keys()
+                .collect()
+        } else {
+            asset_ids
+        };
+
+        let mut targets = vec![];
+        for asset_id in asset_ids {
+            let Some(asset) = basic_store.get_asset(asset_id).await else {
+                return Err(format!("Asset not found: {}", asset_id));
+            };
+
+            let description = asset.description();
+            let Some(booth_item_id) = description.booth_item_id else {
+                return Err(format!("Asset is not linked to BOOTH item: {}", asset_id));
+            };
+
+            targets.push((
+                asset_id,
+                booth_item_id,
+                BoothMetadataValues::from_description(description),
+            ));
+        }
+
+        targets
+    };
+
+    let source_image_urls = {
+        let tracker = app_handle.state::<Arc<Mutex<BoothUpdateTracker>>>();
+        let tracker = tracker.lock().await;
+
+        targets
+            .iter()
+            .map(|(asset_id, _, _)| tracker.get_image_url(*asset_id).map(str::to_string))
+            .collect::<Vec<_>>()
+    };
+
+    log::info!("Refreshing BOOTH metadata ({} assets)", targets.len());
+
+    let booth_fetcher = app_handle
+        .state::<Mutex<BoothFetcher>>()
+        .lock()
+        .await
+        .clone();
+    let mut diffs = Vec::with_capacity(targets.len());
+
+    for ((asset_id, booth_item_id, current), source_image_url) in
+        targets.into_iter().zip(source_image_urls)
+    {
+        let result = booth_fetcher.refresh(booth_item_id).await;
+
+        let diff = match result {
+            Ok(info) => {
+                let fetched = BoothMetadataValues::from_info(&info, name_source);
+
+                BoothMetadataDiff {
+                    asset_id,
+                    booth_item_id,
+                    changed_fields: booth::diff_booth_metadata(
+                        &current,
+                        &fetched,
+                        source_image_url.as_deref(),
+                    ),
+                    current,
+                    fetched: Some(fetched),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to refresh BOOTH metadata (id = {}): {}",
+                    booth_item_id,
+                    e
+                );
+
+                BoothMetadataDiff {
+                    asset_id,
+                    booth_item_id,
+                    current,
+                    fetched: None,
+                    changed_fields: vec![],
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+
+        diffs.push(diff);
+    }
+
+    Ok(diffs)
+}
+
+/// 選択された項目を BOOTH の情報で上書きし、更新できたアセットの ID を返す
+///
+/// 画像が選択された場合は、BOOTH の 1 枚目の画像をサムネイルにして差し替える
+/// 失敗したアセットがあっても残りは続行する
+pub async fn apply_booth_metadata(
+    app_handle: &AppHandle,
+    updates: Vec<BoothMetadataUpdate>,
+) -> Result<Vec<Uuid>, String> {
+    let (name_source, use_trash_bin) = {
+        let preference = app_handle.state::<Arc<Mutex<PreferenceStore>>>();
+        let preference = preference.lock().await;
+        (preference.booth_name_source, preference.use_trash_bin)
+    };
+
+    let mut updated = vec![];
+
+    for update in updates {
+        if update.fields.is_empty() {
+            continue;
+        }
+
+        match apply_to_asset(app_handle, &update, name_source, use_trash_bin).await {
+            Ok(()) => updated.push(update.asset_id),
+            Err(e) => log::error!(
+                "Failed to apply BOOTH metadata (asset = {}): {}",
+                update.asset_id,
+                e
+            ),
+        }
+    }
+
+    log::info!("Applied BOOTH metadata to {} assets", updated.len());
+
+    Ok(updated)
+}
+
+async fn apply_to_asset(
+    app_handle: &AppHandle,
+    update: &BoothMetadataUpdate,
+    name_source: BoothNameSource,
+    use_trash_bin: bool,
+) -> Result<(), String> {
+    let basic_store = app_handle.state::<Arc<Mutex<AssetStorage>>>();
+
+    let booth_item_id = basic_store
+        .lock()
+        .await
+        .get_asset(update.asset_id)
+        .await
+        .ok_or_else(|| format!("Asset not found: {}", update.asset_id))?
+        .description()
+        .booth_item_id
+        .ok_or_else(|| format!("Asset is not linked to BOOTH item: {}", update.asset_id))?;
+
+    // 差分の確認時に取得し直しているため、キャッシュを使う
+    let booth_fetcher = app_handle
+        .state::<Mutex<BoothFetcher>>()
+        .lock()
+        .await
+        .clone();
+    let info = booth_fetcher
+        .fetch(booth_item_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let fetched = BoothMetadataValues::from_info(&info, name_source);
+
+    let image = match &fetched.image {
+        Some(image_url) if update.fields.contains(&BoothMetadataField::Image) => {
+            // 一時ファイルは更新時に確定され移動するため、同じ画像を使うアセットごとに別のファイルを作成する
+            // (`resolve` は URL ごとに同じ一時ファイルを返す)
+            let image_filename = app_handle
+                .state::<Arc<Mutex<PximgResolver>>>()
+                .lock()
+                .await
+                .resolve_with_crop(image_url, None, false)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            Some((image_url.clone(), image_filename))
+        }
+        _ => None,
+    };
+
+    {
+        let basic_store = basic_store.lock().await;
+
+        // 取得中に編集された内容を上書きしないよう、ロックしてから読み直す
+        let mut asset = basic_store
+            .get_asset(update.asset_id)
+            .await
+            .ok_or_else(|| format!("Asset not found: {}", update.asset_id))?;
+
+        if asset.description().booth_item_id != Some(booth_item_id) {
+            return Err(format!(
+                "BOOTH item linked to asset has changed: {}",
+                update.asset_id
+            ));
+        }
+
+        booth::apply_booth_metadata(asset.description_mut(), &fetched, &update.fields);
+
+        if let Some((_, image_filename)) = &image {
+            // 古いサムネイルの削除と、新しいサムネイルの確定は更新時に行われる
+            asset.description_mut().image_filename = Some(image_filename.clone());
+        }
+
+        basic_store
+            .update_asset_and_save(asset, use_trash_bin)
+            .await?;
+    }
+
+    if let Some((image_url, _)) = image {
+        let tracker = app_handle.state::<Arc<Mutex<BoothUpdateTracker>>>();
+        let mut tracker = tracker.lock().await;
+
+        tracker.record_image_url(update.asset_id, &info, image_url);
+
+        if let Err(e) = tracker.save() {
+            log::error!("Failed to save BOOTH update records: {}", e);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+/// BOOTH での更新の確認が終わったことを通知する
+#[derive(Serialize, Debug, Clone, specta::Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothItemUpdatesChecked {
+    pub update_available_ids: Vec<Uuid>,
+}
+
+impl BoothItemUpdatesChecked {
+    pub fn new(update_available_ids: Vec<Uuid>) -> Self {
+        Self {
+            update_available_ids,
+        }
+    }
+}
@@ -0,0 +1,125 @@
+mod definitions;
+
+use std::{collections::HashMap, sync::Arc};
+
+use booth::{BoothFetcher, BoothUpdateTracker};
+use storage::asset_storage::AssetStorage;
+use tauri::{AppHandle, Manager, async_runtime::Mutex};
+use tauri_specta::Event;
+use uuid::Uuid;
+
+pub use definitions::BoothItemUpdatesChecked;
+
+/// インポートしたアセットについて、インポート時の BOOTH のアイテムの状態を記録する
+///
+/// インポート前に取得した情報がキャッシュにない場合は、最初の確認時の状態が記録される
+pub async fn record_imported_booth_item(
+    app_handle: &AppHandle,
+    asset_id: Uuid,
+    booth_item_id: u64,
+) {
+    let info = app_handle
+        .state::<Mutex<BoothFetcher>>()
+        .lock()
+        .await
+        .get_cached(booth_item_id);
+
+    let Some(info) = info else {
+        return;
+    };
+
+    let tracker = app_handle.state::<Arc<Mutex<BoothUpdateTracker>>>();
+    let mut tracker = tracker.lock().await;
+
+    tracker.record(asset_id, &info);
+
+    if let Err(e) = tracker.save() {
+        log::error!("Failed to save BOOTH update records: {}", e);
+    }
+}
+
+/// 削除したアセットについて、BOOTH のアイテムの状態の記録を取り除く
+pub async fn remove_booth_item_record(tracker: &Mutex<BoothUpdateTracker>, asset_id: Uuid) {
+    let mut tracker = tracker.lock().await;
+
+    tracker.remove(asset_id);
+
+    if let Err(e) = tracker.save() {
+        log::error!("Failed to save BOOTH update records: {}", e);
+    }
+}
+
+/// BOOTH のアイテムが紐づいているアセットについて、作者による更新がないかを確認する
+///
+/// 更新されているアセットの ID を返す (リクエストの頻度は `BoothFetcher` 側で制限される)。
+/// インポート時の状態が記録されていないアセットは、最初の確認時の状態を基準にする
+pub async fn check_booth_item_updates(app_handle: &AppHandle) -> Result<Vec<Uuid>, String> {
+    let assets = {
+        let basic_store = app_handle.state::<Arc<Mutex<AssetStorage>>>();
+        let basic_store = basic_store.lock().await;
+        basic_store.get_booth_linked_assets().await
+    };
+
+    let tracker = app_handle.state::<Arc<Mutex<BoothUpdateTracker>>>();
+    let booth_fetcher = app_handle
+        .state::<Mutex<BoothFetcher>>()
+        .lock()
+        .await
+        .clone();
+
+    // キャッシュと比較しても意味がないため、オフラインの場合は前回の結果を返す
+    if booth_fetcher.is_offline_mode() {
+        log::info!("Skipping BOOTH item update check in offline mode");
+        return Ok(tracker.lock().await.get_update_available_ids());
+    }
+
+    // 同じアイテムに紐づくアセットが複数ある場合も、取得は 1 回にする
+    let mut assets_by_item: HashMap<u64, Vec<Uuid>> = HashMap::new();
+    for (asset_id, booth_item_id) in assets {
+        assets_by_item
+            .entry(booth_item_id)
+            .or_default()
+            .push(asset_id);
+    }
+
+    log::info!(
+        "Checking BOOTH item updates ({} items)",
+        assets_by_item.len()
+    );
+
+    for (booth_item_id, asset_ids) in assets_by_item {
+        // キャッシュが有効期限内でも、更新を見逃さないよう BOOTH から取得し直す
+        let result = booth_fetcher.refresh(booth_item_id).await;
+
+        match result {
+            Ok(info) => {
+                let mut tracker = tracker.lock().await;
+                for asset_id in asset_ids {
+                    tracker.check(asset_id, &info);
+                }
+            }
+            Err(e) => log::warn!(
+                "Failed to fetch BOOTH item for update check (id = {}): {}",
+                booth_item_id,
+                e
+            ),
+        }
+    }
+
+    let update_available_ids = {
+        let tracker = tracker.lock().await;
+        tracker.save()?;
+        tracker.get_update_available_ids()
+    };
+
+    log::info!(
+        "Finished checking BOOTH item updates ({} updated)",
+        update_available_ids.len()
+    );
+
+    if let Err(e) = BoothItemUpdatesChecked::new(update_available_ids.clone()).emit(app_handle) {
+        log::error!("Failed to emit BoothItemUpdatesChecked event: {}", e);
+    }
+
+    Ok(update_available_ids)
+}
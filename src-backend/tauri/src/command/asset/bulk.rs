@@ -0,0 +1,131 @@
+use std::{path::PathBuf, sync::Arc};
+
+use model::preference::PreferenceStore;
+use storage::asset_storage::AssetStorage;
+use task::TaskContainer;
+use tauri::{AppHandle, State, async_runtime::Mutex};
+use tauri_specta::Event;
+use uuid::Uuid;
+
+use crate::importer::{
+    bulk::{self, BulkImportPlan, BulkImportPlanCreated},
+    classifier::build_asset_classifier,
+    fileutils::{build_extract_limits, build_import_filter},
+};
+
+/// BOOTH から情報を取得しながらインポート計画を作成する
+///
+/// 作成された計画は `BulkImportPlanCreated` で通知される
+#[tauri::command]
+#[specta::specta]
+pub async fn create_bulk_import_plan(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    task_container: State<'_, Arc<Mutex<TaskContainer>>>,
+    handle: State<'_, AppHandle>,
+    dir: PathBuf,
+) -> Result<Uuid, String> {
+    log::info!("Creating bulk import plan from: {}", dir.display());
+
+    if !dir.is_dir() {
+        let err = format!("Directory does not exist: {}", dir.display());
+        log::error!("{}", err);
+        return Err(err);
+    }
+
+    let (filter, name_source) = {
+        let preference = preference.lock().await;
+        (
+            build_import_filter(&preference)?,
+            preference.booth_name_source,
+        )
+    };
+    let (candidates, skipped) = bulk::scan_bulk_import_dir(&dir, &filter).map_err(|e| {
+        log::error!("Failed to scan directory for bulk import: {}", e);
+        e
+    })?;
+
+    let (registered_booth_item_ids, classifier) = {
+        let basic_store = basic_store.lock().await;
+        (
+            basic_store.get_used_booth_item_ids().await,
+            build_asset_classifier(&basic_store).await,
+        )
+    };
+
+    let app_handle = (*handle).clone();
+
+    task_container
+        .lock()
+        .await
+        .run_with_id(move |task_id| async move {
+            let plan = bulk::create_bulk_import_plan(
+                &app_handle,
+                task_id,
+                candidates,
+                skipped,
+                &registered_booth_item_ids,
+                name_source,
+                &classifier,
+            )
+            .await;
+
+            let event = BulkImportPlanCreated { task_id, plan };
+
+            if let Err(e) = event.emit(&app_handle) {
+                log::error!("Failed to emit BulkImportPlanCreated event: {}", e);
+            }
+
+            Ok(())
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn execute_bulk_import_plan(
+    task_container: State<'_, Arc<Mutex<TaskContainer>>>,
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    handle: State<'_, AppHandle>,
+    plan: BulkImportPlan,
+    delete_source: bool,
+) -> Result<Uuid, String> {
+    log::info!(
+        "Executing bulk import plan ({} items)",
+        plan.items.iter().filter(|item| item.enabled).count()
+    );
+
+    let (zip_extraction, use_trash_bin, filter, limits) = {
+        let preference = preference.lock().await;
+        (
+            preference.zip_extraction,
+            preference.use_trash_bin,
+            build_import_filter(&preference)?,
+            build_extract_limits(&preference),
+        )
+    };
+
+    let app_handle = (*handle).clone();
+
+    task_container
+        .lock()
+        .await
+        .run_with_id(move |task_id| async move {
+            let result = bulk::execute_bulk_import_plan(
+                &app_handle,
+                task_id,
+                plan,
+                delete_source,
+                zip_extraction,
+                use_trash_bin,
+                &filter,
+                &limits,
+            )
+            .await;
+
+            if let Err(e) = &result {
+                log::error!("Failed to execute bulk import plan: {}", e);
+            }
+
+            result
+        })
+}
@@ -10,8 +10,11 @@ use crate::{
     definitions::import_request::{
         AssetImportRequest, PreAvatar, PreAvatarWearable, PreOtherAsset, PreWorldObject,
     },
-    importer::import_wrapper::{
-        import_avatar, import_avatar_wearable, import_other_asset, import_world_object,
+    importer::{
+        fileutils::{build_extract_limits, build_import_filter},
+        import_wrapper::{
+            import_avatar, import_avatar_wearable, import_other_asset, import_world_object,
+        },
     },
 };
 
@@ -30,31 +33,41 @@ pub async fn request_avatar_import(
     let cloned_basic_store = (*basic_store).clone();
     let cloned_app_handle = (*handle).clone();
 
-    let (zip_extraction, use_trash_bin) = {
+    let (zip_extraction, use_trash_bin, filter, limits) = {
         let preference = preference.lock().await;
-        (preference.zip_extraction, preference.use_trash_bin)
-    };
-
-    let task = task_container.lock().await.run(async move {
-        let basic_store = cloned_basic_store.lock().await;
-        let result = import_avatar(
-            &basic_store,
-            request,
-            &cloned_app_handle,
-            zip_extraction,
-            use_trash_bin,
+        (
+            preference.zip_extraction,
+            preference.use_trash_bin,
+            build_import_filter(&preference)?,
+            build_extract_limits(&preference),
         )
-        .await;
-
-        if let Err(e) = result {
-            log::error!("Failed to import avatar: {}", e);
-            return Err(e);
-        }
-
-        log::info!("Successfully imported avatar: {:?}", result.unwrap());
+    };
 
-        Ok(())
-    });
+    let task = task_container
+        .lock()
+        .await
+        .run_with_id(move |task_id| async move {
+            let result = import_avatar(
+                &cloned_basic_store,
+                request,
+                &cloned_app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                &filter,
+                &limits,
+            )
+            .await;
+
+            if let Err(e) = result {
+                log::error!("Failed to import avatar: {}", e);
+                return Err(e);
+            }
+
+            log::info!("Successfully imported avatar: {:?}", result.unwrap());
+
+            Ok(())
+        });
 
     task
 }
@@ -77,33 +90,43 @@ pub async fn request_avatar_wearable_import(
     let cloned_basic_store = (*basic_store).clone();
     let cloned_app_handle = (*handle).clone();
 
-    let (zip_extraction, use_trash_bin) = {
+    let (zip_extraction, use_trash_bin, filter, limits) = {
         let preference = preference.lock().await;
-        (preference.zip_extraction, preference.use_trash_bin)
-    };
-
-    let task = task_container.lock().await.run(async move {
-        let basic_store = cloned_basic_store.lock().await;
-        let result = import_avatar_wearable(
-            &basic_store,
-            request,
-            &cloned_app_handle,
-            zip_extraction,
-            use_trash_bin,
+        (
+            preference.zip_extraction,
+            preference.use_trash_bin,
+            build_import_filter(&preference)?,
+            build_extract_limits(&preference),
         )
-        .await;
-
-        if let Err(e) = result {
-            log::error!("Failed to import avatar wearable: {}", e);
-            return Err(e);
-        }
+    };
 
-        log::info!(
-            "Successfully imported avatar wearable: {:?}",
-            result.unwrap()
-        );
-        Ok(())
-    });
+    let task = task_container
+        .lock()
+        .await
+        .run_with_id(move |task_id| async move {
+            let result = import_avatar_wearable(
+                &cloned_basic_store,
+                request,
+                &cloned_app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                &filter,
+                &limits,
+            )
+            .await;
+
+            if let Err(e) = result {
+                log::error!("Failed to import avatar wearable: {}", e);
+                return Err(e);
+            }
+
+            log::info!(
+                "Successfully imported avatar wearable: {:?}",
+                result.unwrap()
+            );
+            Ok(())
+        });
 
     task
 }
@@ -123,30 +146,40 @@ pub async fn request_world_object_import(
     let cloned_basic_store = (*basic_store).clone();
     let cloned_app_handle = (*handle).clone();
 
-    let (zip_extraction, use_trash_bin) = {
+    let (zip_extraction, use_trash_bin, filter, limits) = {
         let preference = preference.lock().await;
-        (preference.zip_extraction, preference.use_trash_bin)
-    };
-
-    let task = task_container.lock().await.run(async move {
-        let basic_store = cloned_basic_store.lock().await;
-        let result = import_world_object(
-            &basic_store,
-            request,
-            &cloned_app_handle,
-            zip_extraction,
-            use_trash_bin,
+        (
+            preference.zip_extraction,
+            preference.use_trash_bin,
+            build_import_filter(&preference)?,
+            build_extract_limits(&preference),
         )
-        .await;
-
-        if let Err(e) = result {
-            log::error!("Failed to import world object: {}", e);
-            return Err(e);
-        }
+    };
 
-        log::info!("Successfully imported world object: {:?}", result.unwrap());
-        Ok(())
-    });
+    let task = task_container
+        .lock()
+        .await
+        .run_with_id(move |task_id| async move {
+            let result = import_world_object(
+                &cloned_basic_store,
+                request,
+                &cloned_app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                &filter,
+                &limits,
+            )
+            .await;
+
+            if let Err(e) = result {
+                log::error!("Failed to import world object: {}", e);
+                return Err(e);
+            }
+
+            log::info!("Successfully imported world object: {:?}", result.unwrap());
+            Ok(())
+        });
 
     task
 }
@@ -166,30 +199,40 @@ pub async fn request_other_asset_import(
     let cloned_basic_store = (*basic_store).clone();
     let cloned_app_handle = (*handle).clone();
 
-    let (zip_extraction, use_trash_bin) = {
+    let (zip_extraction, use_trash_bin, filter, limits) = {
         let preference = preference.lock().await;
-        (preference.zip_extraction, preference.use_trash_bin)
-    };
-
-    let task = task_container.lock().await.run(async move {
-        let basic_store = cloned_basic_store.lock().await;
-        let result = import_other_asset(
-            &basic_store,
-            request,
-            &cloned_app_handle,
-            zip_extraction,
-            use_trash_bin,
+        (
+            preference.zip_extraction,
+            preference.use_trash_bin,
+            build_import_filter(&preference)?,
+            build_extract_limits(&preference),
         )
-        .await;
-
-        if let Err(e) = result {
-            log::error!("Failed to import other asset: {}", e);
-            return Err(e);
-        }
+    };
 
-        log::info!("Successfully imported other asset: {:?}", result.unwrap());
-        Ok(())
-    });
+    let task = task_container
+        .lock()
+        .await
+        .run_with_id(move |task_id| async move {
+            let result = import_other_asset(
+                &cloned_basic_store,
+                request,
+                &cloned_app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                &filter,
+                &limits,
+            )
+            .await;
+
+            if let Err(e) = result {
+                log::error!("Failed to import other asset: {}", e);
+                return Err(e);
+            }
+
+            log::info!("Successfully imported other asset: {:?}", result.unwrap());
+            Ok(())
+        });
 
     task
 }
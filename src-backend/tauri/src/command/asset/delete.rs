@@ -1,15 +1,19 @@
 use std::sync::Arc;
 
+use booth::BoothUpdateTracker;
 use model::preference::PreferenceStore;
 use storage::{asset_storage::AssetStorage, delete::delete_asset};
 use tauri::{State, async_runtime::Mutex};
 use uuid::Uuid;
 
+use crate::booth_update::remove_booth_item_record;
+
 #[tauri::command]
 #[specta::specta]
 pub async fn request_asset_deletion(
     basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
     preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    booth_update_tracker: State<'_, Arc<Mutex<BoothUpdateTracker>>>,
     id: Uuid,
 ) -> Result<(), String> {
     log::info!("Deleting asset with id: {:?}", id);
@@ -26,6 +30,7 @@ pub async fn request_asset_deletion(
 
     if result.is_ok() {
         log::info!("Successfully deleted asset: {:?}", id);
+        remove_booth_item_record(&booth_update_tracker, id).await;
     } else {
         log::error!("Failed to delete asset: {:?}", result);
     }
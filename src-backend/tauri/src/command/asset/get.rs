@@ -1,5 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
+use booth::BoothUpdateTracker;
 use model::{AssetSummary, SortBy};
 use storage::asset_storage::AssetStorage;
 use tauri::{State, async_runtime::Mutex};
@@ -44,6 +45,7 @@ pub async fn get_asset(
 #[specta::specta]
 pub async fn get_sorted_asset_summaries(
     basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    booth_update_tracker: State<'_, Arc<Mutex<BoothUpdateTracker>>>,
     sort_by: SortBy,
 ) -> Result<Vec<AssetSummary>, String> {
     let mut created_at_map: HashMap<Uuid, i64> = HashMap::new();
@@ -109,6 +111,8 @@ pub async fn get_sorted_asset_summaries(
             });
     }
 
+    mark_update_available(&booth_update_tracker, &mut result).await;
+
     match sort_by {
         SortBy::Name => result.sort_by(|a, b| a.name.cmp(&b.name)),
         SortBy::Creator => result.sort_by(|a, b| a.creator.cmp(&b.creator)),
@@ -132,6 +136,7 @@ pub async fn get_sorted_asset_summaries(
 #[specta::specta]
 pub async fn get_asset_displays_by_booth_id(
     basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    booth_update_tracker: State<'_, Arc<Mutex<BoothUpdateTracker>>>,
     booth_item_id: u64,
 ) -> Result<Vec<AssetSummary>, String> {
     let basic_store = basic_store.lock().await;
@@ -181,5 +186,18 @@ pub async fn get_asset_displays_by_booth_id(
             }
         });
 
+    mark_update_available(&booth_update_tracker, &mut result).await;
+
     Ok(result)
 }
+
+async fn mark_update_available(
+    booth_update_tracker: &Arc<Mutex<BoothUpdateTracker>>,
+    summaries: &mut [AssetSummary],
+) {
+    let tracker = booth_update_tracker.lock().await;
+
+    for summary in summaries {
+        summary.update_available = tracker.is_update_available(summary.id);
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use booth::BoothUpdateTracker;
+use model::preference::PreferenceStore;
+use storage::{asset_storage::AssetStorage, integrity};
+use task::TaskContainer;
+use tauri::{AppHandle, State, async_runtime::Mutex};
+use tauri_specta::Event;
+use uuid::Uuid;
+
+use crate::{
+    booth_update::remove_booth_item_record, definitions::entities::IntegrityCheckReported,
+};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn execute_integrity_check_task(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    task_container: State<'_, Arc<Mutex<TaskContainer>>>,
+    app_handle: State<'_, AppHandle>,
+) -> Result<Uuid, String> {
+    let cloned_basic_store = (*basic_store).clone();
+    let cloned_app_handle = (*app_handle).clone();
+
+    task_container
+        .lock()
+        .await
+        .run_with_id(move |task_id| async move {
+            let report = {
+                let basic_store = cloned_basic_store.lock().await;
+                integrity::check_integrity(&basic_store).await
+            };
+
+            let report = match report {
+                Ok(report) => report,
+                Err(e) => {
+                    log::error!("Failed to check integrity of data dir: {}", e);
+                    return Err(e);
+                }
+            };
+
+            if !report.is_empty() {
+                log::warn!("Integrity check found inconsistencies: {:?}", report);
+            }
+
+            let event = IntegrityCheckReported { task_id, report };
+
+            if let Err(e) = event.emit(&cloned_app_handle) {
+                log::error!("Failed to emit IntegrityCheckReported event: {}", e);
+            }
+
+            Ok(())
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn adopt_orphan_data_dir(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    dir_name: String,
+) -> Result<Uuid, String> {
+    let basic_store = basic_store.lock().await;
+
+    let result = integrity::adopt_orphan_data_dir(&basic_store, &dir_name).await;
+
+    match &result {
+        Ok(id) => log::info!("Adopted orphan data dir {} as asset {}", dir_name, id),
+        Err(e) => log::error!("Failed to adopt orphan data dir: {}", e),
+    }
+
+    result
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_dead_asset_entry(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    booth_update_tracker: State<'_, Arc<Mutex<BoothUpdateTracker>>>,
+    id: Uuid,
+) -> Result<(), String> {
+    let use_trash_bin = preference.lock().await.use_trash_bin;
+    let basic_store = basic_store.lock().await;
+
+    let result = integrity::remove_dead_entry(&basic_store, id, use_trash_bin).await;
+
+    match &result {
+        Ok(_) => {
+            log::info!("Removed dead asset entry: {}", id);
+            remove_booth_item_record(&booth_update_tracker, id).await;
+        }
+        Err(e) => log::error!("Failed to remove dead asset entry: {}", e),
+    }
+
+    result
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn trash_orphan_image(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    filename: String,
+) -> Result<(), String> {
+    let basic_store = basic_store.lock().await;
+
+    let result = integrity::trash_unreferenced_image(&basic_store, &filename).await;
+
+    match &result {
+        Ok(_) => log::info!("Moved orphan image to trash: {}", filename),
+        Err(e) => log::error!("Failed to trash orphan image: {}", e),
+    }
+
+    result
+}
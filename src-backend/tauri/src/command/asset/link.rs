@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use storage::{asset_storage::AssetStorage, definitions::BrokenLinkedAsset};
+use tauri::{State, async_runtime::Mutex};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_broken_linked_assets(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+) -> Result<Vec<BrokenLinkedAsset>, String> {
+    let broken = basic_store.lock().await.find_broken_linked_assets().await;
+
+    for asset in &broken {
+        log::warn!(
+            "Linked directory of asset not found (ID = {:?}): {}",
+            asset.id,
+            asset.path
+        );
+    }
+
+    Ok(broken)
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use storage::{
+    asset_storage::{AssetStorage, LibraryLockStatus},
+    definitions::ExternalChangeReport,
+};
+use tauri::{State, async_runtime::Mutex};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_library_lock_status(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+) -> Result<LibraryLockStatus, String> {
+    Ok(basic_store.lock().await.lock_status())
+}
+
+/// 他のインスタンスが保持しているロックを奪い、書き込み可能にする
+///
+/// 相手のインスタンスが異常終了していることをユーザーが確認した上で呼び出す
+#[tauri::command]
+#[specta::specta]
+pub async fn force_acquire_library_lock(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+) -> Result<LibraryLockStatus, String> {
+    let mut basic_store = basic_store.lock().await;
+
+    let status = basic_store.take_over_lock().map_err(|e| {
+        log::error!("Failed to take over library lock: {}", e);
+        e
+    })?;
+
+    // 読み取り専用の間に他のインスタンスが保存した内容を取り込んでおく
+    if let Err(e) = basic_store.reload_if_modified().await {
+        log::error!("Failed to reload modified metadata: {}", e);
+    }
+
+    Ok(status)
+}
+
+/// 他のインスタンスによってメタデータが変更されていれば読み込み直す
+#[tauri::command]
+#[specta::specta]
+pub async fn reload_library_if_modified(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+) -> Result<ExternalChangeReport, String> {
+    basic_store
+        .lock()
+        .await
+        .reload_if_modified()
+        .await
+        .map_err(|e| {
+            log::error!("Failed to reload modified metadata: {}", e);
+            e
+        })
+}
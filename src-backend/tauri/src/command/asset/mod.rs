@@ -1,8 +1,14 @@
 pub mod adapter;
+pub mod bulk;
 pub mod create;
 pub mod delete;
 pub mod filter;
 pub mod get;
+pub mod integrity;
+pub mod link;
+pub mod lock;
+pub mod purchase;
+pub mod queue;
 pub mod statistics;
 pub mod status;
 pub mod update;
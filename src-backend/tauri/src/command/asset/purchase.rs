@@ -0,0 +1,48 @@
+use std::{path::PathBuf, sync::Arc};
+
+use booth::BoothPurchasedItem;
+use storage::asset_storage::AssetStorage;
+use tauri::{State, async_runtime::Mutex};
+use uuid::Uuid;
+
+use crate::importer::purchase::{self, BoothPurchaseHistoryEntry};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn read_booth_purchase_history(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    path: PathBuf,
+) -> Result<Vec<BoothPurchaseHistoryEntry>, String> {
+    log::info!("Reading BOOTH purchase history from: {}", path.display());
+
+    let content = std::fs::read(&path).map_err(|e| {
+        log::error!("Failed to read BOOTH purchase history: {}", e);
+        format!("Failed to read BOOTH purchase history: {}", e)
+    })?;
+    let content = String::from_utf8_lossy(&content);
+
+    let registered_booth_item_ids = basic_store.lock().await.get_used_booth_item_ids().await;
+    let entries = purchase::read_purchase_history(&content, &registered_booth_item_ids);
+
+    log::info!("Found {} items in BOOTH purchase history", entries.len());
+
+    Ok(entries)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_not_downloaded_assets(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    items: Vec<BoothPurchasedItem>,
+) -> Result<Vec<Uuid>, String> {
+    log::info!("Creating {} not downloaded assets", items.len());
+
+    let basic_store = basic_store.lock().await;
+
+    purchase::register_not_downloaded_assets(&basic_store, items)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to create not downloaded assets: {}", e);
+            e
+        })
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use storage::asset_storage::AssetStorage;
+use tauri::{AppHandle, State, async_runtime::Mutex};
+use uuid::Uuid;
+
+use crate::importer::queue::{
+    ImportQueue, ImportQueueItem, QueuedImportRequest, emit_queue_updated, process_import_queue,
+};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn enqueue_asset_import(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    queue: State<'_, Arc<Mutex<ImportQueue>>>,
+    handle: State<'_, AppHandle>,
+    mut request: QueuedImportRequest,
+) -> Result<Uuid, String> {
+    log::debug!("Enqueueing asset import: {:?}", request);
+
+    let images_dir = basic_store.lock().await.data_dir().join("images");
+    request.fix_temp_image(&images_dir).await?;
+
+    let id = {
+        let mut queue = queue.lock().await;
+        let id = queue.enqueue(request);
+        queue.save()?;
+        id
+    };
+
+    process_import_queue(&handle).await.map_err(|e| {
+        log::error!("Failed to process import queue: {}", e);
+        e
+    })?;
+
+    Ok(id)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_import_queue(
+    queue: State<'_, Arc<Mutex<ImportQueue>>>,
+) -> Result<Vec<ImportQueueItem>, String> {
+    Ok(queue.lock().await.items().clone())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn retry_import_queue_item(
+    queue: State<'_, Arc<Mutex<ImportQueue>>>,
+    handle: State<'_, AppHandle>,
+    id: Uuid,
+) -> Result<(), String> {
+    {
+        let mut queue = queue.lock().await;
+        queue.retry(id)?;
+        queue.save()?;
+    }
+
+    process_import_queue(&handle).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_import_queue_item(
+    queue: State<'_, Arc<Mutex<ImportQueue>>>,
+    handle: State<'_, AppHandle>,
+    id: Uuid,
+) -> Result<(), String> {
+    let items = {
+        let mut queue = queue.lock().await;
+        queue.remove(id)?;
+        queue.save()?;
+        queue.items().clone()
+    };
+
+    emit_queue_updated(&handle, items);
+    Ok(())
+}
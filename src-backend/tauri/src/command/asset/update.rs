@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
-use model::preference::PreferenceStore;
+use model::{AssetDataLocation, preference::PreferenceStore};
 use storage::{asset_storage::AssetStorage, definitions::AssetUpdatePayload};
 use tauri::{State, async_runtime::Mutex};
+use uuid::Uuid;
 
 #[tauri::command]
 #[specta::specta]
@@ -60,3 +61,51 @@ pub async fn update_asset(
         Err(e) => Err(e),
     }
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_asset_data_location(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    id: Uuid,
+    location: AssetDataLocation,
+) -> Result<(), String> {
+    log::info!(
+        "Setting data location of asset (ID = {:?}) to: {:?}",
+        id,
+        location
+    );
+
+    let basic_store = basic_store.lock().await;
+
+    if !location.has_local_data() {
+        let asset_data_dir = basic_store.data_dir().join("data").join(id.to_string());
+
+        // ローカルにファイルが残っている場合は、ファイルを持たないアセットにできない
+        let has_entries = std::fs::read_dir(&asset_data_dir)
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false);
+
+        if has_entries {
+            let err = format!("Asset still has local data: {}", asset_data_dir.display());
+            log::error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    // リンク先を変更する場合は、新しい参照先のフォルダが存在することを確認する
+    if let AssetDataLocation::Linked { path } = &location {
+        if !std::path::Path::new(path).is_dir() {
+            let err = format!("Linked directory does not exist: {}", path);
+            log::error!("{}", err);
+            return Err(err);
+        }
+    }
+
+    basic_store
+        .set_data_location(id, location)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to set data location: {}", e);
+            e
+        })
+}
@@ -1,13 +1,26 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-use booth::{BoothAssetInfo, BoothFetcher, PximgResolver};
+use booth::{
+    AssetClassification, BoothAssetInfo, BoothFetcher, BoothMetadataDiff, BoothMetadataUpdate,
+    BoothShopItemEntry, BoothUpdateTracker, PximgResolver,
+};
+use file::CropRect;
 use model::preference::PreferenceStore;
-use tauri::{State, async_runtime::Mutex};
+use storage::asset_storage::AssetStorage;
+use task::TaskContainer;
+use tauri::{AppHandle, State, async_runtime::Mutex};
+use uuid::Uuid;
+
+use crate::{
+    booth_refresh, booth_update, command::suggest::get::get_all_asset_tags,
+    importer::classifier::build_asset_classifier,
+};
 
 #[tauri::command]
 #[specta::specta]
 pub async fn get_asset_info_from_booth(
     booth_fetcher: State<'_, Mutex<BoothFetcher>>,
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
     booth_item_id: u64,
 ) -> Result<BoothAssetInfo, String> {
     log::info!(
@@ -15,10 +28,14 @@ pub async fn get_asset_info_from_booth(
         booth_item_id
     );
 
-    let result = {
-        let mut fetcher = booth_fetcher.lock().await;
-        fetcher.fetch(booth_item_id).await
-    };
+    let name_source = preference.lock().await.booth_name_source;
+
+    let fetcher = booth_fetcher.lock().await.clone();
+    // インポート画面では name をそのまま使うため、設定に応じた名前にしておく
+    let result = fetcher.fetch(booth_item_id).await.map(|mut info| {
+        info.name = info.name_for(name_source).to_string();
+        info
+    });
 
     if let Ok(info) = &result {
         log::info!(
@@ -33,6 +50,155 @@ pub async fn get_asset_info_from_booth(
     result.map_err(|e| e.to_string())
 }
 
+/// BOOTH のタグを、設定された規則と登録済みのタグをもとに、インポート時に付けるタグに変換する
+#[tauri::command]
+#[specta::specta]
+pub async fn map_booth_tags(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    booth_tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let known_tags = get_all_asset_tags(basic_store, None)
+        .await?
+        .into_iter()
+        .map(|entry| entry.value)
+        .collect::<HashSet<_>>();
+    let mappings = preference.lock().await.booth_tag_mappings.clone();
+
+    Ok(booth::map_booth_tags(&booth_tags, &mappings, &known_tags))
+}
+
+/// BOOTH の情報と登録済みのアセットから、アセットの種類とカテゴリを推定する
+#[tauri::command]
+#[specta::specta]
+pub async fn suggest_asset_classification(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    booth_fetcher: State<'_, Mutex<BoothFetcher>>,
+    booth_item_id: u64,
+) -> Result<AssetClassification, String> {
+    let fetcher = booth_fetcher.lock().await.clone();
+    let info = fetcher.fetch(booth_item_id).await.map_err(|e| {
+        let err = format!("Failed to fetch BOOTH info for classification: {}", e);
+        log::error!("{}", err);
+        err
+    })?;
+
+    let classifier = build_asset_classifier(&*basic_store.lock().await).await;
+
+    Ok(classifier.classify(&info))
+}
+
+/// ショップに出品されているアイテムを取得し、登録済みのアセットと紐づいているかを返す
+///
+/// `shop` にはショップの URL (`https://shop.booth.pm/`) かサブドメインを渡す
+#[tauri::command]
+#[specta::specta]
+pub async fn get_booth_shop_items(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    booth_fetcher: State<'_, Mutex<BoothFetcher>>,
+    shop: String,
+) -> Result<Vec<BoothShopItemEntry>, String> {
+    let shop_subdomain = booth::parse_shop_subdomain(&shop).ok_or_else(|| {
+        let err = format!("Invalid BOOTH shop: {}", shop);
+        log::error!("{}", err);
+        err
+    })?;
+
+    log::info!("Fetching BOOTH shop items (shop = {})", shop_subdomain);
+
+    let fetcher = booth_fetcher.lock().await.clone();
+    let items = fetcher
+        .fetch_shop_items(&shop_subdomain)
+        .await
+        .map_err(|e| {
+            let err = format!("Failed to fetch BOOTH shop items: {}", e);
+            log::error!("{}", err);
+            err
+        })?;
+
+    let linked_assets = basic_store.lock().await.get_booth_linked_assets().await;
+
+    Ok(booth::cross_reference_shop_items(items, &linked_assets))
+}
+
+/// 保存された BOOTH のアイテム情報のキャッシュを削除する
+#[tauri::command]
+#[specta::specta]
+pub async fn purge_booth_cache(
+    booth_fetcher: State<'_, Mutex<BoothFetcher>>,
+) -> Result<(), String> {
+    booth_fetcher.lock().await.purge_cache().map_err(|e| {
+        let err = format!("Failed to purge BOOTH cache: {}", e);
+        log::error!("{}", err);
+        err
+    })
+}
+
+/// 所有しているアセットが BOOTH で更新されていないかを、バックグラウンドで確認する
+#[tauri::command]
+#[specta::specta]
+pub async fn check_booth_item_updates(
+    task_container: State<'_, Arc<Mutex<TaskContainer>>>,
+    handle: State<'_, AppHandle>,
+) -> Result<Uuid, String> {
+    let cloned_app_handle = (*handle).clone();
+
+    task_container.lock().await.run(async move {
+        booth_update::check_booth_item_updates(&cloned_app_handle)
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                let err = format!("Failed to check BOOTH item updates: {}", e);
+                log::error!("{}", err);
+                err
+            })
+    })
+}
+
+/// 更新を確認済みにし、「更新あり」の表示を消す
+#[tauri::command]
+#[specta::specta]
+pub async fn acknowledge_booth_item_update(
+    booth_update_tracker: State<'_, Arc<Mutex<BoothUpdateTracker>>>,
+    id: Uuid,
+) -> Result<(), String> {
+    let mut tracker = booth_update_tracker.lock().await;
+
+    tracker.acknowledge(id)?;
+    tracker.save().map_err(|e| {
+        log::error!("{}", e);
+        e
+    })
+}
+
+/// BOOTH から情報を取得し直し、登録済みのアセットとの差分を項目ごとに返す
+///
+/// `asset_ids` が空の場合は、BOOTH のアイテムが紐づいているすべてのアセットを対象にする
+#[tauri::command]
+#[specta::specta]
+pub async fn get_booth_metadata_diffs(
+    handle: State<'_, AppHandle>,
+    asset_ids: Vec<Uuid>,
+) -> Result<Vec<BoothMetadataDiff>, String> {
+    booth_refresh::get_booth_metadata_diffs(&handle, asset_ids)
+        .await
+        .map_err(|e| {
+            let err = format!("Failed to refresh BOOTH metadata: {}", e);
+            log::error!("{}", err);
+            err
+        })
+}
+
+/// 選択された項目を BOOTH の情報で上書きし、更新できたアセットの ID を返す
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_booth_metadata(
+    handle: State<'_, AppHandle>,
+    updates: Vec<BoothMetadataUpdate>,
+) -> Result<Vec<Uuid>, String> {
+    booth_refresh::apply_booth_metadata(&handle, updates).await
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn resolve_pximg_filename(
@@ -50,6 +216,51 @@ pub async fn resolve_pximg_filename(
     })
 }
 
+/// サムネイルを選べるように、BOOTH の画像をすべて縮小せずに取得する
+///
+/// 取得できなかった画像は `None` になる
+#[tauri::command]
+#[specta::specta]
+pub async fn download_pximg_previews(
+    pximg_resolver: State<'_, Arc<Mutex<PximgResolver>>>,
+    urls: Vec<String>,
+) -> Result<Vec<Option<String>>, String> {
+    let mut resolver = pximg_resolver.lock().await;
+    let mut filenames = Vec::with_capacity(urls.len());
+
+    for url in urls {
+        match resolver.download_preview(&url).await {
+            Ok(filename) => filenames.push(Some(filename)),
+            Err(e) => {
+                log::error!("Failed to download pximg preview ({}): {}", url, e);
+                filenames.push(None);
+            }
+        }
+    }
+
+    Ok(filenames)
+}
+
+/// 選んだ画像の指定された範囲をサムネイルにする
+#[tauri::command]
+#[specta::specta]
+pub async fn resolve_pximg_filename_with_crop(
+    pximg_resolver: State<'_, Arc<Mutex<PximgResolver>>>,
+    url: String,
+    crop: Option<CropRect>,
+    keep_original: bool,
+) -> Result<String, String> {
+    let result = {
+        let mut resolver = pximg_resolver.lock().await;
+        resolver.resolve_with_crop(&url, crop, keep_original).await
+    };
+
+    result.map_err(|e| {
+        log::error!("Failed to resolve pximg filename with crop: {}", e);
+        e.to_string()
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_booth_url(
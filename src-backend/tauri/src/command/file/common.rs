@@ -5,13 +5,14 @@ use model::preference::PreferenceStore;
 use storage::{
     asset_storage::{AssetStorage, MigrateResult},
     find::{FileInfo, find_unitypackage},
+    migration::{MigrationVerification, PendingMigration, find_pending_migration},
 };
 use task::TaskContainer;
 use tauri::{AppHandle, State, async_runtime::Mutex};
 use tauri_specta::Event;
 use uuid::Uuid;
 
-use crate::definitions::entities::ProgressEvent;
+use crate::{definitions::entities::ProgressEvent, metadata_sync::restart_metadata_watcher};
 
 #[tauri::command]
 #[specta::specta]
@@ -39,9 +40,11 @@ pub async fn list_unitypackage_files(
     basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
     id: Uuid,
 ) -> Result<HashMap<String, Vec<FileInfo>>, String> {
-    let mut dir = basic_store.lock().await.data_dir();
-    dir.push("data");
-    dir.push(id.to_string());
+    let dir = basic_store.lock().await.resolve_asset_data_dir(id).await;
+
+    let Some(dir) = dir else {
+        return Ok(HashMap::new());
+    };
 
     if !dir.exists() {
         let err = format!("Directory does not exist: {}", dir.display());
@@ -62,6 +65,7 @@ pub async fn migrate_data_dir(
     handle: State<'_, AppHandle>,
     new_path: PathBuf,
     migrate_data: bool,
+    verification: Option<MigrationVerification>,
 ) -> Result<Uuid, String> {
     log::info!(
         "Data directory migration triggered (dest: {})",
@@ -74,7 +78,13 @@ pub async fn migrate_data_dir(
         return Err(err);
     }
 
-    if migrate_data && new_path.exists() {
+    // 同じデータフォルダからの中断された移行が残っている場合は、空でなくても再開できる
+    let resumable = match find_pending_migration(&new_path)? {
+        Some(pending) => pending.source == basic_store.lock().await.data_dir(),
+        None => false,
+    };
+
+    if migrate_data && !resumable && new_path.exists() {
         let read_dir = new_path
             .read_dir()
             .map_err(|e| format!("Failed to read directory: {}", e))?;
@@ -109,7 +119,11 @@ pub async fn migrate_data_dir(
             };
 
             let result = basic_store
-                .migrate_data_dir(&new_path, progress_callback)
+                .migrate_data_dir(
+                    &new_path,
+                    verification.unwrap_or_default(),
+                    progress_callback,
+                )
                 .await
                 .map_err(|e| {
                     let msg = format!("Failed to migrate data: {:?}", e);
@@ -125,10 +139,8 @@ pub async fn migrate_data_dir(
         }
 
         let mut preference = cloned_preference.lock().await;
-        let mut new_preference = preference.clone();
-        new_preference.set_data_dir(new_path.clone());
+        preference.set_data_dir(new_path.clone());
 
-        preference.overwrite(&new_preference);
         loader::wrapper::save_preference_store(&preference).map_err(|e| e.to_string())?;
 
         cloned_pximg_resolver
@@ -143,6 +155,8 @@ pub async fn migrate_data_dir(
             }
         }
 
+        restart_metadata_watcher(&cloned_app_handle, &new_path).await;
+
         log::info!(
             "Successfully changed data directory to: {}",
             new_path.display()
@@ -154,6 +168,17 @@ pub async fn migrate_data_dir(
     task
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pending_data_dir_migration(
+    new_path: PathBuf,
+) -> Result<Option<PendingMigration>, String> {
+    find_pending_migration(&new_path).map_err(|e| {
+        log::error!("Failed to read pending migration: {}", e);
+        e
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_image_absolute_path(
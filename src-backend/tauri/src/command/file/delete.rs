@@ -17,6 +17,21 @@ pub async fn delete_entry_from_asset_data_dir(
 ) -> Result<(), String> {
     let path = {
         let store = basic_store.lock().await;
+
+        store.ensure_writable().map_err(|e| {
+            log::error!("{}", e);
+            e
+        })?;
+
+        // リンクされたアセットの参照先のファイルは削除しない
+        let data_location = store.get_data_location(asset_id).await;
+
+        if !data_location.is_some_and(|location| location.has_local_data()) {
+            let err = format!("Asset has no managed data directory: {}", asset_id);
+            log::error!("{}", err);
+            return Err(err);
+        }
+
         store
             .data_dir()
             .join("data")
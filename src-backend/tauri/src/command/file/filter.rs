@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use model::preference::{ImportFilterRules, PreferenceStore};
+use tauri::{State, async_runtime::Mutex};
+
+use crate::importer::fileutils::preview_filtered_entries;
+
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_import_filter(
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    rules: ImportFilterRules,
+    paths: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let zip_extraction = preference.lock().await.zip_extraction;
+
+    let filter = rules.build_filter().map_err(|e| {
+        let err = format!("Invalid import filter: {}", e);
+        log::error!("{}", err);
+        err
+    })?;
+
+    let mut excluded = vec![];
+
+    for path in paths {
+        let entries = preview_filtered_entries(&path, zip_extraction, &filter).await?;
+        excluded.extend(entries.into_iter().map(|entry| format!("{path}: {entry}")));
+    }
+
+    Ok(excluded)
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use file::modify_guard::{self, DeletionGuard};
 use model::preference::PreferenceStore;
@@ -53,6 +57,30 @@ pub async fn optimize_and_import_image(
     Ok(filename)
 }
 
+/// 詳細表示用に元の画像を残している場合、画像フォルダからの相対パスを返す
+#[tauri::command]
+#[specta::specta]
+pub async fn get_original_image_path(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    filename: String,
+) -> Result<Option<String>, String> {
+    let images_dir = basic_store.lock().await.data_dir().join("images");
+    let original = file::original_image_path(&images_dir, &filename);
+
+    // 画像フォルダの外を指していないか確認する
+    if original.parent() != Some(images_dir.join(file::ORIGINAL_IMAGES_DIR).as_path()) {
+        let err = format!("Invalid image filename: {}", filename);
+        log::error!("{}", err);
+        return Err(err);
+    }
+
+    if !original.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(format!("{}/{}", file::ORIGINAL_IMAGES_DIR, filename)))
+}
+
 #[derive(Serialize, specta::Type)]
 #[serde(rename_all = "camelCase")]
 pub struct ImageOptimizationResult {
@@ -107,6 +135,12 @@ pub async fn optimize_images_directory(
         err
     })? {
         let path = entry.path();
+
+        // 元の画像のフォルダは後で別に整理する
+        if path.is_dir() {
+            continue;
+        }
+
         let filename = match path.file_name() {
             Some(filename) => filename.to_string_lossy().to_string(),
             None => {
@@ -166,6 +200,14 @@ pub async fn optimize_images_directory(
         candidate_images.push(path);
     }
 
+    deleted += delete_unused_original_images(&images_dir, &used_filenames, dry_run, use_trash_bin)
+        .await
+        .map_err(|e| {
+            let err = format!("Failed to delete unused original images: {}", e);
+            log::error!("{}", err);
+            err
+        })?;
+
     let resizable = if dry_run {
         let result = file::optimize_thumbnails(candidate_images, dry_run, |_, _| {}).await;
 
@@ -217,7 +259,7 @@ pub async fn optimize_images_directory(
             log::error!("Failed to emit progress event: {}", e);
         }
 
-        store.replace_thumbnails(map).await.map_err(|e| {
+        store.replace_thumbnails(&map).await.map_err(|e| {
             let err = format!("Failed to replace thumbnails: {}", e);
             log::error!("{}", err);
             err
@@ -231,6 +273,20 @@ pub async fn optimize_images_directory(
                 None => "".to_string(),
             };
 
+            // 元の画像は縮小せず、新しいサムネイルの名前に付け替える
+            if let Some(new_filename) = map.get(&old_filename) {
+                let original = file::original_image_path(&images_dir, &old_filename);
+
+                if original.exists() {
+                    if let Err(e) = std::fs::rename(
+                        &original,
+                        file::original_image_path(&images_dir, new_filename),
+                    ) {
+                        log::error!("Failed to rename original image: {}", e);
+                    }
+                }
+            }
+
             let delete_error = if use_trash_bin {
                 modify_guard::trash_recursive(old, &DeletionGuard::new(&images_dir))
                     .map_err(|e| format!("Failed to trash old image: {}", e))
@@ -263,6 +319,54 @@ pub async fn optimize_images_directory(
     })
 }
 
+/// どのアセットのサムネイルにも対応しない、元の画像を削除する
+async fn delete_unused_original_images(
+    images_dir: &Path,
+    used_filenames: &HashSet<String>,
+    dry_run: bool,
+    use_trash_bin: bool,
+) -> Result<u32, String> {
+    let originals_dir = images_dir.join(file::ORIGINAL_IMAGES_DIR);
+
+    if !originals_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut deleted = 0;
+
+    let mut read_dir = tokio::fs::read_dir(&originals_dir)
+        .await
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    while let Some(entry) = read_dir
+        .next_entry()
+        .await
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+    {
+        let path = entry.path();
+        let filename = entry.file_name().to_string_lossy().to_string();
+
+        // 一時ファイルの場合や、使われているサムネイルに対応する場合はスキップ
+        if filename.starts_with("temp_") || used_filenames.contains(&filename) {
+            continue;
+        }
+
+        if !dry_run {
+            if use_trash_bin {
+                modify_guard::trash_recursive(&path, &DeletionGuard::new(images_dir))?;
+            } else {
+                modify_guard::delete_recursive_completely(&path, &DeletionGuard::new(images_dir))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        deleted += 1;
+    }
+
+    Ok(deleted)
+}
+
 fn create_dest_filename(temporary: bool) -> String {
     if temporary {
         format!("temp_{}.jpg", Uuid::new_v4().to_string())
@@ -1,11 +1,20 @@
+use file::ConflictPolicy;
 use model::preference::PreferenceStore;
 use std::sync::Arc;
 use storage::asset_storage::AssetStorage;
 use task::TaskContainer;
-use tauri::{State, async_runtime::Mutex};
+use tauri::{AppHandle, State, async_runtime::Mutex};
+use tauri_specta::Event;
 use uuid::Uuid;
 
-use crate::importer::import_wrapper::import_additional_data;
+use crate::{
+    definitions::entities::AdditionalImportReported,
+    importer::{
+        fileutils::{self, ImportConflict, build_extract_limits, build_import_filter},
+        import_wrapper::import_additional_data,
+        password::ZipPasswordBroker,
+    },
+};
 
 #[tauri::command]
 #[specta::specta]
@@ -13,29 +22,118 @@ pub async fn import_file_entries_to_asset(
     basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
     task_container: State<'_, Arc<Mutex<TaskContainer>>>,
     preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    handle: State<'_, AppHandle>,
     asset_id: Uuid,
     paths: Vec<String>,
+    policy: Option<ConflictPolicy>,
 ) -> Result<Vec<Uuid>, String> {
     let mut task_ids = vec![];
 
-    let zip_extraction = (*preference.lock().await).zip_extraction;
+    let (zip_extraction, filter, limits) = {
+        let preference = preference.lock().await;
+        (
+            preference.zip_extraction,
+            build_import_filter(&preference)?,
+            build_extract_limits(&preference),
+        )
+    };
+    let policy = policy.unwrap_or_default();
 
     for path in paths {
         let basic_store = (*basic_store).clone();
+        let app_handle = (*handle).clone();
+        let filter = filter.clone();
+        let limits = limits.clone();
 
-        let id = task_container.lock().await.run(async move {
-            let result = import_additional_data(basic_store, asset_id, path, zip_extraction).await;
+        let id = task_container
+            .lock()
+            .await
+            .run_with_id(move |task_id| async move {
+                let result = import_additional_data(
+                    basic_store,
+                    asset_id,
+                    &path,
+                    zip_extraction,
+                    &filter,
+                    &limits,
+                    policy,
+                    Some(&app_handle),
+                )
+                .await;
 
-            if let Err(e) = result {
-                log::error!("Failed to import additional data: {:?}", e);
-                return Err(e);
-            }
+                let entries = match result {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        log::error!("Failed to import additional data: {:?}", e);
+                        return Err(e);
+                    }
+                };
+
+                let event = AdditionalImportReported {
+                    task_id,
+                    asset_id,
+                    source: path,
+                    entries,
+                };
+
+                if let Err(e) = event.emit(&app_handle) {
+                    log::error!("Failed to emit AdditionalImportReported event: {}", e);
+                }
 
-            Ok(())
-        })?;
+                Ok(())
+            })?;
 
         task_ids.push(id);
     }
 
     Ok(task_ids)
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_import_conflicts(
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    asset_id: Uuid,
+    paths: Vec<String>,
+) -> Result<Vec<ImportConflict>, String> {
+    let (zip_extraction, filter) = {
+        let preference = preference.lock().await;
+        (preference.zip_extraction, build_import_filter(&preference)?)
+    };
+
+    let asset_data_dir = basic_store
+        .lock()
+        .await
+        .data_dir()
+        .join("data")
+        .join(asset_id.to_string());
+
+    let mut conflicts = vec![];
+
+    for path in paths {
+        let result =
+            fileutils::preview_import_conflicts(&path, &asset_data_dir, zip_extraction, &filter)
+                .await;
+
+        match result {
+            Ok(result) => conflicts.extend(result),
+            Err(e) => {
+                log::error!("Failed to preview import conflicts ({}): {}", path, e);
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn submit_zip_password(
+    broker: State<'_, Arc<Mutex<ZipPasswordBroker>>>,
+    request_id: Uuid,
+    password: Option<String>,
+) -> Result<(), String> {
+    broker.lock().await.resolve(request_id, password)
+}
@@ -10,9 +10,11 @@ pub async fn list_asset_dir_entry(
     basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
     id: Uuid,
 ) -> Result<Vec<SimplifiedDirEntry>, String> {
-    let dir = {
-        let store = basic_store.lock().await;
-        store.data_dir().join("data").join(id.to_string())
+    let dir = basic_store.lock().await.resolve_asset_data_dir(id).await;
+
+    // ファイルを持たないアセットは空として扱う
+    let Some(dir) = dir else {
+        return Ok(vec![]);
     };
 
     let result = list_top_files_and_directories(&dir).await;
@@ -1,5 +1,6 @@
 pub mod common;
 pub mod delete;
+pub mod filter;
 pub mod image;
 pub mod import;
 pub mod list;
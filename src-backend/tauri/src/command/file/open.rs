@@ -3,16 +3,21 @@ use std::{path::PathBuf, sync::Arc};
 use model::preference::PreferenceStore;
 use storage::asset_storage::AssetStorage;
 use tauri::{AppHandle, Manager, State, async_runtime::Mutex};
+use uuid::Uuid;
 
 #[tauri::command]
 #[specta::specta]
 pub async fn open_managed_dir(
     basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
-    id: String,
+    id: Uuid,
 ) -> Result<(), String> {
-    let mut path = basic_store.lock().await.data_dir();
-    path.push("data");
-    path.push(id);
+    let path = basic_store.lock().await.resolve_asset_data_dir(id).await;
+
+    let Some(path) = path else {
+        let err = format!("Asset has no data directory: {}", id);
+        log::error!("{}", err);
+        return Err(err);
+    };
 
     file::open_in_file_manager(&path)
 }
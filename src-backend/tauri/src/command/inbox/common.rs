@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use tauri::{State, async_runtime::Mutex};
+use uuid::Uuid;
+
+use crate::inbox::{PendingImport, PendingImportInbox};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pending_imports(
+    inbox: State<'_, Arc<Mutex<PendingImportInbox>>>,
+) -> Result<Vec<PendingImport>, String> {
+    Ok(inbox.lock().await.entries().clone())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn dismiss_pending_import(
+    inbox: State<'_, Arc<Mutex<PendingImportInbox>>>,
+    id: Uuid,
+) -> Result<(), String> {
+    inbox.lock().await.dismiss(id)
+}
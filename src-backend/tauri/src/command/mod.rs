@@ -4,6 +4,7 @@ mod asset;
 mod deep_link;
 mod external;
 mod file;
+mod inbox;
 mod language;
 mod preference;
 mod state;
@@ -20,8 +21,25 @@ pub fn generate_tauri_specta_builder() -> Builder<tauri::Wry> {
         asset::create::request_avatar_wearable_import,
         asset::create::request_world_object_import,
         asset::create::request_other_asset_import,
+        asset::queue::enqueue_asset_import, // インポートキューに追加
+        asset::queue::get_import_queue,
+        asset::queue::retry_import_queue_item,
+        asset::queue::remove_import_queue_item,
+        asset::bulk::create_bulk_import_plan, // フォルダから一括インポートする計画を作成
+        asset::bulk::execute_bulk_import_plan,
+        asset::purchase::read_booth_purchase_history, // BOOTHの購入履歴を読み取る
+        asset::purchase::create_not_downloaded_assets, // 未ダウンロードのアセットを登録する
         asset::delete::request_asset_deletion,
         asset::update::update_asset,
+        asset::update::set_asset_data_location, // アセットのデータの保存場所を設定
+        asset::link::get_broken_linked_assets,  // 参照先が見つからないリンクを検出
+        asset::integrity::execute_integrity_check_task, // データフォルダの整合性チェック
+        asset::integrity::adopt_orphan_data_dir, // 登録されていないデータをアセットとして登録
+        asset::integrity::remove_dead_asset_entry, // データが見つからないアセットを削除
+        asset::integrity::trash_orphan_image,   // 参照されていない画像をゴミ箱に移動
+        asset::lock::get_library_lock_status,   // ライブラリのロックの状態を取得
+        asset::lock::force_acquire_library_lock, // 他のインスタンスのロックを奪う
+        asset::lock::reload_library_if_modified, // 他のインスタンスによる変更を読み込む
         asset::filter::get_filtered_asset_ids,
         asset::status::get_load_status,
         asset::adapter::import_from_other_data_store,
@@ -40,7 +58,17 @@ pub fn generate_tauri_specta_builder() -> Builder<tauri::Wry> {
         suggest::get::get_other_asset_categories,
         // 外部API関連
         external::booth::get_asset_info_from_booth, // BOOTHからアセット情報を取得する
+        external::booth::map_booth_tags,            // BOOTHのタグをインポート時のタグに変換
+        external::booth::suggest_asset_classification, // BOOTHの情報からアセットの種類とカテゴリを推定する
+        external::booth::get_booth_shop_items,         // ショップの他のアイテムと所有状況を取得する
+        external::booth::purge_booth_cache,            // BOOTHのアイテム情報のキャッシュを削除
+        external::booth::check_booth_item_updates,     // 所有アセットのBOOTHでの更新を確認する
+        external::booth::acknowledge_booth_item_update, // BOOTHでの更新を確認済みにする
+        external::booth::get_booth_metadata_diffs, // BOOTHから取得し直した情報との差分を取得する
+        external::booth::apply_booth_metadata, // BOOTHの情報を選択した項目だけアセットに反映する
         external::booth::resolve_pximg_filename,
+        external::booth::download_pximg_previews, // サムネイルを選ぶためにBOOTHの画像をすべて取得する
+        external::booth::resolve_pximg_filename_with_crop, // 選んだ画像を切り抜いてサムネイルにする
         external::booth::get_booth_url,
         // アップデート関連
         update::common::check_for_update,
@@ -57,20 +85,33 @@ pub fn generate_tauri_specta_builder() -> Builder<tauri::Wry> {
         file::open::open_managed_dir,
         file::open::open_logs_dir,
         file::import::import_file_entries_to_asset, // 追加のファイル等をインポート
+        file::import::preview_import_conflicts,     // 追加時に競合するファイルを確認
+        file::import::submit_zip_password,          // zipのパスワードを入力
+        file::filter::preview_import_filter,        // インポート時に除外されるファイルを確認
         file::image::optimize_and_import_image,     // 画像新規作成
         file::image::optimize_images_directory,     // 登録済みの画像を最適化する
+        file::image::get_original_image_path,       // 詳細表示用に残した元の画像のパスを取得する
         file::list::list_asset_dir_entry,           // アセットのディレクトリの内容を取得
         file::delete::delete_entry_from_asset_data_dir, // アセットデータディレクトリからエントリを削除
         file::common::get_directory_path,               // 管理ディレクトリのパス取得
         file::common::list_unitypackage_files,          // unitypackage探索
         file::common::migrate_data_dir,                 // データフォルダ移行
+        file::common::get_pending_data_dir_migration,   // 中断されたデータフォルダ移行の取得
         file::common::get_image_absolute_path,          // 画像の絶対パス取得
         file::common::extract_non_existent_paths,       // 存在しないパスを抽出
+        // 監視フォルダ関連
+        inbox::common::get_pending_imports,
+        inbox::common::dismiss_pending_import,
         // 設定関連
         preference::common::require_initial_setup,
         preference::common::get_preferences,
         preference::common::set_preferences,
         preference::reset::reset_application,
+        preference::profile::create_library_profile, // ライブラリのプロファイルを作成
+        preference::profile::rename_library_profile,
+        preference::profile::remove_library_profile,
+        preference::profile::switch_library_profile, // プロファイルを切り替えて再読み込み
+        preference::profile::open_recent_library,    // 最近使ったライブラリを開く
         preference::logging::get_logs,
         // タスク関連
         task::commands::get_task_status,
@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
+use booth::BoothFetcher;
 use model::preference::PreferenceStore;
-use tauri::{State, async_runtime::Mutex};
+use tauri::{AppHandle, State, async_runtime::Mutex};
 
-use crate::definitions::entities::InitialSetup;
+use crate::{definitions::entities::InitialSetup, inbox::PendingImportInbox};
 
 #[tauri::command]
 #[specta::specta]
@@ -32,6 +33,9 @@ pub async fn get_preferences(
 #[specta::specta]
 pub async fn set_preferences(
     preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    inbox: State<'_, Arc<Mutex<PendingImportInbox>>>,
+    booth_fetcher: State<'_, Mutex<BoothFetcher>>,
+    handle: State<'_, AppHandle>,
     new_preference: PreferenceStore,
 ) -> Result<(), String> {
     let mut preference = preference.lock().await;
@@ -42,6 +46,51 @@ pub async fn set_preferences(
         return Err(err);
     }
 
+    if let Err(e) = new_preference.import_filter.build_filter() {
+        let err = format!("Invalid import filter: {}", e);
+        log::error!("{}", err);
+        return Err(err);
+    }
+
+    // 無制限は None で表すため、0 は受け付けない
+    let limits = new_preference.zip_extraction_limits;
+    if limits.max_total_size_mb == Some(0)
+        || limits.max_entry_count == Some(0)
+        || limits.max_compression_ratio == Some(0)
+    {
+        let err = "Zip extraction limits must be greater than zero".to_string();
+        log::error!("{}", err);
+        return Err(err);
+    }
+
+    if preference.watch_dirs != new_preference.watch_dirs {
+        inbox
+            .lock()
+            .await
+            .restart_watcher(&new_preference.watch_dirs, (*handle).clone());
+    }
+
+    if preference.booth_cache != new_preference.booth_cache {
+        booth_fetcher
+            .lock()
+            .await
+            .set_cache_config((&new_preference.booth_cache).into());
+    }
+
+    if preference.language != new_preference.language {
+        booth_fetcher
+            .lock()
+            .await
+            .set_language(new_preference.language.booth_lang_code());
+    }
+
+    if preference.booth_offline_mode != new_preference.booth_offline_mode {
+        booth_fetcher
+            .lock()
+            .await
+            .set_offline_mode(new_preference.booth_offline_mode);
+    }
+
     preference.overwrite(&new_preference);
     loader::wrapper::save_preference_store(&preference).map_err(|e| {
         let err = format!("Failed to save preferences: {}", e);
@@ -1,3 +1,4 @@
 pub mod common;
 pub mod logging;
+pub mod profile;
 pub mod reset;
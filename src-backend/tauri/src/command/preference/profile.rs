@@ -0,0 +1,232 @@
+use std::{path::PathBuf, sync::Arc};
+
+use booth::{BoothFetcher, PximgResolver};
+use model::preference::{LibraryProfile, PreferenceStore};
+use storage::asset_storage::AssetStorage;
+use tauri::{AppHandle, State, async_runtime::Mutex};
+use uuid::Uuid;
+
+use crate::{
+    importer::queue::{ImportQueue, ImportQueueItemStatus},
+    inbox::PendingImportInbox,
+    metadata_sync::restart_metadata_watcher,
+};
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_library_profile(
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    name: String,
+    data_dir_path: PathBuf,
+    own_preferences: bool,
+) -> Result<LibraryProfile, String> {
+    let mut preference = preference.lock().await;
+
+    let profile = preference.add_library_profile(name, data_dir_path, own_preferences);
+    save_preference(&preference)?;
+
+    log::info!(
+        "Created library profile: {} ({})",
+        profile.name,
+        profile.data_dir_path.display()
+    );
+
+    Ok(profile)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_library_profile(
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    id: Uuid,
+    name: String,
+) -> Result<(), String> {
+    let mut preference = preference.lock().await;
+
+    preference.rename_library_profile(id, name).map_err(|e| {
+        log::error!("{}", e);
+        e
+    })?;
+
+    save_preference(&preference)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_library_profile(
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    id: Uuid,
+) -> Result<(), String> {
+    let mut preference = preference.lock().await;
+
+    preference.remove_library_profile(id).map_err(|e| {
+        log::error!("{}", e);
+        e
+    })?;
+
+    save_preference(&preference)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_library_profile(
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    pximg_resolver: State<'_, Arc<Mutex<PximgResolver>>>,
+    booth_fetcher: State<'_, Mutex<BoothFetcher>>,
+    import_queue: State<'_, Arc<Mutex<ImportQueue>>>,
+    inbox: State<'_, Arc<Mutex<PendingImportInbox>>>,
+    handle: State<'_, AppHandle>,
+    id: Uuid,
+) -> Result<(), String> {
+    log::info!("Switching library profile to: {}", id);
+
+    switch_library(
+        &preference,
+        &basic_store,
+        &pximg_resolver,
+        &booth_fetcher,
+        &import_queue,
+        &inbox,
+        &handle,
+        |preference| preference.switch_library_profile(id),
+    )
+    .await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn open_recent_library(
+    preference: State<'_, Arc<Mutex<PreferenceStore>>>,
+    basic_store: State<'_, Arc<Mutex<AssetStorage>>>,
+    pximg_resolver: State<'_, Arc<Mutex<PximgResolver>>>,
+    booth_fetcher: State<'_, Mutex<BoothFetcher>>,
+    import_queue: State<'_, Arc<Mutex<ImportQueue>>>,
+    inbox: State<'_, Arc<Mutex<PendingImportInbox>>>,
+    handle: State<'_, AppHandle>,
+    data_dir_path: PathBuf,
+) -> Result<(), String> {
+    log::info!("Opening library: {}", data_dir_path.display());
+
+    switch_library(
+        &preference,
+        &basic_store,
+        &pximg_resolver,
+        &booth_fetcher,
+        &import_queue,
+        &inbox,
+        &handle,
+        |preference| {
+            preference.open_library_without_profile(data_dir_path.clone());
+            Ok(())
+        },
+    )
+    .await
+}
+
+/// `AssetStorage` を新しいデータフォルダで読み込み直し、成功した場合のみ設定を保存する
+///
+/// 読み込み中に他の設定が変更されても上書きしないよう、`switch` は読み込み後の最新の設定に適用する
+async fn switch_library(
+    preference: &Arc<Mutex<PreferenceStore>>,
+    basic_store: &Arc<Mutex<AssetStorage>>,
+    pximg_resolver: &Arc<Mutex<PximgResolver>>,
+    booth_fetcher: &Mutex<BoothFetcher>,
+    import_queue: &Arc<Mutex<ImportQueue>>,
+    inbox: &Arc<Mutex<PendingImportInbox>>,
+    handle: &AppHandle,
+    switch: impl Fn(&mut PreferenceStore) -> Result<(), String>,
+) -> Result<(), String> {
+    let new_data_dir = {
+        let mut new_preference = preference.lock().await.clone();
+        switch(&mut new_preference).map_err(|e| {
+            log::error!("{}", e);
+            e
+        })?;
+
+        new_preference.get_data_dir().clone()
+    };
+
+    if !new_data_dir.is_dir() {
+        let err = format!("Data directory not found: {}", new_data_dir.display());
+        log::error!("{}", err);
+        return Err(err);
+    }
+
+    // インポート中のアセットが別のライブラリに登録されないよう、キューが空になるまで切り替えない
+    let has_active_imports = import_queue
+        .lock()
+        .await
+        .items()
+        .iter()
+        .any(|item| item.status != ImportQueueItemStatus::Failed);
+
+    if has_active_imports {
+        let err = "Cannot switch library while imports are in progress".to_string();
+        log::error!("{}", err);
+        return Err(err);
+    }
+
+    let mut basic_store = basic_store.lock().await;
+    let old_data_dir = basic_store.data_dir();
+
+    if let Err(e) = basic_store.set_data_dir_and_reload(&new_data_dir).await {
+        log::error!("Failed to load library: {}", e);
+
+        if let Err(e) = basic_store.set_data_dir_and_reload(&old_data_dir).await {
+            log::error!("Failed to reload previous library: {}", e);
+        }
+
+        return Err(format!("Failed to load library: {}", e));
+    }
+
+    let mut preference = preference.lock().await;
+    let old_watch_dirs = preference.watch_dirs.clone();
+
+    if let Err(e) = switch(&mut preference) {
+        log::error!("Failed to switch library: {}", e);
+
+        if let Err(e) = basic_store.set_data_dir_and_reload(&old_data_dir).await {
+            log::error!("Failed to reload previous library: {}", e);
+        }
+
+        return Err(format!("Failed to switch library: {}", e));
+    }
+
+    restart_metadata_watcher(handle, &new_data_dir).await;
+
+    if preference.watch_dirs != old_watch_dirs {
+        inbox
+            .lock()
+            .await
+            .restart_watcher(&preference.watch_dirs, handle.clone());
+    }
+
+    save_preference(&preference)?;
+
+    // 切り替え後の設定を BOOTH からの取得にも反映する
+    {
+        let mut booth_fetcher = booth_fetcher.lock().await;
+
+        booth_fetcher.set_cache_config((&preference.booth_cache).into());
+        booth_fetcher.set_offline_mode(preference.booth_offline_mode);
+        booth_fetcher.set_language(preference.language.booth_lang_code());
+    }
+
+    pximg_resolver
+        .lock()
+        .await
+        .change_images_dir(new_data_dir.join("images"));
+
+    log::info!("Switched library to: {}", new_data_dir.display());
+
+    Ok(())
+}
+
+fn save_preference(preference: &PreferenceStore) -> Result<(), String> {
+    loader::wrapper::save_preference_store(preference).map_err(|e| {
+        let err = format!("Failed to save preferences: {}", e);
+        log::error!("{}", err);
+        err
+    })
+}
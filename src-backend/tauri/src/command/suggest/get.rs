@@ -8,7 +8,7 @@ use uuid::Uuid;
 #[derive(Serialize, Clone, Debug, PartialEq, Eq, specta::Type)]
 pub struct PrioritizedEntry {
     priority: u32,
-    value: String,
+    pub(crate) value: String,
 }
 
 #[tauri::command]
@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
+use file::MergeReportEntry;
 use serde::Serialize;
+use storage::{asset_storage::LibraryLockStatus, definitions::IntegrityReport};
 use tauri_specta::Event;
+use uuid::Uuid;
 
 #[derive(Serialize, Clone, specta::Type)]
 #[serde(rename_all = "camelCase")]
@@ -32,6 +35,7 @@ impl LoadResult {
 #[derive(Serialize, Clone, specta::Type, Event)]
 #[serde(rename_all = "camelCase")]
 pub struct ProgressEvent {
+    pub task_id: Option<Uuid>,
     pub percentage: f32,
     pub filename: String,
 }
@@ -39,10 +43,70 @@ pub struct ProgressEvent {
 impl ProgressEvent {
     pub fn new(percentage: f32, filename: String) -> Self {
         Self {
+            task_id: None,
             percentage,
             filename,
         }
     }
+
+    pub fn for_task(task_id: Uuid, percentage: f32, filename: String) -> Self {
+        Self {
+            task_id: Some(task_id),
+            percentage,
+            filename,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, specta::Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipPasswordRequested {
+    pub request_id: Uuid,
+    pub filename: String,
+    pub invalid_password: bool,
+}
+
+impl ZipPasswordRequested {
+    pub fn new(request_id: Uuid, filename: String, invalid_password: bool) -> Self {
+        Self {
+            request_id,
+            filename,
+            invalid_password,
+        }
+    }
+}
+
+/// 既存のアセットへのファイルの追加が完了した際に、ファイルごとの結果を通知する
+#[derive(Serialize, Clone, specta::Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct AdditionalImportReported {
+    pub task_id: Uuid,
+    pub asset_id: Uuid,
+    pub source: String,
+    pub entries: Vec<MergeReportEntry>,
+}
+
+/// データフォルダの整合性チェックが完了した際に、検出された不整合を通知する
+#[derive(Serialize, Clone, specta::Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityCheckReported {
+    pub task_id: Uuid,
+    pub report: IntegrityReport,
+}
+
+/// ライブラリのロックの状態が変わったことを通知する
+///
+/// 他のインスタンスによる変更を読み込んだことは `MetadataReloaded` で通知する
+#[derive(Serialize, Clone, specta::Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryLockStatusChanged {
+    pub status: LibraryLockStatus,
+}
+
+impl LibraryLockStatusChanged {
+    pub fn new(status: LibraryLockStatus) -> Self {
+        Self { status }
+    }
 }
 
 #[derive(specta::Type)]
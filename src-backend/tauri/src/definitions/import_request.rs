@@ -1,8 +1,8 @@
 use model::{AssetDescription, AssetTrait, Avatar, AvatarWearable, OtherAsset, WorldObject};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 
-#[derive(Deserialize, Debug, Clone, specta::Type)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 #[serde(rename_all = "camelCase")]
 pub struct AssetImportRequest<T: PreAsset> {
     pub pre_asset: T,
@@ -10,12 +10,12 @@ pub struct AssetImportRequest<T: PreAsset> {
     pub delete_source: bool,
 }
 
-#[derive(Deserialize, Debug, Clone, specta::Type)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 pub struct PreAvatar {
     pub description: AssetDescription,
 }
 
-#[derive(Deserialize, Debug, Clone, specta::Type)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 #[serde(rename_all = "camelCase")]
 pub struct PreAvatarWearable {
     pub description: AssetDescription,
@@ -23,13 +23,13 @@ pub struct PreAvatarWearable {
     pub supported_avatars: BTreeSet<String>,
 }
 
-#[derive(Deserialize, Debug, Clone, specta::Type)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 pub struct PreWorldObject {
     pub description: AssetDescription,
     pub category: String,
 }
 
-#[derive(Deserialize, Debug, Clone, specta::Type)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 pub struct PreOtherAsset {
     pub description: AssetDescription,
     pub category: String,
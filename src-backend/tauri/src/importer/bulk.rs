@@ -0,0 +1,455 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use booth::{AssetClassifier, BoothFetcher, PximgResolver};
+use filter::FileFilter;
+use model::{AssetDataLocation, AssetDescription, AssetType, preference::BoothNameSource};
+use serde::{Deserialize, Serialize};
+use storage::asset_storage::AssetStorage;
+use tauri::{AppHandle, Manager, async_runtime::Mutex};
+use tauri_specta::Event;
+use uuid::Uuid;
+use zip::ExtractLimits;
+
+use crate::definitions::import_request::{
+    AssetImportRequest, PreAvatar, PreAvatarWearable, PreOtherAsset, PreWorldObject,
+};
+
+use super::import_wrapper::{
+    import_avatar, import_avatar_wearable, import_other_asset, import_world_object,
+};
+
+/// フォルダ内のファイルをまとめた、1 つのアセットになる予定のまとまり
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkImportCandidate {
+    pub paths: Vec<PathBuf>,
+    pub name: String,
+    pub booth_item_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportPlanItem {
+    pub id: Uuid,
+    /// false の場合、実行時にスキップされる
+    pub enabled: bool,
+    pub absolute_paths: Vec<String>,
+    pub asset_type: AssetType,
+    pub name: String,
+    pub creator: String,
+    pub category: String,
+    pub booth_item_id: Option<u64>,
+    pub image_url: Option<String>,
+    pub published_at: Option<i64>,
+    /// 同じ BOOTH のアイテムIDを持つアセットが既に登録されている
+    pub already_registered: bool,
+    pub fetch_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportPlan {
+    pub items: Vec<BulkImportPlanItem>,
+    /// インポート対象にならなかったファイル
+    pub skipped: Vec<String>,
+}
+
+/// 計画の作成と実行の進捗。どちらも `task_id` のタスクとして実行される
+#[derive(Serialize, Clone, specta::Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportProgress {
+    pub task_id: Uuid,
+    pub processed: usize,
+    pub total: usize,
+    pub name: String,
+}
+
+/// インポート計画の作成が完了した際に、作成された計画を通知する
+#[derive(Serialize, Clone, specta::Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportPlanCreated {
+    pub task_id: Uuid,
+    pub plan: BulkImportPlan,
+}
+
+/// フォルダ直下のエントリを、アセット単位のまとまりに分類する
+///
+/// - zip / unitypackage とフォルダは、それぞれ 1 つのアセットとして扱う
+/// - 名前に同じ BOOTH のアイテムIDを含むエントリは、1 つのアセットにまとめる
+/// - それ以外のファイルはインポート対象にせず、`skipped` として返す
+pub fn scan_bulk_import_dir<P>(
+    dir: P,
+    filter: &FileFilter,
+) -> Result<(Vec<BulkImportCandidate>, Vec<PathBuf>), String>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    let mut candidates: Vec<BulkImportCandidate> = vec![];
+    let mut skipped = vec![];
+
+    for path in entries {
+        let is_dir = path.is_dir();
+
+        if let Ok(relative_path) = path.strip_prefix(dir) {
+            if filter.is_excluded(relative_path, is_dir) {
+                continue;
+            }
+        }
+
+        if !is_dir && !watcher::is_import_candidate(&path) {
+            skipped.push(path);
+            continue;
+        }
+
+        let entry_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let booth_item_id = watcher::extract_booth_item_id(&entry_name);
+
+        if let Some(booth_item_id) = booth_item_id {
+            let existing = candidates
+                .iter_mut()
+                .find(|candidate| candidate.booth_item_id == Some(booth_item_id));
+
+            if let Some(existing) = existing {
+                existing.paths.push(path);
+                continue;
+            }
+        }
+
+        let name = if is_dir {
+            entry_name
+        } else {
+            watcher::suggest_asset_name(&path)
+        };
+
+        candidates.push(BulkImportCandidate {
+            paths: vec![path],
+            name,
+            booth_item_id,
+        });
+    }
+
+    Ok((candidates, skipped))
+}
+
+/// BOOTH から取得した情報を元に、確認用のインポート計画を作成する
+pub async fn create_bulk_import_plan(
+    app_handle: &AppHandle,
+    task_id: Uuid,
+    candidates: Vec<BulkImportCandidate>,
+    skipped: Vec<PathBuf>,
+    registered_booth_item_ids: &HashSet<u64>,
+    name_source: BoothNameSource,
+    classifier: &AssetClassifier,
+) -> BulkImportPlan {
+    let booth_fetcher = app_handle
+        .state::<Mutex<BoothFetcher>>()
+        .lock()
+        .await
+        .clone();
+    let total = candidates.len();
+
+    let mut items = Vec::with_capacity(total);
+
+    for (i, candidate) in candidates.into_iter().enumerate() {
+        let mut item = BulkImportPlanItem {
+            id: Uuid::new_v4(),
+            enabled: true,
+            absolute_paths: candidate
+                .paths
+                .iter()
+                .map(|path| {
+                    std::path::absolute(path)
+                        .unwrap_or_else(|_| path.clone())
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect(),
+            asset_type: AssetType::OtherAsset,
+            name: candidate.name.clone(),
+            creator: String::new(),
+            category: String::new(),
+            booth_item_id: candidate.booth_item_id,
+            image_url: None,
+            published_at: None,
+            already_registered: false,
+            fetch_error: None,
+        };
+
+        if let Some(booth_item_id) = candidate.booth_item_id {
+            let already_registered = registered_booth_item_ids.contains(&booth_item_id);
+
+            item.already_registered = already_registered;
+            item.enabled = !already_registered;
+
+            let result = booth_fetcher.fetch(booth_item_id).await;
+
+            match result {
+                Ok(info) => {
+                    let classification = classifier.classify(&info);
+                    if let Some(asset_type) = classification.asset_type {
+                        item.asset_type = asset_type;
+                    }
+                    // アバターにはカテゴリがない
+                    if item.asset_type != AssetType::Avatar {
+                        item.category = classification.category.unwrap_or_default();
+                    }
+
+                    item.name = info.name_for(name_source).to_string();
+                    item.creator = info.creator;
+                    item.image_url = info.image_urls.into_iter().next();
+                    item.published_at = Some(info.published_at);
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Failed to fetch BOOTH info for bulk import (id = {}): {}",
+                        booth_item_id,
+                        e
+                    );
+                    item.fetch_error = Some(e.to_string());
+                }
+            }
+        }
+
+        emit_bulk_import_progress(app_handle, task_id, i + 1, total, candidate.name);
+        items.push(item);
+    }
+
+    BulkImportPlan {
+        items,
+        skipped: skipped
+            .into_iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+    }
+}
+
+/// 計画内の有効なアイテムを順にインポートする。失敗したアイテムがあっても残りは続行する
+pub async fn execute_bulk_import_plan(
+    app_handle: &AppHandle,
+    task_id: Uuid,
+    plan: BulkImportPlan,
+    delete_source: bool,
+    zip_extraction: bool,
+    use_trash_bin: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
+) -> Result<(), String> {
+    let basic_store = app_handle.state::<Arc<Mutex<AssetStorage>>>();
+    let pximg_resolver = app_handle.state::<Arc<Mutex<PximgResolver>>>();
+
+    let items = plan
+        .items
+        .into_iter()
+        .filter(|item| item.enabled)
+        .collect::<Vec<_>>();
+    let total = items.len();
+
+    let mut failed = vec![];
+
+    for (i, item) in items.into_iter().enumerate() {
+        let name = item.name.clone();
+
+        let image_filename = match &item.image_url {
+            Some(url) => match pximg_resolver.lock().await.resolve(url).await {
+                Ok(filename) => Some(filename),
+                Err(e) => {
+                    log::warn!("Failed to resolve image for {}: {}", name, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let description = AssetDescription {
+            name: item.name,
+            creator: item.creator,
+            image_filename,
+            tags: vec![],
+            memo: None,
+            booth_item_id: item.booth_item_id,
+            dependencies: vec![],
+            created_at: chrono::Local::now().timestamp_millis(),
+            published_at: item.published_at,
+            data_location: AssetDataLocation::Local,
+        };
+
+        let result = match item.asset_type {
+            AssetType::Avatar => import_avatar(
+                &basic_store,
+                AssetImportRequest {
+                    pre_asset: PreAvatar { description },
+                    absolute_paths: item.absolute_paths,
+                    delete_source,
+                },
+                app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                filter,
+                limits,
+            )
+            .await
+            .map(|_| ()),
+            AssetType::AvatarWearable => import_avatar_wearable(
+                &basic_store,
+                AssetImportRequest {
+                    pre_asset: PreAvatarWearable {
+                        description,
+                        category: item.category,
+                        supported_avatars: BTreeSet::new(),
+                    },
+                    absolute_paths: item.absolute_paths,
+                    delete_source,
+                },
+                app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                filter,
+                limits,
+            )
+            .await
+            .map(|_| ()),
+            AssetType::WorldObject => import_world_object(
+                &basic_store,
+                AssetImportRequest {
+                    pre_asset: PreWorldObject {
+                        description,
+                        category: item.category,
+                    },
+                    absolute_paths: item.absolute_paths,
+                    delete_source,
+                },
+                app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                filter,
+                limits,
+            )
+            .await
+            .map(|_| ()),
+            AssetType::OtherAsset => import_other_asset(
+                &basic_store,
+                AssetImportRequest {
+                    pre_asset: PreOtherAsset {
+                        description,
+                        category: item.category,
+                    },
+                    absolute_paths: item.absolute_paths,
+                    delete_source,
+                },
+                app_handle,
+                task_id,
+                zip_extraction,
+                use_trash_bin,
+                filter,
+                limits,
+            )
+            .await
+            .map(|_| ()),
+        };
+
+        if let Err(e) = result {
+            log::error!("Failed to import {} in bulk import: {}", name, e);
+            failed.push(name.clone());
+        }
+
+        emit_bulk_import_progress(app_handle, task_id, i + 1, total, name);
+    }
+
+    if !failed.is_empty() {
+        return Err(format!(
+            "Failed to import {} of {} assets: {}",
+            failed.len(),
+            total,
+            failed.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+fn emit_bulk_import_progress(
+    app_handle: &AppHandle,
+    task_id: Uuid,
+    processed: usize,
+    total: usize,
+    name: String,
+) {
+    let event = BulkImportProgress {
+        task_id,
+        processed,
+        total,
+        name,
+    };
+
+    if let Err(e) = event.emit(app_handle) {
+        log::error!("Failed to emit BulkImportProgress event: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_bulk_import_dir() {
+        let dir = PathBuf::from("test/temp/scan_bulk_import_dir");
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+
+        std::fs::create_dir_all(dir.join("Avatar")).unwrap();
+        std::fs::create_dir_all(dir.join("__MACOSX")).unwrap();
+        std::fs::write(dir.join("Avatar/Avatar.unitypackage"), b"").unwrap();
+        std::fs::write(dir.join("Outfit_booth_123456.zip"), b"").unwrap();
+        std::fs::write(dir.join("Outfit_textures_booth_123456.zip"), b"").unwrap();
+        std::fs::write(dir.join("Shader.unitypackage"), b"").unwrap();
+        std::fs::write(dir.join("readme.txt"), b"").unwrap();
+
+        let filter = FileFilter::new::<&str>(&[], &["__MACOSX"]).unwrap();
+        let (candidates, skipped) = scan_bulk_import_dir(&dir, &filter).unwrap();
+
+        assert_eq!(
+            candidates,
+            vec![
+                BulkImportCandidate {
+                    paths: vec![dir.join("Avatar")],
+                    name: "Avatar".into(),
+                    booth_item_id: None,
+                },
+                BulkImportCandidate {
+                    paths: vec![
+                        dir.join("Outfit_booth_123456.zip"),
+                        dir.join("Outfit_textures_booth_123456.zip"),
+                    ],
+                    name: "Outfit_booth_123456".into(),
+                    booth_item_id: Some(123456),
+                },
+                BulkImportCandidate {
+                    paths: vec![dir.join("Shader.unitypackage")],
+                    name: "Shader".into(),
+                    booth_item_id: None,
+                },
+            ]
+        );
+        assert_eq!(skipped, vec![dir.join("readme.txt")]);
+    }
+}
@@ -0,0 +1,35 @@
+use booth::AssetClassifier;
+use model::AssetType;
+use storage::asset_storage::AssetStorage;
+
+/// 登録済みのアセットの種類とカテゴリを学習した分類器を作成する
+pub async fn build_asset_classifier(basic_store: &AssetStorage) -> AssetClassifier {
+    let mut classifier = AssetClassifier::new();
+
+    for avatar in basic_store.get_avatar_store().get_all().await {
+        classifier.learn(AssetType::Avatar, "", &avatar.description);
+    }
+    for avatar_wearable in basic_store.get_avatar_wearable_store().get_all().await {
+        classifier.learn(
+            AssetType::AvatarWearable,
+            &avatar_wearable.category,
+            &avatar_wearable.description,
+        );
+    }
+    for world_object in basic_store.get_world_object_store().get_all().await {
+        classifier.learn(
+            AssetType::WorldObject,
+            &world_object.category,
+            &world_object.description,
+        );
+    }
+    for other_asset in basic_store.get_other_asset_store().get_all().await {
+        classifier.learn(
+            AssetType::OtherAsset,
+            &other_asset.category,
+            &other_asset.description,
+        );
+    }
+
+    classifier
+}
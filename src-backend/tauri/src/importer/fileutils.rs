@@ -1,12 +1,28 @@
 use file::{
-    DeleteOnDrop,
+    ConflictPolicy, DeleteOnDrop, MergeReportEntry,
     modify_guard::{self, FileTransferGuard},
 };
+use filter::FileFilter;
+use model::preference::PreferenceStore;
+use serde::Serialize;
 use std::{
     error::Error,
     ffi::OsStr,
+    future::Future,
     path::{Path, PathBuf},
 };
+use zip::{ExtractLimits, ExtractOptions, ZipExtractError};
+
+/// 既存のアセットに追加しようとしているファイルのうち、同じ名前のファイルが既にあるもの
+#[derive(Serialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConflict {
+    /// アセットのフォルダからの相対パス
+    pub path: String,
+    pub existing_size: u64,
+    /// zip 内のファイルはサイズを取得しないため None
+    pub incoming_size: Option<u64>,
+}
 
 pub async fn execute_image_fixation<P>(src: P) -> Result<Option<String>, String>
 where
@@ -51,19 +67,53 @@ where
         return Err(e.to_string());
     }
 
+    // 詳細表示用に元の画像を残している場合は、同じ名前で確定させる
+    if let Some(images_dir) = src.parent() {
+        if let Err(e) = file::copy_original_image(images_dir, file_name, new_filename).await {
+            return Err(e.to_string());
+        }
+    }
+
     return Ok(Some(new_filename.to_string()));
 }
 
-pub async fn import_asset<P, Q>(
+/// 設定されたインポートフィルタを構築する
+pub fn build_import_filter(preference: &PreferenceStore) -> Result<FileFilter, String> {
+    preference.import_filter.build_filter().map_err(|e| {
+        let err = format!("Invalid import filter: {}", e);
+        log::error!("{}", err);
+        err
+    })
+}
+
+/// 設定された zip の展開時の上限を取得する
+pub fn build_extract_limits(preference: &PreferenceStore) -> ExtractLimits {
+    let limits = preference.zip_extraction_limits;
+
+    ExtractLimits {
+        max_total_size: limits
+            .max_total_size_mb
+            .map(|size| size.saturating_mul(1024 * 1024)),
+        max_entry_count: limits.max_entry_count.map(|count| count as usize),
+        max_compression_ratio: limits.max_compression_ratio.map(f64::from),
+    }
+}
+
+pub async fn import_asset<P, Q, F, Fut>(
     src: P,
     dest: Q,
     cleanup_on_fail: bool,
     zip_extraction: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
     progress_callback: impl Fn(f32, String),
+    password_prompt: F,
 ) -> Result<(), Box<dyn Error>>
 where
     P: AsRef<Path>,
     Q: AsRef<Path>,
+    F: Fn(String, bool) -> Fut,
+    Fut: Future<Output = Option<String>>,
 {
     let src = src.as_ref();
     let dest = dest.as_ref();
@@ -87,11 +137,12 @@ where
         // Convert to PathBuf to avoid lifetime issues
         let src = src.to_path_buf();
 
-        modify_guard::copy_dir(
+        modify_guard::copy_dir_with_filter(
             src,
             destination,
             false,
             FileTransferGuard::none(),
+            filter,
             progress_callback,
         )
         .await?;
@@ -117,7 +168,41 @@ where
             };
 
             tokio::fs::create_dir_all(&destination).await?;
-            zip::extract_zip(&src.to_path_buf(), &destination, progress_callback).await?;
+
+            let mut options = ExtractOptions {
+                filter: filter.clone(),
+                limits: limits.clone(),
+                ..Default::default()
+            };
+
+            loop {
+                let result =
+                    zip::extract_zip_with_options(src, &destination, &options, &progress_callback)
+                        .await;
+
+                match result {
+                    Ok(()) => break,
+                    Err(e) if e.is_password_error() => {
+                        let invalid_password = matches!(e, ZipExtractError::InvalidPassword);
+                        let filename = src
+                            .file_name()
+                            .unwrap_or(OsStr::new("imported"))
+                            .to_string_lossy()
+                            .to_string();
+
+                        match password_prompt(filename, invalid_password).await {
+                            Some(password) => options.password = Some(password),
+                            None => return Err(e.into()),
+                        }
+
+                        // パスワードの誤りが展開の途中で判明した場合は書き込み済みのエントリがあるため、
+                        // 展開先を空にしてから再試行する
+                        tokio::fs::remove_dir_all(&destination).await?;
+                        tokio::fs::create_dir_all(&destination).await?;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
 
             if let Some(mut delete_on_drop) = delete_on_drop {
                 delete_on_drop.mark_as_completed();
@@ -153,6 +238,235 @@ where
     Ok(())
 }
 
+/// インポート時にフィルタによって除外されるエントリを、インポート元からの相対パスで列挙する
+///
+/// 明示的に選択された単一のファイルは除外の対象にならない
+pub async fn preview_filtered_entries<P>(
+    src: P,
+    zip_extraction: bool,
+    filter: &FileFilter,
+) -> Result<Vec<String>, String>
+where
+    P: AsRef<Path>,
+{
+    let src = src.as_ref();
+
+    if src.is_dir() {
+        let mut excluded = vec![];
+        collect_filtered_entries(src, src, filter, &mut excluded)?;
+
+        excluded.sort();
+        return Ok(excluded);
+    }
+
+    if zip_extraction && src.extension() == Some(OsStr::new("zip")) {
+        let entries = zip::list_entries(src)
+            .await
+            .map_err(|e| format!("Failed to read zip file: {}", e))?;
+
+        let mut excluded = entries
+            .into_iter()
+            .filter(|entry| filter.is_excluded(&entry.name, entry.is_dir))
+            .map(|entry| entry.name.trim_end_matches('/').to_string())
+            .collect::<Vec<_>>();
+
+        excluded.sort();
+        return Ok(excluded);
+    }
+
+    Ok(vec![])
+}
+
+fn collect_filtered_entries(
+    root: &Path,
+    dir: &Path,
+    filter: &FileFilter,
+    excluded: &mut Vec<String>,
+) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?
+            .path();
+        let is_dir = path.is_dir();
+
+        let Ok(relative_path) = path.strip_prefix(root) else {
+            continue;
+        };
+
+        if filter.is_excluded(relative_path, is_dir) {
+            // 除外されたフォルダの中身は列挙しない
+            excluded.push(relative_path.to_string_lossy().replace('\\', "/"));
+            continue;
+        }
+
+        if is_dir {
+            collect_filtered_entries(root, &path, filter, excluded)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 既存のアセットにファイルを追加する
+///
+/// 一時フォルダに取り込んでから `dest` へ移動し、競合したファイルは `policy` に従って処理する
+pub async fn import_with_conflict_policy<P, Q, R, F, Fut>(
+    src: P,
+    dest: Q,
+    staging_dir: R,
+    zip_extraction: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
+    policy: ConflictPolicy,
+    progress_callback: impl Fn(f32, String),
+    password_prompt: F,
+) -> Result<Vec<MergeReportEntry>, Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    R: AsRef<Path>,
+    F: Fn(String, bool) -> Fut,
+    Fut: Future<Output = Option<String>>,
+{
+    let src = src.as_ref();
+    let staging_dir = staging_dir.as_ref();
+
+    tokio::fs::create_dir_all(staging_dir).await?;
+    let _delete_on_drop = DeleteOnDrop::new(staging_dir.to_path_buf());
+
+    import_asset(
+        src,
+        staging_dir,
+        false,
+        zip_extraction,
+        filter,
+        limits,
+        progress_callback,
+        password_prompt,
+    )
+    .await?;
+
+    // コピーすると更新日時が現在の日時になるため、KeepNewer で比較できるようコピー元の日時に戻す
+    // zip を展開した場合は、展開時に zip に記録された日時が設定されているため何もしない
+    let extracted = zip_extraction && src.extension() == Some(OsStr::new("zip"));
+
+    if let (false, Some(file_name)) = (extracted, src.file_name()) {
+        file::copy_modified_times(src, staging_dir.join(file_name))?;
+    }
+
+    let report = file::merge_dir(staging_dir, dest, policy).await?;
+
+    Ok(report)
+}
+
+/// 既存のアセットにファイルを追加した場合に、名前が競合するファイルを列挙する
+pub async fn preview_import_conflicts<P, Q>(
+    src: P,
+    dest: Q,
+    zip_extraction: bool,
+    filter: &FileFilter,
+) -> Result<Vec<ImportConflict>, String>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let src = src.as_ref();
+    let dest = dest.as_ref();
+
+    let mut incoming: Vec<(String, Option<u64>)> = vec![];
+
+    if src.is_dir() {
+        let name = src
+            .file_name()
+            .unwrap_or(OsStr::new("imported"))
+            .to_string_lossy()
+            .to_string();
+
+        collect_incoming_files(src, src, &name, filter, &mut incoming)?;
+    } else if zip_extraction && src.extension() == Some(OsStr::new("zip")) {
+        let stem = src
+            .file_stem()
+            .unwrap_or(OsStr::new("imported"))
+            .to_string_lossy()
+            .to_string();
+
+        let entries = zip::list_entries(src)
+            .await
+            .map_err(|e| format!("Failed to read zip file: {}", e))?;
+
+        incoming.extend(
+            entries
+                .into_iter()
+                .filter(|entry| !entry.is_dir && !filter.is_excluded(&entry.name, false))
+                .map(|entry| (format!("{}/{}", stem, entry.name), None)),
+        );
+    } else {
+        let name = src
+            .file_name()
+            .unwrap_or(OsStr::new("imported"))
+            .to_string_lossy()
+            .to_string();
+        let size = src.metadata().ok().map(|metadata| metadata.len());
+
+        incoming.push((name, size));
+    }
+
+    let mut conflicts = incoming
+        .into_iter()
+        .filter_map(|(path, incoming_size)| {
+            let metadata = dest.join(&path).metadata().ok()?;
+
+            Some(ImportConflict {
+                path,
+                existing_size: metadata.len(),
+                incoming_size,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(conflicts)
+}
+
+fn collect_incoming_files(
+    root: &Path,
+    dir: &Path,
+    prefix: &str,
+    filter: &FileFilter,
+    incoming: &mut Vec<(String, Option<u64>)>,
+) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?
+            .path();
+        let is_dir = path.is_dir();
+
+        let Ok(relative_path) = path.strip_prefix(root) else {
+            continue;
+        };
+
+        if filter.is_excluded(relative_path, is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            collect_incoming_files(root, &path, prefix, filter, incoming)?;
+        } else {
+            let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+            let size = path.metadata().ok().map(|metadata| metadata.len());
+
+            incoming.push((format!("{}/{}", prefix, relative_path), size));
+        }
+    }
+
+    Ok(())
+}
+
 fn select_destination_path<P, S>(base: P, prefer_filename: S) -> PathBuf
 where
     P: AsRef<Path>,
@@ -190,6 +504,8 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, SystemTime};
+
     use super::*;
 
     #[tokio::test]
@@ -278,15 +594,42 @@ mod tests {
         std::fs::copy("test/zip/normal.zip", &zip_src).unwrap();
         std::fs::write(&normal_file_src, b"dummy").unwrap();
 
-        import_asset(&dir_src, &dest, true, true, |_, _| {})
-            .await
-            .unwrap();
-        import_asset(&zip_src, &dest, true, true, |_, _| {})
-            .await
-            .unwrap();
-        import_asset(&normal_file_src, &dest, true, true, |_, _| {})
-            .await
-            .unwrap();
+        import_asset(
+            &dir_src,
+            &dest,
+            true,
+            true,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
+            |_, _| {},
+            |_, _| async { None },
+        )
+        .await
+        .unwrap();
+        import_asset(
+            &zip_src,
+            &dest,
+            true,
+            true,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
+            |_, _| {},
+            |_, _| async { None },
+        )
+        .await
+        .unwrap();
+        import_asset(
+            &normal_file_src,
+            &dest,
+            true,
+            true,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
+            |_, _| {},
+            |_, _| async { None },
+        )
+        .await
+        .unwrap();
 
         let dir_dummy_txt = dest.join("dir/dummy.txt");
         let extracted_zip_dir = dest.join("zip-file");
@@ -298,4 +641,151 @@ mod tests {
         assert!(normal_file_txt.exists());
         assert_eq!(std::fs::read_to_string(&normal_file_txt).unwrap(), "dummy");
     }
+
+    fn set_modified<P: AsRef<Path>>(path: P, modified: SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_with_keep_newer_policy() {
+        let base = PathBuf::from("test/temp/import_with_keep_newer_policy");
+
+        if std::fs::exists(&base).unwrap() {
+            std::fs::remove_dir_all(&base).unwrap();
+        }
+
+        let dir_src = base.join("src/Package");
+        let zip_src = base.join("src/zip-file.zip");
+        let dest = base.join("dest");
+
+        std::fs::create_dir_all(&dir_src).unwrap();
+        std::fs::create_dir_all(dest.join("Package")).unwrap();
+        std::fs::create_dir_all(dest.join("zip-file")).unwrap();
+        std::fs::copy("test/zip/normal.zip", &zip_src).unwrap();
+
+        let old = SystemTime::now() - Duration::from_secs(60 * 60);
+
+        // インポート元の方が古いファイルと、新しいファイル
+        std::fs::write(dir_src.join("older.txt"), b"incoming").unwrap();
+        std::fs::write(dest.join("Package/older.txt"), b"existing").unwrap();
+        set_modified(dir_src.join("older.txt"), old);
+
+        std::fs::write(dir_src.join("newer.txt"), b"incoming").unwrap();
+        std::fs::write(dest.join("Package/newer.txt"), b"existing").unwrap();
+        set_modified(dest.join("Package/newer.txt"), old);
+
+        // zip 内のファイルは 2025 年の日時が記録されている
+        std::fs::write(dest.join("zip-file/dummy1.txt"), b"existing").unwrap();
+
+        for (i, src) in [&dir_src, &zip_src].into_iter().enumerate() {
+            import_with_conflict_policy(
+                src,
+                &dest,
+                base.join(format!("staging{}", i)),
+                true,
+                &FileFilter::none(),
+                &ExtractLimits::default(),
+                ConflictPolicy::KeepNewer,
+                |_, _| {},
+                |_, _| async { None },
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(
+            std::fs::read_to_string(dest.join("Package/older.txt")).unwrap(),
+            "existing"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("Package/newer.txt")).unwrap(),
+            "incoming"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("zip-file/dummy1.txt")).unwrap(),
+            "existing"
+        );
+        assert!(dest.join("zip-file/dummy-dir/dummy2.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_preview_filtered_entries() {
+        let base = PathBuf::from("test/temp/preview_filtered_entries");
+
+        if std::fs::exists(&base).unwrap() {
+            std::fs::remove_dir_all(&base).unwrap();
+        }
+
+        std::fs::create_dir_all(base.join("__MACOSX")).unwrap();
+        std::fs::create_dir_all(base.join("Avatar")).unwrap();
+        std::fs::write(base.join("__MACOSX/._Avatar.unitypackage"), b"dummy").unwrap();
+        std::fs::write(base.join("Avatar/Avatar.unitypackage"), b"dummy").unwrap();
+        std::fs::write(base.join("Avatar/Thumbs.db"), b"dummy").unwrap();
+
+        let filter = FileFilter::new::<&str>(&[], &["__MACOSX", "Thumbs.db"]).unwrap();
+
+        let result = preview_filtered_entries(&base, true, &filter)
+            .await
+            .unwrap();
+        assert_eq!(result, vec!["Avatar/Thumbs.db", "__MACOSX"]);
+
+        let filter = FileFilter::new::<&str>(&[], &["dummy-dir"]).unwrap();
+
+        let result = preview_filtered_entries("test/zip/normal.zip", true, &filter)
+            .await
+            .unwrap();
+        assert_eq!(result, vec!["dummy-dir", "dummy-dir/dummy2.txt"]);
+
+        let result = preview_filtered_entries("test/zip/normal.zip", false, &filter)
+            .await
+            .unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_assets_with_filter() {
+        let base = PathBuf::from("test/temp/import_asset_with_filter");
+
+        if std::fs::exists(&base).unwrap() {
+            std::fs::remove_dir_all(&base).unwrap();
+        }
+
+        let dir_src = base.join("src/dir");
+        let zip_src = base.join("src/zip-file.zip");
+        let dest = base.join("dest");
+
+        std::fs::create_dir_all(dir_src.join("__MACOSX")).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+
+        std::fs::write(dir_src.join("dummy.txt"), b"dummy").unwrap();
+        std::fs::write(dir_src.join("__MACOSX/._dummy.txt"), b"dummy").unwrap();
+        std::fs::copy("test/zip/normal.zip", &zip_src).unwrap();
+
+        let filter = FileFilter::new::<&str>(&[], &["__MACOSX", "dummy-dir"]).unwrap();
+
+        for src in [&dir_src, &zip_src] {
+            import_asset(
+                src,
+                &dest,
+                true,
+                true,
+                &filter,
+                &ExtractLimits::default(),
+                |_, _| {},
+                |_, _| async { None },
+            )
+            .await
+            .unwrap();
+        }
+
+        assert!(dest.join("dir/dummy.txt").exists());
+        assert!(!dest.join("dir/__MACOSX").exists());
+        assert!(dest.join("zip-file/dummy1.txt").exists());
+        assert!(!dest.join("zip-file/dummy-dir").exists());
+    }
 }
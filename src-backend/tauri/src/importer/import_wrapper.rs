@@ -6,30 +6,41 @@ use std::{
 };
 
 use file::{
-    DeleteOnDrop,
+    ConflictPolicy, DeleteOnDrop, MergeReportEntry,
     modify_guard::{self, DeletionGuard},
 };
-use model::{AssetTrait, Avatar, AvatarWearable, OtherAsset, WorldObject};
+use filter::FileFilter;
+use model::{AssetDataLocation, AssetTrait, Avatar, AvatarWearable, OtherAsset, WorldObject};
 use storage::asset_storage::AssetStorage;
 use tauri::AppHandle;
 use tauri_specta::Event;
 use tokio::sync::Mutex;
 use uuid::Uuid;
-
-use crate::definitions::{
-    entities::ProgressEvent,
-    import_request::{AssetImportRequest, PreAsset, PreAvatar},
+use zip::ExtractLimits;
+
+use crate::{
+    booth_update::record_imported_booth_item,
+    definitions::{
+        entities::ProgressEvent,
+        import_request::{AssetImportRequest, PreAsset, PreAvatar},
+    },
 };
 
-use super::fileutils::{self, execute_image_fixation};
+use super::{
+    fileutils::{self, execute_image_fixation},
+    password::request_zip_password,
+};
 
 async fn import_asset<T, F>(
-    basic_store: &AssetStorage,
+    basic_store: &Arc<Mutex<AssetStorage>>,
     mut request: AssetImportRequest<T>,
     app_handle: Option<&AppHandle>,
+    task_id: Uuid,
     register_fn: F,
     zip_extraction: bool,
     use_trash_bin: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
 ) -> Result<T::AssetType, String>
 where
     T: PreAsset,
@@ -38,10 +49,37 @@ where
         T::AssetType,
     ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>>,
 {
+    // ファイルのコピー中はストレージをロックしないよう、データディレクトリを先に取得しておく
+    let data_dir = {
+        let basic_store = basic_store.lock().await;
+
+        // 読み取り専用の場合は、ファイルをコピーする前に中断する
+        basic_store.ensure_writable()?;
+        basic_store.data_dir()
+    };
+
+    let data_location = &mut request.pre_asset.description().data_location;
+
+    if data_location.has_local_data() {
+        // インポートするファイルがない場合は、未ダウンロードのアセットとして登録する
+        if request.absolute_paths.is_empty() {
+            *data_location = AssetDataLocation::NotDownloaded;
+        }
+    } else if !request.absolute_paths.is_empty() {
+        return Err("Files cannot be imported into an asset without local data".into());
+    }
+
+    // リンクする場合は、参照先のフォルダが存在することを確認する
+    if let AssetDataLocation::Linked { path } = data_location {
+        if !Path::new(path).is_dir() {
+            return Err(format!("Linked directory does not exist: {}", path));
+        }
+    }
+
     let image_filename = request.pre_asset.description().image_filename.as_ref();
 
     if let Some(image_filename) = image_filename {
-        let images_path = basic_store.data_dir().join("images");
+        let images_path = data_dir.join("images");
         let new_filename = bind_temp_image(&images_path, image_filename).await?;
 
         request.pre_asset.description().image_filename = Some(new_filename);
@@ -49,21 +87,21 @@ where
 
     let asset = request.pre_asset.create();
     let file_count = request.absolute_paths.len();
+    let destination = data_dir.join("data").join(asset.get_id().to_string());
+
+    // 登録に失敗した場合は、取り込んだファイルを削除する
+    let mut delete_on_drop = DeleteOnDrop::new(destination.clone());
 
     for i in 0..file_count {
         let path_str = request.absolute_paths.get(i).unwrap();
 
         let src_import_asset_path: PathBuf = PathBuf::from(path_str);
-        let destination = basic_store
-            .data_dir()
-            .join("data")
-            .join(asset.get_id().to_string());
 
         let progress_callback = |progress, filename| {
             if let Some(handle) = app_handle {
                 let percentage = (i as f32 + progress) / file_count as f32 * 100f32;
 
-                ProgressEvent::new(percentage, filename)
+                ProgressEvent::for_task(task_id, percentage, filename)
                     .emit(handle)
                     .unwrap();
             }
@@ -74,6 +112,9 @@ where
             &destination,
             progress_callback,
             zip_extraction,
+            filter,
+            limits,
+            app_handle,
         )
         .await;
 
@@ -82,12 +123,31 @@ where
         }
     }
 
-    let result = register_fn(basic_store, asset.clone()).await;
+    let result = {
+        let basic_store = basic_store.lock().await;
+
+        // コピー中にデータフォルダが移行・切り替えされた場合は、古いフォルダに取り込んだファイルを登録しない
+        if basic_store.data_dir() != data_dir {
+            Err(format!(
+                "Data directory was changed during import: {}",
+                data_dir.display()
+            ))
+        } else {
+            register_fn(&basic_store, asset.clone()).await
+        }
+    };
 
     if let Err(err) = result {
         return Err(format!("Failed to import asset: {}", err));
     }
 
+    delete_on_drop.mark_as_completed();
+
+    if let (Some(handle), Some(booth_item_id)) = (app_handle, asset.get_description().booth_item_id)
+    {
+        record_imported_booth_item(handle, asset.get_id(), booth_item_id).await;
+    }
+
     if request.delete_source {
         for i in 0..file_count {
             let path: PathBuf = PathBuf::from(request.absolute_paths.get(i).unwrap());
@@ -116,31 +176,40 @@ where
 }
 
 pub async fn import_avatar(
-    basic_store: &AssetStorage,
+    basic_store: &Arc<Mutex<AssetStorage>>,
     request: AssetImportRequest<PreAvatar>,
     app_handle: &AppHandle,
+    task_id: Uuid,
     zip_extraction: bool,
     use_trash_bin: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
 ) -> Result<Avatar, String> {
     import_asset(
         basic_store,
         request,
         Some(app_handle),
+        task_id,
         |provider: &'_ AssetStorage, asset: Avatar| {
             Box::pin(async { provider.get_avatar_store().add_asset_and_save(asset).await })
         },
         zip_extraction,
         use_trash_bin,
+        filter,
+        limits,
     )
     .await
 }
 
 pub async fn import_avatar_wearable<T>(
-    basic_store: &AssetStorage,
+    basic_store: &Arc<Mutex<AssetStorage>>,
     request: AssetImportRequest<T>,
     app_handle: &AppHandle,
+    task_id: Uuid,
     zip_extraction: bool,
     use_trash_bin: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
 ) -> Result<AvatarWearable, String>
 where
     T: PreAsset<AssetType = AvatarWearable>,
@@ -149,6 +218,7 @@ where
         basic_store,
         request,
         Some(app_handle),
+        task_id,
         |provider: &'_ AssetStorage, asset: AvatarWearable| {
             Box::pin(async {
                 provider
@@ -159,16 +229,21 @@ where
         },
         zip_extraction,
         use_trash_bin,
+        filter,
+        limits,
     )
     .await
 }
 
 pub async fn import_world_object<T>(
-    basic_store: &AssetStorage,
+    basic_store: &Arc<Mutex<AssetStorage>>,
     request: AssetImportRequest<T>,
     app_handle: &AppHandle,
+    task_id: Uuid,
     zip_extraction: bool,
     use_trash_bin: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
 ) -> Result<WorldObject, String>
 where
     T: PreAsset<AssetType = WorldObject>,
@@ -177,6 +252,7 @@ where
         basic_store,
         request,
         Some(app_handle),
+        task_id,
         |provider: &'_ AssetStorage, asset: WorldObject| {
             Box::pin(async {
                 provider
@@ -187,16 +263,21 @@ where
         },
         zip_extraction,
         use_trash_bin,
+        filter,
+        limits,
     )
     .await
 }
 
 pub async fn import_other_asset<T>(
-    basic_store: &AssetStorage,
+    basic_store: &Arc<Mutex<AssetStorage>>,
     request: AssetImportRequest<T>,
     app_handle: &AppHandle,
+    task_id: Uuid,
     zip_extraction: bool,
     use_trash_bin: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
 ) -> Result<OtherAsset, String>
 where
     T: PreAsset<AssetType = OtherAsset>,
@@ -205,6 +286,7 @@ where
         basic_store,
         request,
         Some(app_handle),
+        task_id,
         |provider: &'_ AssetStorage, asset: OtherAsset| {
             Box::pin(async {
                 provider
@@ -215,6 +297,8 @@ where
         },
         zip_extraction,
         use_trash_bin,
+        filter,
+        limits,
     )
     .await
 }
@@ -224,13 +308,29 @@ pub async fn import_additional_data<P>(
     id: Uuid,
     path: P,
     zip_extraction: bool,
-) -> Result<(), String>
+    filter: &FileFilter,
+    limits: &ExtractLimits,
+    policy: ConflictPolicy,
+    app_handle: Option<&AppHandle>,
+) -> Result<Vec<MergeReportEntry>, String>
 where
     P: AsRef<Path>,
 {
-    let asset_data_dir = {
+    let (asset_data_dir, staging_dir) = {
         let store_provider = basic_store.lock().await;
-        store_provider.data_dir().join("data").join(id.to_string())
+        store_provider.ensure_writable()?;
+
+        // リンクされたアセットの参照先には書き込まない
+        if let Some(AssetDataLocation::Linked { .. }) = store_provider.get_data_location(id).await {
+            return Err(format!("Cannot import files into a linked asset: {}", id));
+        }
+
+        let data_dir = store_provider.data_dir();
+
+        (
+            data_dir.join("data").join(id.to_string()),
+            data_dir.join("temp").join(Uuid::new_v4().to_string()),
+        )
     };
 
     let path = path.as_ref();
@@ -239,11 +339,32 @@ where
         return Err(format!("File or directory not found: {}", path.display()));
     }
 
-    fileutils::import_asset(path, &asset_data_dir, true, zip_extraction, |_, _| {})
-        .await
-        .map_err(|e| format!("Failed to import additional data for asset ({}): {}", id, e))?;
+    let report = fileutils::import_with_conflict_policy(
+        path,
+        &asset_data_dir,
+        &staging_dir,
+        zip_extraction,
+        filter,
+        limits,
+        policy,
+        |_, _| {},
+        |filename, invalid_password| prompt_zip_password(app_handle, filename, invalid_password),
+    )
+    .await
+    .map_err(|e| format!("Failed to import additional data for asset ({}): {}", id, e))?;
 
-    Ok(())
+    // ファイルを持たないアセットにファイルが追加された場合は、通常のアセットとして扱う
+    let store_provider = basic_store.lock().await;
+
+    if let Some(data_location) = store_provider.get_data_location(id).await {
+        if !data_location.has_local_data() {
+            store_provider
+                .set_data_location(id, AssetDataLocation::Local)
+                .await?;
+        }
+    }
+
+    Ok(report)
 }
 
 async fn import_files(
@@ -251,6 +372,9 @@ async fn import_files(
     dest: &PathBuf,
     progress_callback: impl Fn(f32, String),
     zip_extraction: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
+    app_handle: Option<&AppHandle>,
 ) -> Result<(), String> {
     if !dest.exists() {
         std::fs::create_dir_all(dest)
@@ -259,15 +383,35 @@ async fn import_files(
 
     let mut delete_on_drop = DeleteOnDrop::new(dest.clone());
 
-    fileutils::import_asset(src, dest, false, zip_extraction, progress_callback)
-        .await
-        .map_err(|e| format!("Failed to import asset: {:?}", e))?;
+    fileutils::import_asset(
+        src,
+        dest,
+        false,
+        zip_extraction,
+        filter,
+        limits,
+        progress_callback,
+        |filename, invalid_password| prompt_zip_password(app_handle, filename, invalid_password),
+    )
+    .await
+    .map_err(|e| format!("Failed to import asset: {:?}", e))?;
 
     delete_on_drop.mark_as_completed();
 
     Ok(())
 }
 
+async fn prompt_zip_password(
+    app_handle: Option<&AppHandle>,
+    filename: String,
+    invalid_password: bool,
+) -> Option<String> {
+    // app_handle is None only in test environment
+    let app_handle = app_handle?;
+
+    request_zip_password(app_handle, filename, invalid_password).await
+}
+
 async fn bind_temp_image(images_path: &PathBuf, temp_path_str: &str) -> Result<String, String> {
     let temp_image_path = images_path.clone().join(temp_path_str);
 
@@ -298,7 +442,7 @@ mod tests {
             std::fs::remove_dir_all(test_root_dir).unwrap();
         }
 
-        let provider = AssetStorage::create(&data_dir).unwrap();
+        let provider = Arc::new(Mutex::new(AssetStorage::create(&data_dir).unwrap()));
 
         std::fs::create_dir_all(format!("{data_dir}/images")).unwrap();
         std::fs::write(format!("{data_dir}/images/temp_image.png"), b"").unwrap();
@@ -313,6 +457,7 @@ mod tests {
             dependencies: vec![],
             created_at: 123456,
             published_at: Some(123456),
+            data_location: AssetDataLocation::Local,
         };
 
         let pre_avatar = PreAvatar {
@@ -339,11 +484,14 @@ mod tests {
             &provider,
             request,
             None,
+            Uuid::new_v4(),
             |provider: &'_ AssetStorage, asset: Avatar| {
                 Box::pin(async { provider.get_avatar_store().add_asset_and_save(asset).await })
             },
             true,
             false,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
         )
         .await
         .unwrap();
@@ -369,10 +517,200 @@ mod tests {
         assert!(std::fs::exists(avatar_json_path).unwrap());
 
         let registered_avatar = provider
+            .lock()
+            .await
             .get_avatar_store()
             .get_asset(id.clone())
             .await
             .unwrap();
         assert_eq!(avatar, registered_avatar);
     }
+
+    #[tokio::test]
+    async fn test_import_avatar_without_files() {
+        let data_dir = "test/temp/import-test/avatar-without-files";
+
+        if std::fs::exists(data_dir).unwrap() {
+            std::fs::remove_dir_all(data_dir).unwrap();
+        }
+
+        let provider = Arc::new(Mutex::new(AssetStorage::create(data_dir).unwrap()));
+
+        let description = AssetDescription {
+            name: "Test Asset".to_string(),
+            creator: "Test Creator".to_string(),
+            image_filename: None,
+            tags: vec![],
+            memo: None,
+            booth_item_id: Some(123456),
+            dependencies: vec![],
+            created_at: 123456,
+            published_at: None,
+            data_location: AssetDataLocation::Local,
+        };
+
+        let request = AssetImportRequest {
+            pre_asset: PreAvatar { description },
+            absolute_paths: vec![],
+            delete_source: false,
+        };
+
+        let avatar = import_asset(
+            &provider,
+            request,
+            None,
+            Uuid::new_v4(),
+            |provider: &'_ AssetStorage, asset: Avatar| {
+                Box::pin(async { provider.get_avatar_store().add_asset_and_save(asset).await })
+            },
+            true,
+            false,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            avatar.description.data_location,
+            AssetDataLocation::NotDownloaded
+        );
+        assert!(!std::fs::exists(format!("{data_dir}/data/{}", avatar.get_id())).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_import_additional_data_with_conflict() {
+        let test_root_dir = "test/temp/import-test/additional-data-conflict";
+        let data_dir = format!("{test_root_dir}/provider");
+        let src_dir = format!("{test_root_dir}/Package");
+
+        if std::fs::exists(test_root_dir).unwrap() {
+            std::fs::remove_dir_all(test_root_dir).unwrap();
+        }
+
+        let provider = Arc::new(Mutex::new(AssetStorage::create(&data_dir).unwrap()));
+        let id = Uuid::new_v4();
+
+        let asset_dir = format!("{data_dir}/data/{id}/Package");
+        std::fs::create_dir_all(&asset_dir).unwrap();
+        std::fs::write(format!("{asset_dir}/same.txt"), "same").unwrap();
+        std::fs::write(format!("{asset_dir}/changed.txt"), "existing").unwrap();
+
+        std::fs::create_dir_all(&src_dir).unwrap();
+        std::fs::write(format!("{src_dir}/same.txt"), "same").unwrap();
+        std::fs::write(format!("{src_dir}/changed.txt"), "incoming").unwrap();
+
+        let conflicts = fileutils::preview_import_conflicts(
+            &src_dir,
+            format!("{data_dir}/data/{id}"),
+            true,
+            &FileFilter::none(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| conflict.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Package/changed.txt", "Package/same.txt"]
+        );
+
+        let report = import_additional_data(
+            provider.clone(),
+            id,
+            &src_dir,
+            true,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
+            ConflictPolicy::CompareHash,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.len(), 2);
+        assert!(std::fs::exists(format!("{asset_dir}/changed (1).txt")).unwrap());
+        assert!(!std::fs::exists(format!("{asset_dir}/same (1).txt")).unwrap());
+        assert!(std::fs::exists(&src_dir).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_import_linked_avatar() {
+        let test_root_dir = "test/temp/import-test/linked-avatar";
+        let data_dir = format!("{test_root_dir}/provider");
+        let linked_dir = format!("{test_root_dir}/linked");
+
+        if std::fs::exists(test_root_dir).unwrap() {
+            std::fs::remove_dir_all(test_root_dir).unwrap();
+        }
+
+        std::fs::create_dir_all(&linked_dir).unwrap();
+        std::fs::write(format!("{linked_dir}/dummy.txt"), "dummy").unwrap();
+
+        let provider = Arc::new(Mutex::new(AssetStorage::create(&data_dir).unwrap()));
+
+        let linked_path = std::path::absolute(&linked_dir)
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+
+        let description = AssetDescription {
+            name: "Linked Asset".to_string(),
+            creator: "Test Creator".to_string(),
+            image_filename: None,
+            tags: vec![],
+            memo: None,
+            booth_item_id: None,
+            dependencies: vec![],
+            created_at: 123456,
+            published_at: None,
+            data_location: AssetDataLocation::Linked {
+                path: linked_path.clone(),
+            },
+        };
+
+        let request = AssetImportRequest {
+            pre_asset: PreAvatar { description },
+            absolute_paths: vec![],
+            delete_source: false,
+        };
+
+        let avatar = import_asset(
+            &provider,
+            request,
+            None,
+            Uuid::new_v4(),
+            |provider: &'_ AssetStorage, asset: Avatar| {
+                Box::pin(async { provider.get_avatar_store().add_asset_and_save(asset).await })
+            },
+            true,
+            false,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
+        )
+        .await
+        .unwrap();
+
+        let id = avatar.get_id();
+
+        assert!(!std::fs::exists(format!("{data_dir}/data/{id}")).unwrap());
+        assert_eq!(
+            provider.lock().await.resolve_asset_data_dir(id).await,
+            Some(PathBuf::from(linked_path))
+        );
+
+        let result = import_additional_data(
+            provider.clone(),
+            id,
+            format!("{linked_dir}/dummy.txt"),
+            true,
+            &FileFilter::none(),
+            &ExtractLimits::default(),
+            ConflictPolicy::Rename,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    }
 }
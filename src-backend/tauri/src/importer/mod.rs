@@ -1,3 +1,7 @@
+pub mod bulk;
+pub mod classifier;
+pub mod fileutils;
 pub mod import_wrapper;
-
-mod fileutils;
+pub mod password;
+pub mod purchase;
+pub mod queue;
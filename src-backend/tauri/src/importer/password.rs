@@ -0,0 +1,112 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tauri::{AppHandle, Manager, async_runtime::Mutex};
+use tauri_specta::Event;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::definitions::entities::ZipPasswordRequested;
+
+/// 入力を待つ最大の時間。ダイアログが失われた場合にインポートが止まり続けないようにする
+const PASSWORD_REQUEST_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// インポート中のタスクとフロントエンドの間でzipのパスワードを受け渡す
+pub struct ZipPasswordBroker {
+    pending: HashMap<Uuid, oneshot::Sender<Option<String>>>,
+}
+
+impl ZipPasswordBroker {
+    pub fn new() -> Self {
+        Self {
+            pending: HashMap::new(),
+        }
+    }
+
+    fn register(&mut self) -> (Uuid, oneshot::Receiver<Option<String>>) {
+        let (sender, receiver) = oneshot::channel();
+        let request_id = Uuid::new_v4();
+
+        self.pending.insert(request_id, sender);
+
+        (request_id, receiver)
+    }
+
+    pub fn resolve(&mut self, request_id: Uuid, password: Option<String>) -> Result<(), String> {
+        let sender = self
+            .pending
+            .remove(&request_id)
+            .ok_or(format!("Password request not found: {}", request_id))?;
+
+        sender
+            .send(password)
+            .map_err(|_| format!("Password request is no longer active: {}", request_id))
+    }
+}
+
+/// 待機が終わった (タスクのキャンセルで破棄された場合を含む) リクエストを取り除く
+struct PendingPasswordRequest {
+    app_handle: AppHandle,
+    request_id: Uuid,
+}
+
+impl Drop for PendingPasswordRequest {
+    fn drop(&mut self) {
+        let app_handle = self.app_handle.clone();
+        let request_id = self.request_id;
+
+        tauri::async_runtime::spawn(async move {
+            let broker = app_handle.state::<Arc<Mutex<ZipPasswordBroker>>>();
+            broker.lock().await.pending.remove(&request_id);
+        });
+    }
+}
+
+/// フロントエンドにパスワードの入力を要求し、入力されるまで待機する
+///
+/// 入力がキャンセルされた場合や、一定時間入力されなかった場合は None を返す
+pub async fn request_zip_password(
+    app_handle: &AppHandle,
+    filename: String,
+    invalid_password: bool,
+) -> Option<String> {
+    let broker = app_handle.state::<Arc<Mutex<ZipPasswordBroker>>>();
+    let (request_id, receiver) = broker.lock().await.register();
+
+    let _pending = PendingPasswordRequest {
+        app_handle: app_handle.clone(),
+        request_id,
+    };
+
+    let event = ZipPasswordRequested::new(request_id, filename, invalid_password);
+
+    if let Err(e) = event.emit(app_handle) {
+        log::error!("Failed to emit ZipPasswordRequested event: {}", e);
+        return None;
+    }
+
+    match tokio::time::timeout(PASSWORD_REQUEST_TIMEOUT, receiver).await {
+        Ok(result) => result.ok().flatten(),
+        Err(_) => {
+            log::warn!("Password request timed out: {}", request_id);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolve_password_request() {
+        let mut broker = ZipPasswordBroker::new();
+
+        let (request_id, receiver) = broker.register();
+        broker.resolve(request_id, Some("password".into())).unwrap();
+
+        assert_eq!(receiver.await.unwrap(), Some("password".into()));
+
+        // 解決済みのリクエストは再度解決できない
+        assert!(broker.resolve(request_id, None).is_err());
+    }
+}
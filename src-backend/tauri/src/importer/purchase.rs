@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+
+use booth::BoothPurchasedItem;
+use model::{AssetDataLocation, AssetDescription, AssetTrait, OtherAsset};
+use serde::{Deserialize, Serialize};
+use storage::asset_storage::AssetStorage;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BoothPurchaseHistoryEntry {
+    pub item: BoothPurchasedItem,
+    /// 同じ BOOTH のアイテムIDを持つアセットが既に登録されている
+    pub already_registered: bool,
+}
+
+/// 保存された BOOTH のライブラリ / 注文履歴を読み取り、登録済みかどうかを付与して返す
+pub fn read_purchase_history(
+    content: &str,
+    registered_booth_item_ids: &HashSet<u64>,
+) -> Vec<BoothPurchaseHistoryEntry> {
+    booth::parse_purchase_history(content)
+        .into_iter()
+        .map(|item| BoothPurchaseHistoryEntry {
+            already_registered: registered_booth_item_ids.contains(&item.id),
+            item,
+        })
+        .collect()
+}
+
+/// 購入済みアイテムを、ファイルを持たない「未ダウンロード」のアセットとして登録する
+///
+/// 既に同じ BOOTH のアイテムIDを持つアセットが登録されている場合はスキップする
+pub async fn register_not_downloaded_assets(
+    basic_store: &AssetStorage,
+    items: Vec<BoothPurchasedItem>,
+) -> Result<Vec<Uuid>, String> {
+    let mut registered_booth_item_ids = basic_store.get_used_booth_item_ids().await;
+    let mut ids = vec![];
+
+    for item in items {
+        if !registered_booth_item_ids.insert(item.id) {
+            log::info!("Skipping already registered BOOTH item (id = {})", item.id);
+            continue;
+        }
+
+        let description = AssetDescription {
+            name: item.name.unwrap_or_else(|| format!("BOOTH #{}", item.id)),
+            creator: item.shop_name.unwrap_or_default(),
+            image_filename: None,
+            tags: vec![],
+            memo: None,
+            booth_item_id: Some(item.id),
+            dependencies: vec![],
+            created_at: chrono::Local::now().timestamp_millis(),
+            published_at: None,
+            data_location: AssetDataLocation::NotDownloaded,
+        };
+
+        let asset = OtherAsset::create(description, String::new());
+        let id = asset.get_id();
+
+        basic_store
+            .get_other_asset_store()
+            .add_asset_and_save(asset)
+            .await?;
+
+        ids.push(id);
+    }
+
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_register_not_downloaded_assets() {
+        let data_dir = "test/temp/purchase-history/provider";
+
+        if std::fs::exists(data_dir).unwrap() {
+            std::fs::remove_dir_all(data_dir).unwrap();
+        }
+
+        let provider = AssetStorage::create(data_dir).unwrap();
+
+        let items = vec![
+            BoothPurchasedItem {
+                id: 123456,
+                name: Some("Sample Outfit".into()),
+                shop_name: Some("Sample Shop".into()),
+                shop_url: Some("https://sample-shop.booth.pm/".into()),
+            },
+            BoothPurchasedItem {
+                id: 234567,
+                name: None,
+                shop_name: None,
+                shop_url: None,
+            },
+        ];
+
+        let ids = register_not_downloaded_assets(&provider, items.clone())
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+
+        let first = provider
+            .get_other_asset_store()
+            .get_asset(ids[0])
+            .await
+            .unwrap();
+        assert_eq!(first.description.name, "Sample Outfit");
+        assert_eq!(first.description.creator, "Sample Shop");
+        assert_eq!(first.description.booth_item_id, Some(123456));
+        assert_eq!(
+            first.description.data_location,
+            AssetDataLocation::NotDownloaded
+        );
+
+        let second = provider
+            .get_other_asset_store()
+            .get_asset(ids[1])
+            .await
+            .unwrap();
+        assert_eq!(second.description.name, "BOOTH #234567");
+        assert_eq!(second.description.creator, "");
+
+        assert!(!std::fs::exists(format!("{data_dir}/data/{}", ids[0])).unwrap());
+
+        // 2 回目は登録済みのためスキップされる
+        let ids = register_not_downloaded_assets(&provider, items)
+            .await
+            .unwrap();
+        assert!(ids.is_empty());
+    }
+}
@@ -0,0 +1,522 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use filter::FileFilter;
+use model::{AssetDescription, preference::PreferenceStore};
+use serde::{Deserialize, Serialize};
+use storage::asset_storage::AssetStorage;
+use task::{TaskContainer, TaskStatus};
+use tauri::{AppHandle, Manager, async_runtime::Mutex};
+use tauri_specta::Event;
+use uuid::Uuid;
+use zip::ExtractLimits;
+
+use crate::definitions::import_request::{
+    AssetImportRequest, PreAsset, PreAvatar, PreAvatarWearable, PreOtherAsset, PreWorldObject,
+};
+
+use super::{
+    fileutils::{build_extract_limits, build_import_filter, execute_image_fixation},
+    import_wrapper::{
+        import_avatar, import_avatar_wearable, import_other_asset, import_world_object,
+    },
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+#[serde(tag = "type", content = "request")]
+pub enum QueuedImportRequest {
+    Avatar(AssetImportRequest<PreAvatar>),
+    AvatarWearable(AssetImportRequest<PreAvatarWearable>),
+    WorldObject(AssetImportRequest<PreWorldObject>),
+    OtherAsset(AssetImportRequest<PreOtherAsset>),
+}
+
+impl QueuedImportRequest {
+    fn description(&mut self) -> &mut AssetDescription {
+        match self {
+            QueuedImportRequest::Avatar(request) => request.pre_asset.description(),
+            QueuedImportRequest::AvatarWearable(request) => request.pre_asset.description(),
+            QueuedImportRequest::WorldObject(request) => request.pre_asset.description(),
+            QueuedImportRequest::OtherAsset(request) => request.pre_asset.description(),
+        }
+    }
+
+    /// 一時画像は起動時に削除されるため、キューに追加する時点で確定させておく
+    pub async fn fix_temp_image<P>(&mut self, images_dir: P) -> Result<(), String>
+    where
+        P: AsRef<Path>,
+    {
+        let description = self.description();
+
+        let Some(image_filename) = description.image_filename.as_ref() else {
+            return Ok(());
+        };
+
+        let image_path = images_dir.as_ref().join(image_filename);
+
+        if let Some(new_filename) = execute_image_fixation(&image_path).await? {
+            description.image_filename = Some(new_filename);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+pub enum ImportQueueItemStatus {
+    Pending,
+    Running,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportQueueItem {
+    pub id: Uuid,
+    pub request: QueuedImportRequest,
+    pub status: ImportQueueItemStatus,
+    pub task_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, specta::Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportQueueUpdated {
+    pub items: Vec<ImportQueueItem>,
+}
+
+/// インポート待ちのリクエストを保持し、完了するまでファイルに保存する
+pub struct ImportQueue {
+    path: PathBuf,
+    items: Vec<ImportQueueItem>,
+}
+
+impl ImportQueue {
+    pub fn load_or_default(path: PathBuf) -> Self {
+        if !path.exists() {
+            return Self {
+                path,
+                items: vec![],
+            };
+        }
+
+        let items: Vec<ImportQueueItem> = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|e| {
+                log::error!("Failed to parse import queue file: {}", e);
+                vec![]
+            }),
+            Err(e) => {
+                log::error!("Failed to open import queue file: {}", e);
+                vec![]
+            }
+        };
+
+        // 前回の終了時に実行中だったものは最初からやり直す
+        let items = items
+            .into_iter()
+            .map(|mut item| {
+                if item.status == ImportQueueItemStatus::Running {
+                    item.status = ImportQueueItemStatus::Pending;
+                    item.task_id = None;
+                }
+                item
+            })
+            .collect();
+
+        Self { path, items }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string(&self.items)
+            .map_err(|e| format!("Failed to serialize import queue: {}", e))?;
+
+        // 書き込み途中で終了してもキューが失われないよう、一時ファイルから置き換える
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, content)
+            .map_err(|e| format!("Failed to write import queue to file: {}", e))?;
+        std::fs::rename(&temp_path, &self.path)
+            .map_err(|e| format!("Failed to replace import queue file: {}", e))
+    }
+
+    pub fn items(&self) -> &Vec<ImportQueueItem> {
+        &self.items
+    }
+
+    pub fn enqueue(&mut self, request: QueuedImportRequest) -> Uuid {
+        let id = Uuid::new_v4();
+
+        self.items.push(ImportQueueItem {
+            id,
+            request,
+            status: ImportQueueItemStatus::Pending,
+            task_id: None,
+            error: None,
+        });
+
+        id
+    }
+
+    /// 同時実行数に空きがある分だけ待機中のアイテムを実行中にして返す
+    fn start_next(&mut self, concurrency: usize) -> Vec<ImportQueueItem> {
+        let running = self
+            .items
+            .iter()
+            .filter(|item| item.status == ImportQueueItemStatus::Running)
+            .count();
+
+        self.items
+            .iter_mut()
+            .filter(|item| item.status == ImportQueueItemStatus::Pending)
+            .take(concurrency.saturating_sub(running))
+            .map(|item| {
+                item.status = ImportQueueItemStatus::Running;
+                item.clone()
+            })
+            .collect()
+    }
+
+    fn set_task_id(&mut self, id: Uuid, task_id: Uuid) {
+        if let Some(item) = self.items.iter_mut().find(|item| item.id == id) {
+            item.task_id = Some(task_id);
+        }
+    }
+
+    /// 成功したアイテムは取り除き、失敗したアイテムは再試行できるように残す
+    ///
+    /// キューに含まれないタスクの場合は false を返す
+    fn finish(&mut self, task_id: Uuid, error: Option<String>) -> bool {
+        let Some(index) = self
+            .items
+            .iter()
+            .position(|item| item.task_id == Some(task_id))
+        else {
+            return false;
+        };
+
+        match error {
+            None => {
+                self.items.remove(index);
+            }
+            Some(error) => {
+                let item = &mut self.items[index];
+                item.status = ImportQueueItemStatus::Failed;
+                item.error = Some(error);
+            }
+        }
+
+        true
+    }
+
+    pub fn retry(&mut self, id: Uuid) -> Result<(), String> {
+        let item = self
+            .items
+            .iter_mut()
+            .find(|item| item.id == id)
+            .ok_or(format!("Import queue item not found: {}", id))?;
+
+        if item.status != ImportQueueItemStatus::Failed {
+            return Err(format!("Import queue item is not failed: {}", id));
+        }
+
+        item.status = ImportQueueItemStatus::Pending;
+        item.task_id = None;
+        item.error = None;
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: Uuid) -> Result<(), String> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| item.id == id)
+            .ok_or(format!("Import queue item not found: {}", id))?;
+
+        if self.items[index].status == ImportQueueItemStatus::Running {
+            return Err(format!("Import queue item is running: {}", id));
+        }
+
+        self.items.remove(index);
+        Ok(())
+    }
+}
+
+/// 同時実行数の上限まで待機中のアイテムのインポートを開始する
+pub async fn process_import_queue(app_handle: &AppHandle) -> Result<(), String> {
+    let (concurrency, zip_extraction, use_trash_bin, filter, limits) = {
+        let preference = app_handle.state::<Arc<Mutex<PreferenceStore>>>();
+        let preference = preference.lock().await;
+
+        (
+            preference.import_concurrency.max(1) as usize,
+            preference.zip_extraction,
+            preference.use_trash_bin,
+            build_import_filter(&preference)?,
+            build_extract_limits(&preference),
+        )
+    };
+
+    let basic_store = app_handle.state::<Arc<Mutex<AssetStorage>>>();
+    let task_container = app_handle.state::<Arc<Mutex<TaskContainer>>>();
+    let queue = app_handle.state::<Arc<Mutex<ImportQueue>>>();
+
+    let items = {
+        // タスクの完了処理がキューを参照する前に task_id を記録するため、ロックを保持したまま開始する
+        let mut queue = queue.lock().await;
+
+        for item in queue.start_next(concurrency) {
+            let basic_store = (*basic_store).clone();
+            let app_handle = app_handle.clone();
+            let filter = filter.clone();
+            let limits = limits.clone();
+
+            let task_id = task_container
+                .lock()
+                .await
+                .run_with_id(move |task_id| async move {
+                    let result = execute_queued_import(
+                        &basic_store,
+                        item.request,
+                        &app_handle,
+                        task_id,
+                        zip_extraction,
+                        use_trash_bin,
+                        &filter,
+                        &limits,
+                    )
+                    .await;
+
+                    if let Err(e) = &result {
+                        log::error!("Failed to import queued asset: {}", e);
+                    }
+
+                    result
+                })?;
+
+            queue.set_task_id(item.id, task_id);
+        }
+
+        queue.save()?;
+        queue.items().clone()
+    };
+
+    emit_queue_updated(app_handle, items);
+    Ok(())
+}
+
+/// TaskContainer から通知されたタスクの終了をキューに反映し、次のアイテムを開始する
+pub async fn on_task_finished(app_handle: &AppHandle, task_id: Uuid, status: TaskStatus) {
+    let Some(queue) = app_handle.try_state::<Arc<Mutex<ImportQueue>>>() else {
+        return;
+    };
+
+    let error = match status {
+        TaskStatus::Completed => None,
+        TaskStatus::Running => return,
+        TaskStatus::Cancelled => Some("Cancelled".to_string()),
+        TaskStatus::Failed => {
+            let task_container = app_handle.state::<Arc<Mutex<TaskContainer>>>();
+            let task = task_container.lock().await.get(&task_id).await;
+
+            let error = match task {
+                Some(task) => task.lock().await.get_error().await,
+                None => None,
+            };
+
+            Some(error.unwrap_or("Unknown error".to_string()))
+        }
+    };
+
+    {
+        let mut queue = queue.lock().await;
+
+        if !queue.finish(task_id, error) {
+            return;
+        }
+
+        if let Err(e) = queue.save() {
+            log::error!("{}", e);
+        }
+    }
+
+    if let Err(e) = process_import_queue(app_handle).await {
+        log::error!("Failed to process import queue: {}", e);
+    }
+}
+
+pub fn emit_queue_updated(app_handle: &AppHandle, items: Vec<ImportQueueItem>) {
+    if let Err(e) = (ImportQueueUpdated { items }).emit(app_handle) {
+        log::error!("Failed to emit ImportQueueUpdated event: {}", e);
+    }
+}
+
+async fn execute_queued_import(
+    basic_store: &Arc<Mutex<AssetStorage>>,
+    request: QueuedImportRequest,
+    app_handle: &AppHandle,
+    task_id: Uuid,
+    zip_extraction: bool,
+    use_trash_bin: bool,
+    filter: &FileFilter,
+    limits: &ExtractLimits,
+) -> Result<(), String> {
+    match request {
+        QueuedImportRequest::Avatar(request) => import_avatar(
+            basic_store,
+            request,
+            app_handle,
+            task_id,
+            zip_extraction,
+            use_trash_bin,
+            filter,
+            limits,
+        )
+        .await
+        .map(|_| ()),
+        QueuedImportRequest::AvatarWearable(request) => import_avatar_wearable(
+            basic_store,
+            request,
+            app_handle,
+            task_id,
+            zip_extraction,
+            use_trash_bin,
+            filter,
+            limits,
+        )
+        .await
+        .map(|_| ()),
+        QueuedImportRequest::WorldObject(request) => import_world_object(
+            basic_store,
+            request,
+            app_handle,
+            task_id,
+            zip_extraction,
+            use_trash_bin,
+            filter,
+            limits,
+        )
+        .await
+        .map(|_| ()),
+        QueuedImportRequest::OtherAsset(request) => import_other_asset(
+            basic_store,
+            request,
+            app_handle,
+            task_id,
+            zip_extraction,
+            use_trash_bin,
+            filter,
+            limits,
+        )
+        .await
+        .map(|_| ()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use model::{AssetDataLocation, AssetDescription};
+
+    use super::*;
+
+    fn create_request(name: &str) -> QueuedImportRequest {
+        QueuedImportRequest::Avatar(AssetImportRequest {
+            pre_asset: PreAvatar {
+                description: AssetDescription {
+                    name: name.to_string(),
+                    creator: "Test Creator".to_string(),
+                    image_filename: None,
+                    tags: vec![],
+                    memo: None,
+                    booth_item_id: None,
+                    dependencies: vec![],
+                    created_at: 0,
+                    published_at: None,
+                    data_location: AssetDataLocation::Local,
+                },
+            },
+            absolute_paths: vec![],
+            delete_source: false,
+        })
+    }
+
+    #[test]
+    fn test_import_queue_concurrency_and_retry() {
+        let dir = PathBuf::from("test/temp/import_queue/concurrency");
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut queue = ImportQueue::load_or_default(dir.join("import_queue.json"));
+
+        let first = queue.enqueue(create_request("first"));
+        let second = queue.enqueue(create_request("second"));
+        let third = queue.enqueue(create_request("third"));
+
+        let started = queue.start_next(2);
+        assert_eq!(started.len(), 2);
+        assert_eq!(started[0].id, first);
+        assert_eq!(started[1].id, second);
+
+        // 同時実行数の上限に達している間は開始しない
+        assert!(queue.start_next(2).is_empty());
+
+        let (first_task, second_task) = (Uuid::new_v4(), Uuid::new_v4());
+        queue.set_task_id(first, first_task);
+        queue.set_task_id(second, second_task);
+
+        assert!(queue.finish(first_task, None));
+        assert!(queue.finish(second_task, Some("error".to_string())));
+        assert!(!queue.finish(Uuid::new_v4(), None));
+
+        assert_eq!(queue.items().len(), 2);
+        assert_eq!(queue.items()[0].status, ImportQueueItemStatus::Failed);
+        assert_eq!(queue.items()[0].error, Some("error".to_string()));
+
+        let started = queue.start_next(2);
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].id, third);
+
+        queue.retry(second).unwrap();
+        assert!(queue.retry(third).is_err());
+
+        let started = queue.start_next(2);
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].id, second);
+        assert!(started[0].error.is_none());
+    }
+
+    #[test]
+    fn test_import_queue_persistence() {
+        let dir = PathBuf::from("test/temp/import_queue/persistence");
+
+        if std::fs::exists(&dir).unwrap() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("import_queue.json");
+        let mut queue = ImportQueue::load_or_default(path.clone());
+
+        let running = queue.enqueue(create_request("running"));
+        let pending = queue.enqueue(create_request("pending"));
+
+        queue.start_next(1);
+        queue.set_task_id(running, Uuid::new_v4());
+        queue.save().unwrap();
+        assert!(!std::fs::exists(path.with_extension("json.tmp")).unwrap());
+
+        let queue = ImportQueue::load_or_default(path);
+        assert_eq!(queue.items().len(), 2);
+
+        // 実行中だったアイテムは待機中として再開される
+        assert_eq!(queue.items()[0].id, running);
+        assert_eq!(queue.items()[0].status, ImportQueueItemStatus::Pending);
+        assert!(queue.items()[0].task_id.is_none());
+        assert_eq!(queue.items()[1].id, pending);
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+/// 監視フォルダで検出された、インポート待ちのファイル
+#[derive(Serialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingImport {
+    pub id: Uuid,
+    pub path: PathBuf,
+    pub name: String,
+    pub booth_item_id: Option<u64>,
+    pub detected_at: i64,
+}
+
+impl PendingImport {
+    pub fn from_path(path: PathBuf) -> Self {
+        let booth_item_id = path
+            .file_name()
+            .and_then(|name| watcher::extract_booth_item_id(&name.to_string_lossy()));
+
+        Self {
+            id: Uuid::new_v4(),
+            name: watcher::suggest_asset_name(&path),
+            path,
+            booth_item_id,
+            detected_at: chrono::Local::now().timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingImportAdded {
+    pub entry: PendingImport,
+}
+
+impl PendingImportAdded {
+    pub fn new(entry: PendingImport) -> Self {
+        Self { entry }
+    }
+}
@@ -0,0 +1,111 @@
+mod definitions;
+
+use std::{path::PathBuf, sync::Arc};
+
+use tauri::{AppHandle, Manager, async_runtime::Mutex};
+use tauri_specta::Event;
+use uuid::Uuid;
+use watcher::FolderWatcher;
+
+pub use definitions::{PendingImport, PendingImportAdded};
+
+/// 監視フォルダで検出されたファイルを、ユーザーが確認するまで保持する
+#[derive(Default)]
+pub struct PendingImportInbox {
+    entries: Vec<PendingImport>,
+    watcher: Option<FolderWatcher>,
+}
+
+impl PendingImportInbox {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            watcher: None,
+        }
+    }
+
+    pub fn entries(&self) -> &Vec<PendingImport> {
+        &self.entries
+    }
+
+    /// 同じパスのファイルが既に存在する場合は追加しない
+    pub fn add(&mut self, path: PathBuf) -> Option<PendingImport> {
+        if self.entries.iter().any(|entry| entry.path == path) {
+            return None;
+        }
+
+        let entry = PendingImport::from_path(path);
+        self.entries.push(entry.clone());
+
+        Some(entry)
+    }
+
+    pub fn dismiss(&mut self, id: Uuid) -> Result<(), String> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or(format!("Pending import not found: {}", id))?;
+
+        self.entries.remove(index);
+        Ok(())
+    }
+
+    /// 監視対象のフォルダを設定し直す (tokio ランタイム上で呼び出す必要がある)
+    pub fn restart_watcher(&mut self, dirs: &[PathBuf], app_handle: AppHandle) {
+        self.watcher = None;
+
+        if dirs.is_empty() {
+            return;
+        }
+
+        let result = FolderWatcher::start(dirs, move |path| {
+            let app_handle = app_handle.clone();
+
+            tauri::async_runtime::spawn(async move {
+                let inbox = app_handle.state::<Arc<Mutex<PendingImportInbox>>>();
+                let entry = inbox.lock().await.add(path);
+
+                if let Some(entry) = entry {
+                    if let Err(e) = PendingImportAdded::new(entry).emit(&app_handle) {
+                        log::error!("Failed to emit PendingImportAdded event: {}", e);
+                    }
+                }
+            });
+        });
+
+        match result {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => log::error!("Failed to start watching folders: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_dismiss_pending_import() {
+        let mut inbox = PendingImportInbox::new();
+
+        let entry = inbox
+            .add(PathBuf::from("Downloads/booth_6641548.zip"))
+            .unwrap();
+
+        assert_eq!(entry.name, "booth_6641548");
+        assert_eq!(entry.booth_item_id, Some(6641548));
+
+        // 同じファイルは重複して追加しない
+        assert!(
+            inbox
+                .add(PathBuf::from("Downloads/booth_6641548.zip"))
+                .is_none()
+        );
+        assert_eq!(inbox.entries().len(), 1);
+
+        inbox.dismiss(entry.id).unwrap();
+        assert!(inbox.entries().is_empty());
+        assert!(inbox.dismiss(entry.id).is_err());
+    }
+}
@@ -1,37 +1,56 @@
 use std::{path::PathBuf, sync::Arc};
 
-use booth::{BoothFetcher, PximgResolver};
+use booth::{BoothFetcher, BoothUpdateTracker, PximgResolver};
+use booth_update::BoothItemUpdatesChecked;
 use command::generate_tauri_specta_builder;
 use deep_link::{
     definitions::{AddAssetDeepLink, StartupDeepLinkStore},
     execute_deep_links, parse_args_to_deep_links,
 };
-use definitions::entities::{InitialSetup, LoadResult, ProgressEvent};
+use definitions::entities::{
+    AdditionalImportReported, InitialSetup, IntegrityCheckReported, LibraryLockStatusChanged,
+    LoadResult, ProgressEvent, ZipPasswordRequested,
+};
 use file::modify_guard::{self, FileTransferGuard};
+use importer::{
+    bulk::{BulkImportPlanCreated, BulkImportProgress},
+    password::ZipPasswordBroker,
+    queue::{ImportQueue, ImportQueueUpdated},
+};
+use inbox::{PendingImportAdded, PendingImportInbox};
 use language::LocalizationData;
+use metadata_sync::{MetadataReloaded, MetadataSync};
 use model::preference::{PreferenceStore, UpdateChannel};
 use state::StateHandler;
 use statistics::{AssetVolumeEstimatedEvent, AssetVolumeStatisticsCache};
-use storage::{asset_storage::AssetStorage, delete::delete_temporary_images};
+use storage::{
+    asset_storage::AssetStorage, delete::delete_temporary_images, lock::LOCK_REFRESH_INTERVAL_MS,
+};
 use task::{TaskContainer, TaskStatusChanged};
 use tauri::{AppHandle, Manager, async_runtime::Mutex};
 use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_window_state::StateFlags;
 use tauri_specta::{Event, collect_events};
 use updater::update_handler::{UpdateHandler, UpdateProgress};
+use uuid::Uuid;
 
 #[cfg(debug_assertions)]
 use specta_typescript::{BigIntExportBehavior, Typescript};
 
 mod adapter;
+mod booth_refresh;
+mod booth_update;
 mod command;
 mod deep_link;
 mod definitions;
 mod importer;
+mod inbox;
+mod metadata_sync;
 mod statistics;
 mod updater;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+const BOOTH_UPDATE_CHECK_DELAY_MS: u64 = 60 * 1000;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -41,6 +60,16 @@ pub fn run() {
         AddAssetDeepLink,
         UpdateProgress,
         AssetVolumeEstimatedEvent,
+        ZipPasswordRequested,
+        PendingImportAdded,
+        ImportQueueUpdated,
+        BulkImportProgress,
+        BulkImportPlanCreated,
+        AdditionalImportReported,
+        IntegrityCheckReported,
+        LibraryLockStatusChanged,
+        MetadataReloaded,
+        BoothItemUpdatesChecked,
     ]);
 
     #[cfg(debug_assertions)]
@@ -82,6 +111,9 @@ pub fn run() {
         .manage(Mutex::new(BoothFetcher::new(VERSION)))
         .manage(arc_mutex(LocalizationData::default()))
         .manage(arc_mutex(AssetVolumeStatisticsCache::new()))
+        .manage(arc_mutex(ZipPasswordBroker::new()))
+        .manage(arc_mutex(PendingImportInbox::new()))
+        .manage(arc_mutex(MetadataSync::new()))
         .setup(move |app| {
             logging::initialize_logger(app.path().app_log_dir().unwrap());
             builder.mount_events(app);
@@ -96,6 +128,11 @@ pub fn run() {
                     if let Err(e) = TaskStatusChanged::new(id, status).emit(&app_handle) {
                         log::error!("Failed to emit TaskStatusChanged event: {}", e);
                     }
+
+                    let app_handle = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        importer::queue::on_task_finished(&app_handle, id, status).await;
+                    });
                 },
             ))));
 
@@ -126,6 +163,8 @@ pub fn run() {
 
             let preference_file_path = app_local_data_dir.join("preference.json");
             let state_file_path = app_local_data_dir.join("state.json");
+            let import_queue_file_path = app_local_data_dir.join("import_queue.json");
+            let booth_update_file_path = app_local_data_dir.join("booth_updates.json");
 
             app.manage(arc_mutex(InitialSetup::new(preference_file_path)));
 
@@ -140,35 +179,54 @@ pub fn run() {
                 }
             };
 
+            initialize_booth_fetcher(app.handle(), &app_local_data_dir, &pref_store);
+
             let data_dir = pref_store.get_data_dir().clone();
             let update_channel = pref_store.update_channel.clone();
+            let watch_dirs = pref_store.watch_dirs.clone();
             let state_handler = StateHandler::load_or_default(state_file_path);
 
             let pximg_resolver = PximgResolver::new(data_dir.join("images"), VERSION);
 
             app.manage(arc_mutex(pref_store));
             app.manage(arc_mutex(pximg_resolver));
+            app.manage(arc_mutex(BoothUpdateTracker::load_or_default(
+                booth_update_file_path,
+            )));
             app.manage(arc_mutex(get_update_handler(
                 app.handle().clone(),
                 &update_channel,
             )));
             app.manage(arc_mutex(state_handler));
 
-            let store_provider = match load_store_provider(&data_dir, &app_local_data_dir) {
-                Ok(store_provider) => store_provider,
-                Err(err) => {
-                    log::error!("{}", err);
-                    app.manage(LoadResult::error(true, err));
+            start_watching_folders(app.handle(), &watch_dirs);
 
-                    // Err を返すとアプリケーションが終了してしまうため Ok を返す
-                    return Ok(());
-                }
-            };
+            // 同じライブラリを開いている他のインスタンスと区別するための ID
+            let instance_id = Uuid::new_v4();
+
+            let store_provider =
+                match load_store_provider(&data_dir, &app_local_data_dir, instance_id) {
+                    Ok(store_provider) => store_provider,
+                    Err(err) => {
+                        log::error!("{}", err);
+                        app.manage(LoadResult::error(true, err));
+
+                        // Err を返すとアプリケーションが終了してしまうため Ok を返す
+                        return Ok(());
+                    }
+                };
 
             app.manage(arc_mutex(store_provider));
+            app.manage(arc_mutex(ImportQueue::load_or_default(
+                import_queue_file_path,
+            )));
             app.manage(LoadResult::success());
 
             cleanup_images_dir(&data_dir);
+            resume_import_queue(app.handle());
+            start_watching_metadata(app.handle(), &data_dir);
+            start_library_lock_heartbeat(app.handle());
+            start_booth_update_check(app.handle());
 
             Ok(())
         })
@@ -227,6 +285,7 @@ fn load_preference_store(
 fn load_store_provider(
     data_dir: &PathBuf,
     app_local_dir: &PathBuf,
+    instance_id: Uuid,
 ) -> Result<AssetStorage, String> {
     let result = AssetStorage::create(data_dir);
 
@@ -237,6 +296,11 @@ fn load_store_provider(
         }
     };
 
+    // 他のインスタンスがロックを保持している場合は、読み取り専用で開く
+    if let Err(err) = store_provider.acquire_lock(instance_id, false) {
+        return Err(format!("Failed to acquire library lock: {}", err));
+    }
+
     let store_provider_ref = &mut store_provider;
 
     let metadata_backup_dir = app_local_dir.join("backups").join("metadata");
@@ -283,6 +347,101 @@ async fn migrate_legacy_backup_dir(
     Ok(())
 }
 
+fn initialize_booth_fetcher(
+    app: &AppHandle,
+    app_local_data_dir: &PathBuf,
+    preference: &PreferenceStore,
+) {
+    let booth_fetcher = app.state::<Mutex<BoothFetcher>>();
+    let cache_path = app_local_data_dir.join("booth_cache.json");
+
+    tauri::async_runtime::block_on(async move {
+        let mut booth_fetcher = booth_fetcher.lock().await;
+
+        booth_fetcher.use_persistent_cache(cache_path, (&preference.booth_cache).into());
+        booth_fetcher.set_offline_mode(preference.booth_offline_mode);
+        booth_fetcher.set_language(preference.language.booth_lang_code());
+    });
+}
+
+fn start_watching_folders(app: &AppHandle, watch_dirs: &[PathBuf]) {
+    let inbox = app.state::<Arc<Mutex<PendingImportInbox>>>();
+
+    tauri::async_runtime::block_on(async move {
+        inbox.lock().await.restart_watcher(watch_dirs, app.clone());
+    });
+}
+
+/// 起動処理の妨げにならないよう、少し待ってから BOOTH での更新を確認する
+fn start_booth_update_check(app: &AppHandle) {
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(
+            BOOTH_UPDATE_CHECK_DELAY_MS,
+        ))
+        .await;
+
+        if let Err(e) = booth_update::check_booth_item_updates(&app).await {
+            log::error!("Failed to check BOOTH item updates: {}", e);
+        }
+    });
+}
+
+/// ロックを定期的に更新し、他のインスタンスによるメタデータの変更を読み込む
+fn start_library_lock_heartbeat(app: &AppHandle) {
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(LOCK_REFRESH_INTERVAL_MS)).await;
+
+            let status = {
+                let basic_store = app.state::<Arc<Mutex<AssetStorage>>>();
+                let mut basic_store = basic_store.lock().await;
+
+                let previous = basic_store.lock_status();
+
+                match basic_store.refresh_lock() {
+                    Ok(status) if status != previous => Some(status),
+                    Ok(_) => None,
+                    Err(e) => {
+                        log::error!("Failed to refresh library lock: {}", e);
+                        None
+                    }
+                }
+            };
+
+            // ファイルの監視が届かないネットワークドライブもあるため、定期的にも確認する
+            metadata_sync::reload_modified_metadata(&app).await;
+
+            let Some(status) = status else {
+                continue;
+            };
+
+            if let Err(e) = LibraryLockStatusChanged::new(status).emit(&app) {
+                log::error!("Failed to emit LibraryLockStatusChanged event: {}", e);
+            }
+        }
+    });
+}
+
+fn start_watching_metadata(app: &AppHandle, data_dir: &PathBuf) {
+    tauri::async_runtime::block_on(async move {
+        metadata_sync::restart_metadata_watcher(app, data_dir).await;
+    });
+}
+
+fn resume_import_queue(app: &AppHandle) {
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = importer::queue::process_import_queue(&app).await {
+            log::error!("Failed to resume import queue: {}", e);
+        }
+    });
+}
+
 fn cleanup_images_dir(data_dir: &PathBuf) {
     tauri::async_runtime::block_on(async move {
         if let Err(e) = delete_temporary_images(data_dir).await {
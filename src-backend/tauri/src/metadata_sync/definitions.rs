@@ -0,0 +1,15 @@
+use serde::Serialize;
+use storage::definitions::ExternalChangeReport;
+
+/// 同期ツールや他のインスタンスによるメタデータの変更を読み込んだことを通知する
+#[derive(Serialize, Debug, Clone, specta::Type, tauri_specta::Event)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataReloaded {
+    pub report: ExternalChangeReport,
+}
+
+impl MetadataReloaded {
+    pub fn new(report: ExternalChangeReport) -> Self {
+        Self { report }
+    }
+}
@@ -0,0 +1,75 @@
+mod definitions;
+
+use std::{path::Path, sync::Arc};
+
+use storage::asset_storage::AssetStorage;
+use tauri::{AppHandle, Manager, async_runtime::Mutex};
+use tauri_specta::Event;
+use watcher::MetadataWatcher;
+
+pub use definitions::MetadataReloaded;
+
+/// `metadata/` を監視し、外部で変更されたメタデータを読み込み直す
+#[derive(Default)]
+pub struct MetadataSync {
+    watcher: Option<MetadataWatcher>,
+}
+
+impl MetadataSync {
+    pub fn new() -> Self {
+        Self { watcher: None }
+    }
+
+    /// 監視対象のデータフォルダを設定し直す (tokio ランタイム上で呼び出す必要がある)
+    pub fn restart_watcher(&mut self, data_dir: &Path, app_handle: AppHandle) {
+        self.watcher = None;
+
+        let result = MetadataWatcher::start(&data_dir.join("metadata"), move || {
+            let app_handle = app_handle.clone();
+
+            tauri::async_runtime::spawn(async move {
+                reload_modified_metadata(&app_handle).await;
+            });
+        });
+
+        match result {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => log::error!("Failed to start watching metadata: {}", e),
+        }
+    }
+}
+
+/// データフォルダが変更された際に呼び出し、新しい `metadata/` の監視を始める
+pub async fn restart_metadata_watcher(app_handle: &AppHandle, data_dir: &Path) {
+    let metadata_sync = app_handle.state::<Arc<Mutex<MetadataSync>>>();
+
+    metadata_sync
+        .lock()
+        .await
+        .restart_watcher(data_dir, app_handle.clone());
+}
+
+/// 外部で変更されたメタデータを読み込み直し、変更があれば通知する
+pub async fn reload_modified_metadata(app_handle: &AppHandle) {
+    let basic_store = app_handle.state::<Arc<Mutex<AssetStorage>>>();
+    let result = basic_store.lock().await.reload_if_modified().await;
+
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            // 同期ツールが書き込み中の場合は読み込めないため、次の変更で再試行する
+            log::warn!("Failed to reload modified metadata: {}", e);
+            return;
+        }
+    };
+
+    if report.is_empty() {
+        return;
+    }
+
+    log::info!("Reloaded externally modified metadata: {:?}", report);
+
+    if let Err(e) = MetadataReloaded::new(report).emit(app_handle) {
+        log::error!("Failed to emit MetadataReloaded event: {}", e);
+    }
+}
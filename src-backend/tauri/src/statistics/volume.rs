@@ -100,6 +100,11 @@ async fn calculate_volume_for_assets<T: AssetTrait>(
     let mut result = Vec::new();
 
     for entry in assets {
+        // ファイルを持たないアセットは集計の対象外
+        if !entry.get_description().data_location.has_local_data() {
+            continue;
+        }
+
         let asset_path = data_dir.join(entry.get_id().to_string());
         let size = calculate_asset_volume_for_path(asset_path).await?;
 